@@ -0,0 +1,20 @@
+fn main() {
+    #[cfg(feature = "ffi")]
+    write_header();
+}
+
+/// Regenerates `include/maze.h` from the `ffi` module
+#[cfg(feature = "ffi")]
+fn write_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(cbindgen::Config::from_root_or_default(&crate_dir))
+        .generate()
+        .expect("could not generate include/maze.h")
+        .write_to_file(format!("{crate_dir}/include/maze.h"));
+}
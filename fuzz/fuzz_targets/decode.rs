@@ -0,0 +1,13 @@
+#![no_main]
+
+//! Feeds arbitrary bytes to the dat decoder, which has to reject them with an error instead of
+//! panicking.
+//!
+//! Run with `cargo fuzz run decode` from the repository root.
+
+use libfuzzer_sys::fuzz_target;
+use maze::formats::dat;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = dat::decode(data);
+});
@@ -0,0 +1,34 @@
+#![no_main]
+
+//! Stops generating part way like `--fill`, leaving uncarved cells anywhere including the last
+//! one, and checks that the maze survives writing to the dat format and reading back at its full
+//! size.
+//!
+//! Run with `cargo fuzz run fill` from the repository root.
+
+use libfuzzer_sys::fuzz_target;
+use maze::formats::dat;
+use maze::maze::generators::{Wilson, SEED_LIMIT};
+use maze::maze::{GenStep, Maze, MazeGenerator};
+
+fuzz_target!(|data: [u8; 11]| {
+    let [width, height, fill, seed @ ..] = data;
+    let bounds = (width as usize % 24 + 2, height as usize % 24 + 2);
+    let fill = fill as f32 / 255.;
+    let seed = u64::from_be_bytes(seed) % SEED_LIMIT;
+
+    let mut maze = Maze::new(bounds.0 as u16, bounds.1 as u16);
+    let mut generator = Wilson::with_seed(bounds, seed);
+
+    // The same early stop as `Run::step`
+    while let GenStep::InProgress { remaining, total } = generator.step(&mut maze) {
+        if (total - remaining.min(total)) as f32 >= fill * total as f32 {
+            break;
+        }
+    }
+
+    let decoded = dat::decode(&dat::encode(&maze).unwrap()).unwrap();
+
+    assert_eq!(decoded.get_bounds(), bounds);
+    assert!(decoded.cells().eq(maze.cells()));
+});
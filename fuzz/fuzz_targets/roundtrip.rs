@@ -0,0 +1,36 @@
+#![no_main]
+
+//! Builds a maze from arbitrary bytes, with any cells left uncarved, and checks that writing it
+//! to the dat format and reading it back gives the same maze, down to its size.
+//!
+//! Run with `cargo fuzz run roundtrip` from the repository root.
+
+use libfuzzer_sys::fuzz_target;
+use maze::formats::dat;
+use maze::maze::Maze;
+use maze::Direction;
+
+fuzz_target!(|data: &[u8]| {
+    let [width, height, carves @ ..] = data else {
+        return;
+    };
+    let (width, height) = (*width as usize % 32 + 1, *height as usize % 32 + 1);
+    let mut maze = Maze::new(width as u16, height as u16);
+
+    // Each byte opens the right or bottom wall of a cell, if there is one
+    for &byte in carves {
+        let i = byte as usize >> 1;
+        let (x, y) = (i % width, i / width % height);
+
+        match byte & 1 {
+            0 if x + 1 < width => maze.carve(x, y, Direction::RIGHT),
+            1 if y + 1 < height => maze.carve(x, y, Direction::DOWN),
+            _ => {}
+        }
+    }
+
+    let decoded = dat::decode(&dat::encode(&maze).unwrap()).unwrap();
+
+    assert_eq!(decoded.get_bounds(), maze.get_bounds());
+    assert!(decoded.cells().eq(maze.cells()));
+});
@@ -0,0 +1,148 @@
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#ifndef MAZE_H
+#define MAZE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum MazeGeneratorKind {
+  MAZE_GENERATOR_KIND_WILSON,
+  MAZE_GENERATOR_KIND_RANDOM_DFS,
+} MazeGeneratorKind;
+
+typedef enum MazeAlgorithm {
+  MAZE_ALGORITHM_BFS,
+  MAZE_ALGORITHM_DFS,
+  MAZE_ALGORITHM_A_STAR,
+} MazeAlgorithm;
+
+/**
+ * Cells are packed two per byte (the even-indexed cell in the high nibble), matching the file
+ * format.
+ */
+typedef struct Maze Maze;
+
+/**
+ * Cells from the start to the end of a solved maze
+ */
+typedef struct MazeSolution MazeSolution;
+
+typedef struct MazePoint {
+  size_t x;
+  size_t y;
+} MazePoint;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * An empty maze with every wall closed, or null if either side is 0
+ */
+struct Maze *maze_new(uint16_t width, uint16_t height);
+
+/**
+ * Reads the native `.dat` format, returning null if it is invalid
+ *
+ * # Safety
+ *
+ * `data` must point to `len` readable bytes
+ */
+struct Maze *maze_from_data(const uint8_t *data, size_t len);
+
+/**
+ * # Safety
+ *
+ * `maze` must be null or come from this library, and must not be used afterwards
+ */
+void maze_free(struct Maze *maze);
+
+/**
+ * # Safety
+ *
+ * `maze` must be a live maze from this library
+ */
+size_t maze_width(const struct Maze *maze);
+
+/**
+ * # Safety
+ *
+ * `maze` must be a live maze from this library
+ */
+size_t maze_height(const struct Maze *maze);
+
+/**
+ * The maze's own cell buffer, with its length in bytes written to `len`. Cells are in row-major
+ * order, two per byte with the even-indexed cell in the high nibble, each a bitmask of open
+ * directions: up 1, right 2, down 4, left 8. The pointer is valid until the maze is changed or
+ * freed.
+ *
+ * # Safety
+ *
+ * `maze` must be a live maze from this library and `len` must be null or writable
+ */
+const uint8_t *maze_cells(const struct Maze *maze, size_t *len);
+
+/**
+ * Open directions of one cell, or 0 if it is outside the maze
+ *
+ * # Safety
+ *
+ * `maze` must be a live maze from this library
+ */
+uint8_t maze_get(const struct Maze *maze, size_t x, size_t y);
+
+/**
+ * Replaces the maze with a freshly generated one of the same size
+ *
+ * # Safety
+ *
+ * `maze` must be a live maze from this library
+ */
+void maze_generate(struct Maze *maze, enum MazeGeneratorKind generator);
+
+/**
+ * Path of cells from `start` to `end`, or null if either is outside the maze or `end` cannot be
+ * reached
+ *
+ * # Safety
+ *
+ * `maze` must be a live maze from this library
+ */
+struct MazeSolution *maze_solve(const struct Maze *maze,
+                                enum MazeAlgorithm algorithm,
+                                struct MazePoint start,
+                                struct MazePoint end);
+
+/**
+ * # Safety
+ *
+ * `solution` must be a live solution from this library
+ */
+size_t maze_solution_len(const struct MazeSolution *solution);
+
+/**
+ * The solution's cells, valid until it is freed
+ *
+ * # Safety
+ *
+ * `solution` must be a live solution from this library
+ */
+const struct MazePoint *maze_solution_points(const struct MazeSolution *solution);
+
+/**
+ * # Safety
+ *
+ * `solution` must be null or come from this library, and must not be used afterwards
+ */
+void maze_solution_free(struct MazeSolution *solution);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MAZE_H */
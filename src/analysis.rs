@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+
+use crate::color::Color;
+use crate::maze::grid::{ParentGrid, VisitedGrid};
+use crate::maze::solvers::bfs;
+use crate::maze::tags::Tag;
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Breadth-first search over the whole maze from `root`, returning every reached cell's parent
+/// and the order cells were reached in
+fn explore(maze: &Maze, root: (usize, usize)) -> (ParentGrid, Vec<(usize, usize)>) {
+    let bounds = maze.get_bounds();
+
+    let mut parents = ParentGrid::new(bounds);
+    let mut visited = VisitedGrid::new(bounds);
+    let mut order = vec![];
+    let mut queue = VecDeque::from([root]);
+    visited.insert(root);
+
+    while let Some(pos) = queue.pop_front() {
+        order.push(pos);
+
+        let (neighbors, count) = maze.get_travellable_neighbors(pos);
+
+        for next in neighbors.into_iter().take(count) {
+            if visited.insert(next) {
+                parents.set(next, pos);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    (parents, order)
+}
+
+/// Number of steps from `from` to every cell, indexed like the maze. Unreachable cells are
+/// `None`.
+pub fn distance_map(maze: &Maze, from: (usize, usize)) -> Vec<Option<usize>> {
+    let (width, _) = maze.get_bounds();
+    let (parents, order) = explore(maze, from);
+
+    let mut distances = vec![None; maze.cell_count()];
+    distances[from.1 * width + from.0] = Some(0);
+
+    // BFS order guarantees a parent's distance is known before its children's
+    for pos in order.into_iter().skip(1) {
+        let parent = parents.get(pos).unwrap();
+
+        distances[pos.1 * width + pos.0] = distances[parent.1 * width + parent.0].map(|d| d + 1);
+    }
+
+    distances
+}
+
+/// Cells seen from `from`, indexed like the maze: those at most `steps` steps away, and those
+/// down straight passages however far, as walls block the view of anything around a corner
+pub fn visible_cells(maze: &Maze, from: (usize, usize), steps: usize) -> Vec<bool> {
+    let (width, _) = maze.get_bounds();
+
+    let mut visible = vec![false; maze.cell_count()];
+    visible[from.1 * width + from.0] = true;
+
+    let mut queue = VecDeque::from([(from, 0)]);
+
+    while let Some((pos, distance)) = queue.pop_front() {
+        if distance == steps {
+            continue;
+        }
+
+        let (neighbors, count) = maze.get_travellable_neighbors(pos);
+
+        for next in neighbors.into_iter().take(count) {
+            let seen = &mut visible[next.1 * width + next.0];
+            if !*seen {
+                *seen = true;
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    for direction in Direction::ALL {
+        let mut pos = from;
+
+        while let Some(next) = maze.travel(pos, direction) {
+            visible[next.1 * width + next.0] = true;
+            pos = next;
+        }
+    }
+
+    visible
+}
+
+/// Every cell's color a fraction of the way through `stops` given by its distance from the start
+/// over the longest distance, indexed like the maze. The start is the cell tagged
+/// [`Tag::Start`], or else the top left corner. Unreachable cells are `None`.
+pub fn distance_colors(maze: &Maze, stops: &[Color]) -> Vec<Option<Color>> {
+    let start = maze.find_tag(Tag::Start).next().unwrap_or((0, 0));
+    let distances = distance_map(maze, start);
+    let longest = distances
+        .iter()
+        .flatten()
+        .max()
+        .copied()
+        .unwrap_or(0)
+        .max(1);
+
+    distances
+        .into_iter()
+        .map(|distance| distance.map(|d| gradient(stops, d as f32 / longest as f32)))
+        .collect()
+}
+
+/// For every cell, the direction of the next step on a shortest path to `goal`, indexed like the
+/// maze. This solves the maze for every start position at once. The goal itself and unreachable
+/// cells are `None`.
+pub fn flow_field(maze: &Maze, goal: (usize, usize)) -> Vec<Option<Direction>> {
+    let (width, height) = maze.get_bounds();
+    let (parents, _) = explore(maze, goal);
+
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|pos| parents.direction(pos))
+        .collect()
+}
+
+/// Measures of a maze's shape, for comparing what different generators make
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MazeStats {
+    /// Cells with a single passage
+    pub dead_ends: usize,
+    /// Steps between the two cells furthest apart. Found with two breadth-first sweeps, which is
+    /// exact for perfect mazes but may fall short in mazes with loops.
+    pub diameter: usize,
+    /// Steps on the shortest path from the top left to the bottom right corner, `None` if there
+    /// is no path
+    pub solution_length: Option<usize>,
+    /// Junctions along that path, where someone walking it has more than one way on to pick from
+    pub difficulty: Option<usize>,
+}
+
+pub fn maze_stats(maze: &Maze) -> MazeStats {
+    let (width, height) = maze.get_bounds();
+    let passages = |pos| maze.get_travellable_neighbors(pos).1;
+
+    // The cell furthest from any other is at one end of a longest path
+    let farthest = |from| {
+        distance_map(maze, from)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, distance)| Some((distance?, (i % width, i / width))))
+            .max()
+            .unwrap_or((0, from))
+    };
+    let (_, end) = farthest((0, 0));
+    let (diameter, _) = farthest(end);
+
+    let path = bfs(maze, (0, 0), (width - 1, height - 1));
+
+    MazeStats {
+        dead_ends: maze.iter_coords().filter(|&pos| passages(pos) == 1).count(),
+        diameter,
+        solution_length: path.as_ref().map(|path| path.len() - 1),
+        difficulty: path.map(|path| {
+            path.iter()
+                .enumerate()
+                .take(path.len() - 1)
+                // Every cell but the start has one passage back to where it was entered from
+                .filter(|&(i, &pos)| passages(pos) - (i > 0) as usize > 1)
+                .count()
+        }),
+    }
+}
+
+/// How many of several runs visited each cell, indexed like the maze
+#[derive(Debug, Clone)]
+pub struct HeatMap {
+    width: usize,
+    counts: Vec<u32>,
+    runs: u32,
+}
+
+impl HeatMap {
+    pub fn new(bounds: (usize, usize)) -> Self {
+        Self {
+            width: bounds.0,
+            counts: vec![0; bounds.0 * bounds.1],
+            runs: 0,
+        }
+    }
+
+    /// Counts a run visiting `cells`, each cell once however often it is listed
+    pub fn add_run(&mut self, cells: impl IntoIterator<Item = (usize, usize)>) {
+        let mut seen = VisitedGrid::new((self.width, self.counts.len() / self.width));
+
+        for pos in cells {
+            if seen.insert(pos) {
+                self.counts[pos.1 * self.width + pos.0] += 1;
+            }
+        }
+
+        self.runs += 1;
+    }
+
+    pub fn runs(&self) -> u32 {
+        self.runs
+    }
+
+    /// Fraction of the runs that visited `(x, y)`, from 0 to 1
+    pub fn heat(&self, (x, y): (usize, usize)) -> f32 {
+        if self.runs == 0 {
+            return 0.;
+        }
+
+        self.counts[y * self.width + x] as f32 / self.runs as f32
+    }
+}
+
+/// Cold to hot, for [`gradient`]
+pub const HEAT_COLORS: [Color; 4] = [
+    Color::rgb(30, 30, 90),
+    Color::rgb(200, 0, 0),
+    Color::rgb(255, 200, 0),
+    Color::rgb(255, 255, 255),
+];
+
+/// Red through violet, for [`gradient`]
+pub const RAINBOW_COLORS: [Color; 6] = [
+    Color::rgb(230, 40, 40),
+    Color::rgb(250, 150, 30),
+    Color::rgb(240, 230, 50),
+    Color::rgb(60, 200, 80),
+    Color::rgb(50, 120, 230),
+    Color::rgb(140, 60, 200),
+];
+
+/// The color a fraction `t` of the way through `stops`, blending linearly between neighbors.
+/// `t` is clamped to 0 to 1.
+pub fn gradient(stops: &[Color], t: f32) -> Color {
+    let Some(last) = stops.len().checked_sub(1) else {
+        return Color::BLACK;
+    };
+
+    let position = t.clamp(0., 1.) * last as f32;
+    let i = (position as usize).min(last.saturating_sub(1));
+    let (from, to) = (stops[i], stops[(i + 1).min(last)]);
+    let t = position - i as f32;
+
+    let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+    Color::rgb(
+        blend(from.r, to.r),
+        blend(from.g, to.g),
+        blend(from.b, to.b),
+    )
+}
@@ -0,0 +1,134 @@
+use sfml::cpp::FBox;
+use sfml::graphics::{FloatRect, View};
+use sfml::system::{Vector2f, Vector2i};
+use sfml::window::{mouse, Event, Key};
+
+/// Smallest fraction of the maze that can be zoomed into
+const MIN_ZOOM: f32 = 0.02;
+const ZOOM_STEP: f32 = 0.8;
+const PAN_STEP: f32 = 0.1;
+
+/// Zooming (mouse wheel) and panning (arrow keys or right mouse drag) over the maze. Zoom is the
+/// fraction of the whole maze visible, so `1.0` shows everything.
+pub struct Camera {
+    view: FBox<View>,
+    world_size: Vector2f,
+    /// World units per window pixel when showing the whole maze
+    scale: f32,
+    zoom: f32,
+
+    drag_start: Option<Vector2i>,
+}
+
+impl Camera {
+    pub fn new(world_size: (f32, f32), window_size: (f32, f32)) -> Self {
+        let world_size: Vector2f = world_size.into();
+
+        Self {
+            view: View::new(world_size / 2., world_size).unwrap(),
+            world_size,
+            scale: world_size.x / window_size.0,
+            zoom: 1.,
+
+            drag_start: None,
+        }
+    }
+
+    pub fn view(&self) -> &View {
+        &self.view
+    }
+
+    /// World units each window pixel covers, more than `1.0` when the maze is drawn scaled down
+    pub fn units_per_pixel(&self) -> f32 {
+        self.scale * self.zoom
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom < 1.
+    }
+
+    /// The part of the maze currently on screen, in world coordinates
+    pub fn visible_rect(&self) -> FloatRect {
+        let center = self.view.center();
+        let size = self.view.size();
+
+        FloatRect::new(
+            center.x - size.x / 2.,
+            center.y - size.y / 2.,
+            size.x,
+            size.y,
+        )
+    }
+
+    pub fn reset(&mut self) {
+        self.zoom = 1.;
+        self.view.set_size(self.world_size);
+        self.view.set_center(self.world_size / 2.);
+    }
+
+    /// Returns `true` if the event was used by the camera
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::MouseWheelScrolled { delta, .. } => {
+                self.set_zoom(if delta > 0. {
+                    self.zoom * ZOOM_STEP
+                } else {
+                    self.zoom / ZOOM_STEP
+                });
+            }
+            Event::KeyPressed { code, .. } => {
+                let step = self.view.size() * PAN_STEP;
+
+                match code {
+                    Key::Left => self.pan(Vector2f::new(-step.x, 0.)),
+                    Key::Right => self.pan(Vector2f::new(step.x, 0.)),
+                    Key::Up => self.pan(Vector2f::new(0., -step.y)),
+                    Key::Down => self.pan(Vector2f::new(0., step.y)),
+                    Key::Num0 => self.reset(),
+                    _ => return false,
+                }
+            }
+            Event::MouseButtonPressed {
+                button: mouse::Button::Right,
+                x,
+                y,
+            } => self.drag_start = Some(Vector2i::new(x, y)),
+            Event::MouseButtonReleased {
+                button: mouse::Button::Right,
+                ..
+            } => self.drag_start = None,
+            Event::MouseMoved { x, y } => {
+                let Some(start) = self.drag_start else {
+                    return false;
+                };
+
+                let units = self.units_per_pixel();
+                self.pan(Vector2f::new(
+                    (start.x - x) as f32 * units,
+                    (start.y - y) as f32 * units,
+                ));
+                self.drag_start = Some(Vector2i::new(x, y));
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(MIN_ZOOM, 1.);
+        self.view.set_size(self.world_size * self.zoom);
+        self.pan(Vector2f::new(0., 0.));
+    }
+
+    /// Moves the view, keeping it inside the maze
+    fn pan(&mut self, offset: Vector2f) {
+        let half = self.view.size() / 2.;
+        let center = self.view.center() + offset;
+
+        self.view.set_center((
+            center.x.clamp(half.x, self.world_size.x - half.x),
+            center.y.clamp(half.y, self.world_size.y - half.y),
+        ));
+    }
+}
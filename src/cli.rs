@@ -0,0 +1,1064 @@
+use std::fmt::Display;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::str::FromStr;
+
+use clap::builder::PossibleValuesParser;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use maze::config::{Config, DEFAULT_CONFIG_PATH};
+use maze::consts::*;
+use maze::export::cut::CutOptions;
+use maze::export::mesh::MeshOptions;
+use maze::formats::printable::Paper;
+use maze::formats::svg::SvgStyle;
+use maze::formats::FORMATS;
+use maze::maze::agents::*;
+use maze::maze::generators::{Cave, Generator, Kruskal, RandomDFS, Wilson, SEED_LIMIT};
+use maze::maze::metadata::Metadata;
+use maze::maze::solvers::*;
+use maze::maze::symmetry::Symmetry;
+use maze::maze::MazeSolver;
+use maze::settings;
+use maze::theme::Theme;
+use maze::Direction;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum AlgorithmArg {
+    /// Depth-First Search
+    DFS,
+    /// Depth-First Search taking unvisited passages in random order
+    RandomDFS,
+    /// Breadth-First Search
+    BFS,
+    /// Breadth-First Search expanding each frontier on all cores, for huge mazes
+    ParBFS,
+    /// A*
+    AStar,
+    /// Reverse BFS from the end, pointing every cell towards it
+    FlowField,
+    /// Agent keeping its right hand on the wall
+    WallFollower,
+    /// Agent taking random turns at junctions
+    RandomMouse,
+    /// Agent marking the passages it walks
+    Tremaux,
+    /// Agent heading down, following walls with turn counting to get around obstacles
+    Pledge,
+    /// A* that must pick up the key of each tagged door before passing it
+    Constraint,
+}
+
+impl Display for AlgorithmArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AlgorithmArg::BFS => "bfs",
+                AlgorithmArg::ParBFS => "par-bfs",
+                AlgorithmArg::DFS => "dfs",
+                AlgorithmArg::RandomDFS => "random-dfs",
+                AlgorithmArg::AStar => "a-star",
+                AlgorithmArg::FlowField => "flow-field",
+                AlgorithmArg::WallFollower => "wall-follower",
+                AlgorithmArg::RandomMouse => "random-mouse",
+                AlgorithmArg::Tremaux => "tremaux",
+                AlgorithmArg::Pledge => "pledge",
+                AlgorithmArg::Constraint => "constraint",
+            }
+        )
+    }
+}
+
+impl AlgorithmArg {
+    pub fn solver(
+        self,
+        bounds: (usize, usize),
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Algorithm {
+        match self {
+            AlgorithmArg::BFS => {
+                Algorithm::BreadthFirstSearch(BFSSolver::between(bounds, start, end))
+            }
+            AlgorithmArg::ParBFS => {
+                Algorithm::ParallelBFS(ParallelBFSSolver::between(bounds, start, end))
+            }
+            AlgorithmArg::DFS => {
+                Algorithm::DepthFirstSearch(DFSSolver::between(bounds, start, end))
+            }
+            AlgorithmArg::RandomDFS => {
+                Algorithm::DepthFirstSearch(DFSSolver::randomized(bounds, start, end))
+            }
+            AlgorithmArg::AStar => Algorithm::AStar(AStarSolver::between(bounds, start, end)),
+            AlgorithmArg::FlowField => {
+                Algorithm::FlowField(FlowFieldSolver::between(bounds, start, end))
+            }
+            AlgorithmArg::WallFollower => Algorithm::Agent(AgentSolver::new(
+                bounds,
+                Box::new(WallFollower::new(start)),
+                end,
+            )),
+            AlgorithmArg::RandomMouse => Algorithm::Agent(AgentSolver::new(
+                bounds,
+                Box::new(RandomMouse::new(start)),
+                end,
+            )),
+            AlgorithmArg::Tremaux => Algorithm::Agent(AgentSolver::new(
+                bounds,
+                Box::new(Tremaux::new(bounds, start)),
+                end,
+            )),
+            AlgorithmArg::Pledge => Algorithm::Agent(AgentSolver::new(
+                bounds,
+                Box::new(Pledge::new(start, Direction::DOWN)),
+                end,
+            )),
+            AlgorithmArg::Constraint => {
+                Algorithm::Constraint(ConstraintSolver::between(bounds, start, end))
+            }
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    disable_help_flag = true,
+    arg_required_else_help = true,
+    args_override_self = true
+)]
+pub struct Cli {
+    /// Config file providing defaults for any option, e.g. `speed = 120`. Top level keys apply to
+    /// every command that has that option, keys in a `[solve]` style table to that command only.
+    /// Flags it turns on are turned off again with `--no-<flag>` [default: maze.toml, if present]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Neither use nor remember the options and recent maze files of earlier runs, which are
+    /// otherwise the defaults of the commands that open a window
+    #[arg(long, global = true)]
+    pub no_settings: bool,
+
+    /// Display help
+    #[arg(long, global = true, action = clap::ArgAction::HelpLong)]
+    pub help: Option<bool>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate a new maze, optionally watching it being carved
+    #[command(disable_help_flag = true)]
+    Generate(GenerateCommand),
+    /// Solve a generated or loaded maze in a window
+    #[command(disable_help_flag = true)]
+    Solve(SolveCommand),
+    /// Display a maze file
+    #[command(disable_help_flag = true)]
+    View(ViewCommand),
+    /// Convert a maze file to another format: dat, json, ascii, png, svg, dot, csv, pgm, obj, gltf,
+    /// dxf or cut-svg
+    #[command(disable_help_flag = true)]
+    Convert(ConvertCommand),
+    /// Show a maze driven by JSON commands from stdin or TCP clients, one per line
+    #[command(disable_help_flag = true)]
+    Serve(ServeCommand),
+    /// Time generation and solving over several mazes without opening a window
+    #[command(disable_help_flag = true)]
+    Bench(BenchCommand),
+    /// Generate many mazes with each generator and size, and tabulate their average dead ends,
+    /// diameter, solution length and difficulty to compare generators
+    #[command(disable_help_flag = true)]
+    CompareStats(CompareStatsCommand),
+    /// Check that a URDL solution file walks through the maze from start to end, exiting with an
+    /// error otherwise
+    #[command(disable_help_flag = true)]
+    Verify(VerifyCommand),
+    /// Race external solver programs through a maze, checking and timing every move
+    #[command(disable_help_flag = true)]
+    Referee(RefereeCommand),
+    /// Solve a maze many times and save how often each cell was explored as a PNG heat map
+    #[command(disable_help_flag = true)]
+    Heatmap(HeatmapCommand),
+    /// Walk an endless maze generated in chunks around you, with the arrow keys or WASD
+    #[command(disable_help_flag = true)]
+    Explore(ExploreCommand),
+}
+
+impl Command {
+    /// Remembered options the command takes from the settings. Only commands opening a window
+    /// take any, and the maze size and algorithm are left out when results are reported before
+    /// the window opens.
+    fn remembered_flags(&self) -> &'static [&'static str] {
+        const ALL: &[&str] = &["alg", "width", "height", "theme", "position"];
+        const LOOKS: &[&str] = &["theme", "position"];
+
+        match self {
+            Command::Generate(args)
+                if args.debug
+                    || args.window.fast
+                    || (args.output.is_none() && args.print.printable.is_none()) =>
+            {
+                ALL
+            }
+            Command::Solve(args) if args.instant => LOOKS,
+            Command::Solve(_) | Command::View(_) | Command::Serve(_) | Command::Explore(_) => ALL,
+            Command::Referee(args) if !args.instant => ALL,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SizeArgs {
+    /// Maze width
+    #[arg(short, long, default_value_t = DEFAULT_MAZE_WIDTH, value_parser = clap::value_parser!(u16).range(MIN_MAZE_SIZE as i64..))]
+    pub width: u16,
+
+    /// Maze height
+    #[arg(short, long, default_value_t = DEFAULT_MAZE_HEIGHT, value_parser = clap::value_parser!(u16).range(MIN_MAZE_SIZE as i64..))]
+    pub height: u16,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GeneratorArgs {
+    /// Generate a maze whose corridors trace the dark regions of an image
+    #[arg(long, value_name = "PATH")]
+    pub from_image: Option<String>,
+
+    /// How strongly corridors prefer dark regions of --from-image
+    #[arg(long, default_value_t = DEFAULT_IMAGE_BIAS)]
+    pub image_bias: f32,
+
+    /// Darkness (0.0 - 1.0) below which pixels of --from-image are ignored
+    #[arg(long, default_value_t = DEFAULT_IMAGE_THRESHOLD)]
+    pub image_threshold: f32,
+
+    /// Generate open caves joined by tunnels instead of a maze
+    #[arg(long, conflicts_with = "from_image")]
+    pub cave: bool,
+
+    /// Share (0.0 - 1.0) of --cave cells that start out as rock, before smoothing
+    #[arg(long, default_value_t = DEFAULT_CAVE_FILL)]
+    pub cave_fill: f32,
+
+    /// Mirror the maze onto itself, carving one half and its mirror image at once
+    #[arg(long, conflicts_with = "from_image")]
+    pub symmetry: Option<SymmetryArg>,
+
+    /// Seed for the generator, to make the same maze again [default: random]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(..SEED_LIMIT))]
+    pub seed: Option<u64>,
+
+    /// Retract dead ends N times once generated, leaving sparse caves of solid cells
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub sparsify: usize,
+
+    /// Stop generating once this share (0.0 - 1.0) of the cells is carved, leaving the rest solid.
+    /// The end may then be out of reach.
+    #[arg(long)]
+    pub fill: Option<f32>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SolverArgs {
+    /// Which algorithm to use
+    #[arg(short, long, default_value_t = AlgorithmArg::DFS)]
+    pub alg: AlgorithmArg,
+
+    /// Waypoint the solution must pass through, in the order given
+    #[arg(long, value_name = "X,Y", value_parser = parse_point::<usize>)]
+    pub via: Vec<(usize, usize)>,
+
+    /// Shift the maze origin every N solver steps, making the solver replan around new walls
+    #[arg(long, value_name = "N", conflicts_with = "via")]
+    pub dynamic: Option<NonZeroUsize>,
+
+    /// Which of the open cells with the same estimated cost A* explores first
+    #[arg(long, default_value = "nearest")]
+    pub tie_break: TieBreakArg,
+
+    /// How much A* trusts its distance estimate to the end. Above 1 finds a path sooner that may
+    /// not be the shortest, 0 spreads out like breadth-first search.
+    #[arg(long, value_name = "WEIGHT", default_value_t = 1.)]
+    pub heuristic_weight: f32,
+
+    /// Straighten the solution once found, cutting out detours and turning as little as it can
+    /// without getting longer. Only braided and open mazes have another way to go.
+    #[arg(long)]
+    pub smooth: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WindowArgs {
+    /// Steps per second of the maze generation/solver, whatever the frame rate
+    #[arg(long, default_value_t = DEFAULT_SPEED, value_parser = clap::value_parser!(u32).range(1..))]
+    pub speed: u32,
+
+    /// Sync frames to the display instead of capping them at 60 per second
+    #[arg(long)]
+    pub vsync: bool,
+
+    /// Step as fast as possible instead of at --speed, drawing the maze every 100ms with the
+    /// progress so far in the window title
+    #[arg(long)]
+    pub fast: bool,
+
+    /// Play a click for every cell carved or visited, higher further into the maze while
+    /// generating and closer to the end while solving
+    #[arg(long)]
+    pub sound: bool,
+
+    /// Loudness of --sound, from 0 to 100
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u8).range(..=100), requires = "sound")]
+    pub volume: u8,
+
+    /// Hide the maze in fog outside what the solver or explorer can see: passages straight ahead
+    /// and cells up to STEPS steps away. Cells walked through stay lit, and those seen before
+    /// stay dimly lit.
+    #[arg(long, value_name = "STEPS", num_args = 0..=1, require_equals = true, default_missing_value = "2")]
+    pub fog: Option<u8>,
+
+    /// Let the cells the solver visited fade out over STEPS steps, so the search leaves a trail
+    /// behind it instead of filling in. The visited cells show again once it is solved.
+    #[arg(long, value_name = "STEPS", num_args = 0..=1, require_equals = true, default_missing_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+    pub trail: Option<u32>,
+
+    /// Colors of the maze and solvers. okabe-ito and tol are safe for color blindness.
+    #[arg(long, default_value = "default")]
+    pub theme: ThemeArg,
+
+    /// Tell visited and frontier cells apart by pattern as well as color, using the okabe-ito
+    /// theme unless another is picked
+    #[arg(long)]
+    pub accessible: bool,
+
+    /// Pixels per cell, instead of the largest size that fits the maze on the screen
+    #[arg(long, value_parser = clap::value_parser!(u32).range(MIN_CELL_SIZE as i64..))]
+    pub cell_size: Option<u32>,
+
+    /// Pixels of wall between cells [default: an eighth of the cell size]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub wall_width: Option<u32>,
+
+    /// Screen position of the window's top left corner [default: where the last one was closed]
+    #[arg(long, value_name = "X,Y", value_parser = parse_point::<i32>, allow_hyphen_values = true)]
+    pub position: Option<(i32, i32)>,
+}
+
+impl WindowArgs {
+    pub fn cell_size(&self) -> Option<usize> {
+        self.cell_size.map(|size| size as usize)
+    }
+
+    pub fn wall_width(&self) -> Option<usize> {
+        self.wall_width.map(|width| width as usize)
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme.theme(self.accessible)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum ThemeArg {
+    Default,
+    OkabeIto,
+    Tol,
+}
+
+impl ThemeArg {
+    /// With `accessible` as in `--accessible`
+    pub fn theme(self, accessible: bool) -> Theme {
+        let theme = match (self, accessible) {
+            (Self::Default, true) | (Self::OkabeIto, _) => Theme::OKABE_ITO,
+            (Self::Default, false) => Theme::DEFAULT,
+            (Self::Tol, _) => Theme::TOL,
+        };
+
+        Theme {
+            patterns: accessible,
+            ..theme
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum SymmetryArg {
+    /// Left and right halves mirror each other
+    Horizontal,
+    /// Top and bottom halves mirror each other
+    Vertical,
+    /// The bottom half is the top half turned upside down
+    Rotational,
+}
+
+impl From<SymmetryArg> for Symmetry {
+    fn from(value: SymmetryArg) -> Self {
+        match value {
+            SymmetryArg::Horizontal => Symmetry::Horizontal,
+            SymmetryArg::Vertical => Symmetry::Vertical,
+            SymmetryArg::Rotational => Symmetry::Rotational,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum GeneratorArg {
+    /// Loop-erased random walks, picking evenly among all perfect mazes
+    Wilson,
+    /// Randomized depth-first search, making long winding corridors
+    #[value(alias = "dfs")]
+    RandomDfs,
+    /// Open caves joined by tunnels, see --cave
+    Cave,
+    /// Randomized Kruskal's algorithm, making many short dead ends
+    Kruskal,
+}
+
+impl Display for GeneratorArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                GeneratorArg::Wilson => "wilson",
+                GeneratorArg::RandomDfs => "random-dfs",
+                GeneratorArg::Cave => "cave",
+                GeneratorArg::Kruskal => "kruskal",
+            }
+        )
+    }
+}
+
+impl GeneratorArg {
+    pub fn generator(self, bounds: (usize, usize), seed: u64) -> Generator {
+        match self {
+            GeneratorArg::Wilson => Generator::Wilson(Wilson::with_seed(bounds, seed)),
+            GeneratorArg::RandomDfs => Generator::RandomDFS(RandomDFS::with_seed(bounds, seed)),
+            GeneratorArg::Cave => Generator::Cave(Cave::with_seed(bounds, DEFAULT_CAVE_FILL, seed)),
+            GeneratorArg::Kruskal => Generator::Kruskal(Kruskal::with_seed(bounds, seed)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum TieBreakArg {
+    /// The cell nearest the end
+    Nearest,
+    /// Carrying straight on
+    Straight,
+    /// Turning off
+    Turns,
+    /// A random one
+    Random,
+}
+
+impl From<TieBreakArg> for TieBreak {
+    fn from(value: TieBreakArg) -> Self {
+        match value {
+            TieBreakArg::Nearest => TieBreak::Nearest,
+            TieBreakArg::Straight => TieBreak::Straight,
+            TieBreakArg::Turns => TieBreak::Turns,
+            TieBreakArg::Random => TieBreak::Random,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum PaperArg {
+    A4,
+    Letter,
+}
+
+impl From<PaperArg> for Paper {
+    fn from(value: PaperArg) -> Self {
+        match value {
+            PaperArg::A4 => Paper::A4,
+            PaperArg::Letter => Paper::Letter,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct PrintArgs {
+    /// Write a printable puzzle sheet with an entrance and exit, as .pdf or .svg
+    #[arg(long, value_name = "PATH")]
+    pub printable: Option<String>,
+
+    /// Paper size of the printable sheet
+    #[arg(long, default_value = "a4", requires = "printable")]
+    pub paper: PaperArg,
+
+    /// Title printed above the maze
+    #[arg(long, default_value = "Maze", requires = "printable")]
+    pub title: String,
+
+    /// Add the solution on a second page (a separate .solution.svg for SVG)
+    #[arg(long, requires = "printable")]
+    pub print_solution: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GenerateCommand {
+    #[command(flatten)]
+    pub size: SizeArgs,
+
+    #[command(flatten)]
+    pub generator: GeneratorArgs,
+
+    /// Output maze data path
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Print the maze as a share code, a line of text to paste where files can not go
+    #[arg(long)]
+    pub share: bool,
+
+    /// Metadata saved with the maze, e.g. `--meta name=Foo`. Can be given more than once.
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_meta)]
+    pub meta: Vec<(String, String)>,
+
+    #[command(flatten)]
+    pub print: PrintArgs,
+
+    /// See generation live
+    #[arg(short, long)]
+    pub debug: bool,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+
+    /// Write every generator step as a line of JSON to this file
+    #[arg(long, value_name = "FILE")]
+    pub trace: Option<String>,
+
+    /// Print run statistics as a single JSON object on stdout, sending other messages to stderr
+    #[arg(long)]
+    pub json_stats: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SolveCommand {
+    /// Input maze path, in any readable format, instead of generating a new maze
+    #[arg(short, long, conflicts_with_all = ["width", "height", "from_image"])]
+    pub input: Option<String>,
+
+    /// Input maze as a share code printed by --share, instead of generating a new maze
+    #[arg(long, value_name = "CODE", conflicts_with_all = ["input", "width", "height", "from_image"])]
+    pub input_code: Option<String>,
+
+    #[command(flatten)]
+    pub size: SizeArgs,
+
+    #[command(flatten)]
+    pub generator: GeneratorArgs,
+
+    #[command(flatten)]
+    pub solver: SolverArgs,
+
+    /// Output maze data path
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    /// Save solution to <OUTPUT>.solution.dat
+    #[arg(long, requires = "output")]
+    pub save_solution: bool,
+
+    /// Print the maze as a share code, a line of text to paste where files can not go
+    #[arg(long)]
+    pub share: bool,
+
+    /// Metadata saved with the maze, e.g. `--meta name=Foo`. Can be given more than once.
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_meta)]
+    pub meta: Vec<(String, String)>,
+
+    /// Race the wall follower, random mouse, Tremaux and Pledge agents to the exit instead of solving
+    #[arg(long, conflicts_with_all = ["alg", "via", "dynamic", "save_solution"])]
+    pub race: bool,
+
+    #[command(flatten)]
+    pub print: PrintArgs,
+
+    /// See generation live
+    #[arg(short, long)]
+    pub debug: bool,
+
+    /// Instantly solve the maze
+    #[arg(long)]
+    pub instant: bool,
+
+    /// Reload the maze and restart the solver whenever the input file changes
+    #[arg(long, requires = "input")]
+    pub watch: bool,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+
+    /// Write every generator and solver step as a line of JSON to this file
+    #[arg(long, value_name = "FILE")]
+    pub trace: Option<String>,
+
+    /// Print run statistics as a single JSON object on stdout, sending other messages to stderr
+    #[arg(long)]
+    pub json_stats: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ViewCommand {
+    /// Maze path, in any readable format
+    pub input: String,
+
+    /// Reload the maze whenever the file changes
+    #[arg(long, conflicts_with = "stats")]
+    pub watch: bool,
+
+    /// Print the size and metadata of the maze instead of showing it
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Walk the maze yourself from the top left to the bottom right corner with the arrow keys or
+    /// WASD. H shows the next few cells of the shortest route, at a cost to the score.
+    #[arg(long, conflicts_with_all = ["stats", "watch"])]
+    pub play: bool,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ConvertCommand {
+    /// Maze to read, in any readable format
+    pub input: String,
+
+    /// Path to write the converted maze to
+    pub output: String,
+
+    /// Output format [default: from the extension of OUTPUT]
+    #[arg(long, value_parser = PossibleValuesParser::new(FORMATS.iter().map(|format| format.name())))]
+    pub to: Option<String>,
+
+    /// Solve the maze and draw the solution, in formats that can show it
+    #[arg(long)]
+    pub solution: bool,
+
+    /// Which algorithm to solve with
+    #[arg(short, long, default_value_t = AlgorithmArg::BFS, requires = "solution")]
+    pub alg: AlgorithmArg,
+
+    /// Height of the walls of obj and gltf meshes, in cell widths
+    #[arg(long, default_value_t = MeshOptions::DEFAULT.wall_height)]
+    pub wall_height: f32,
+
+    /// Thickness of the walls of obj and gltf meshes, in cell widths
+    #[arg(long, default_value_t = MeshOptions::DEFAULT.wall_thickness)]
+    pub wall_thickness: f32,
+
+    /// Side of a cell of dxf and cut-svg exports for laser cutters, in millimeters
+    #[arg(long, value_name = "MM", default_value_t = CutOptions::DEFAULT.cell_size)]
+    pub cut_cell_size: f32,
+
+    /// Tint png and svg cells with a rainbow by their distance from the start
+    #[arg(long)]
+    pub color_by_distance: bool,
+
+    /// Pixels per cell of png exports, drawn like the window instead of a pixel per cell and per
+    /// wall. Such images can not be converted back.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(MIN_CELL_SIZE as i64..))]
+    pub cell_size: Option<u32>,
+
+    /// Pixels of wall between cells of png exports with --cell-size [default: an eighth of the
+    /// cell size]
+    #[arg(long, requires = "cell_size", value_parser = clap::value_parser!(u32).range(1..))]
+    pub wall_width: Option<u32>,
+
+    #[command(flatten)]
+    pub svg: SvgArgs,
+
+    /// Metadata saved with the maze, e.g. `--meta name=Foo`. Can be given more than once.
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_meta)]
+    pub meta: Vec<(String, String)>,
+}
+
+/// Look of svg exports, which like any option can also be set in the config file
+#[derive(Args, Debug, Clone)]
+pub struct SvgArgs {
+    /// Side of a cell of svg exports, in millimeters
+    #[arg(long, value_name = "MM", default_value_t = SvgStyle::DEFAULT.cell_size)]
+    pub cell_size_mm: f32,
+
+    /// Thickness of the walls of svg exports, in millimeters
+    #[arg(long, value_name = "MM", default_value_t = SvgStyle::DEFAULT.wall_width)]
+    pub wall_width_mm: f32,
+
+    /// Color of the walls of svg exports, e.g. `black` or `#333`
+    #[arg(long, default_value_t = SvgStyle::DEFAULT.wall_color.to_string())]
+    pub wall_color: String,
+
+    /// Thickness of the solution line of svg exports, in millimeters
+    #[arg(long, value_name = "MM", default_value_t = SvgStyle::DEFAULT.solution_width)]
+    pub solution_width_mm: f32,
+
+    /// Color of the solution line of svg exports
+    #[arg(long, default_value_t = SvgStyle::DEFAULT.solution_color.to_string())]
+    pub solution_color: String,
+
+    /// Turn the solution line of svg exports sharply instead of in arcs
+    #[arg(long)]
+    pub sharp_corners: bool,
+
+    /// Draw the solution line of svg exports dashed
+    #[arg(long)]
+    pub dashed: bool,
+}
+
+impl From<SvgArgs> for SvgStyle {
+    fn from(value: SvgArgs) -> Self {
+        Self {
+            cell_size: value.cell_size_mm,
+            wall_width: value.wall_width_mm,
+            wall_color: value.wall_color.into(),
+            solution_width: value.solution_width_mm,
+            solution_color: value.solution_color.into(),
+            rounded: !value.sharp_corners,
+            dashed: value.dashed,
+            color_by_distance: false,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServeCommand {
+    /// Accept commands from TCP clients on this address instead of stdin, e.g. 127.0.0.1:7878
+    #[arg(long, value_name = "ADDRESS")]
+    pub listen: Option<String>,
+
+    /// Let TCP clients export into this directory, by paths relative to it. Without it only
+    /// commands from stdin can export.
+    #[arg(long, value_name = "DIR", requires = "listen")]
+    pub export_dir: Option<String>,
+
+    /// Start from this maze, in any readable format, instead of an empty one
+    #[arg(short, long, conflicts_with_all = ["width", "height"])]
+    pub input: Option<String>,
+
+    #[command(flatten)]
+    pub size: SizeArgs,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BenchCommand {
+    #[command(flatten)]
+    pub size: SizeArgs,
+
+    /// Algorithms to compare
+    #[arg(short, long, value_delimiter = ',', default_values_t = [AlgorithmArg::DFS, AlgorithmArg::BFS, AlgorithmArg::AStar])]
+    pub alg: Vec<AlgorithmArg>,
+
+    /// Number of mazes to generate and solve
+    #[arg(long, default_value_t = 10)]
+    pub samples: usize,
+
+    /// Print results as a single JSON object on stdout, sending other messages to stderr
+    #[arg(long)]
+    pub json_stats: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum TableFormatArg {
+    Markdown,
+    Csv,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CompareStatsCommand {
+    /// Generators to compare
+    #[arg(long, value_delimiter = ',', default_values_t = [GeneratorArg::Wilson, GeneratorArg::RandomDfs])]
+    pub gens: Vec<GeneratorArg>,
+
+    /// Widths and heights of the square mazes to generate
+    #[arg(long, value_delimiter = ',', default_values_t = [32, 64, 128], value_parser = clap::value_parser!(u16).range(MIN_MAZE_SIZE as i64..))]
+    pub sizes: Vec<u16>,
+
+    /// Number of mazes per generator and size
+    #[arg(long, default_value_t = NonZeroUsize::new(50).unwrap())]
+    pub samples: NonZeroUsize,
+
+    /// Seed of the first maze of every generator and size, the others counting up from it, to
+    /// make the same table again [default: random]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(..SEED_LIMIT))]
+    pub seed: Option<u64>,
+
+    /// How the table is written
+    #[arg(long, default_value = "markdown")]
+    pub format: TableFormatArg,
+
+    /// Path to write the table to [default: stdout]
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct VerifyCommand {
+    /// Maze path, in any readable format
+    pub maze: String,
+
+    /// Solution path, one U, R, D or L per move as written by --save-solution
+    pub solution: String,
+
+    /// Cell the solution starts from [default: top left corner]
+    #[arg(long, value_name = "X,Y", value_parser = parse_point::<usize>)]
+    pub start: Option<(usize, usize)>,
+
+    /// Cell the solution must end on [default: bottom right corner]
+    #[arg(long, value_name = "X,Y", value_parser = parse_point::<usize>)]
+    pub end: Option<(usize, usize)>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RefereeCommand {
+    /// Maze path, in any readable format. Start and end tags, if any, replace the corners.
+    pub maze: String,
+
+    /// Solver programs, each with its arguments split on whitespace, e.g. "python3 solver.py".
+    /// They get the maze as one line of JSON on stdin and answer with one U, R, D or L per line.
+    #[arg(required = true)]
+    pub programs: Vec<String>,
+
+    /// Longest a program may take to send a move, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 1000)]
+    pub move_timeout: u64,
+
+    /// Run the race without a window
+    #[arg(long)]
+    pub instant: bool,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+
+    /// Print run statistics as a single JSON object on stdout, sending other messages to stderr
+    #[arg(long)]
+    pub json_stats: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct HeatmapCommand {
+    /// Maze path, in any readable format
+    pub input: String,
+
+    /// PNG path to write the heat map to
+    #[arg(short, long)]
+    pub output: String,
+
+    /// Which algorithm to solve with, best a randomized one
+    #[arg(short, long, default_value_t = AlgorithmArg::RandomMouse)]
+    pub alg: AlgorithmArg,
+
+    /// Number of runs
+    #[arg(short = 'n', long, default_value_t = NonZeroUsize::new(100).unwrap())]
+    pub runs: NonZeroUsize,
+
+    /// Pixels per cell and per wall
+    #[arg(long, default_value_t = 4)]
+    pub scale: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ExploreCommand {
+    /// Seed of the whole maze, to walk the same one again [default: random]
+    #[arg(long, value_parser = clap::value_parser!(u64).range(..SEED_LIMIT))]
+    pub seed: Option<u64>,
+
+    /// Chunks kept loaded on every side of the one you are in
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub radius: u8,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+}
+
+pub fn parse_point<T: FromStr>(s: &str) -> Result<(T, T), String>
+where
+    T::Err: Display,
+{
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| String::from("expected X,Y"))?;
+
+    Ok((
+        x.trim()
+            .parse()
+            .map_err(|err| format!("invalid x: {err}"))?,
+        y.trim()
+            .parse()
+            .map_err(|err| format!("invalid y: {err}"))?,
+    ))
+}
+
+pub fn parse_meta(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| String::from("expected KEY=VALUE"))?;
+
+    Metadata::default().set(key, value)?;
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Parses the command line on top of the config file and the remembered settings, so flags given
+/// on the command line win
+pub fn parse_cli() -> Result<Cli, String> {
+    let args: Vec<String> = std::env::args().collect();
+    let command = Cli::command();
+
+    // A lenient parse stops at the first error but still finds the subcommand, whose flags tell
+    // which `--no-<flag>` are negations
+    let lenient = |args: &[String]| {
+        command
+            .clone()
+            .ignore_errors(true)
+            .try_get_matches_from(args)
+    };
+    let subcommand = lenient(&args)
+        .ok()
+        .and_then(|matches| matches.subcommand_name().map(String::from))
+        .and_then(|name| command.find_subcommand(name));
+
+    let Some(subcommand) = subcommand else {
+        return Ok(Cli::parse_from(args));
+    };
+
+    let accepted: Vec<&str> = subcommand
+        .get_arguments()
+        .filter_map(|arg| arg.get_long())
+        .collect();
+
+    // `--no-<flag>` turns off a flag the config file turns on, and is not given to clap
+    let is_negation = |arg: &str| {
+        arg.strip_prefix("--no-").is_some_and(|name| {
+            !accepted.contains(&&arg[2..])
+                && subcommand.get_arguments().any(|option| {
+                    option.get_long() == Some(name)
+                        && matches!(option.get_action(), ArgAction::SetTrue)
+                })
+        })
+    };
+    let negated: Vec<&str> = args[1..]
+        .iter()
+        .filter(|arg| is_negation(arg))
+        .map(|arg| &arg["--no-".len()..])
+        .collect();
+    let args: Vec<String> = args[..1]
+        .iter()
+        .chain(args[1..].iter().filter(|arg| !is_negation(arg)))
+        .cloned()
+        .collect();
+
+    // Clap reads the global options, so a `--config` that is the value of another flag is not
+    // taken for one
+    let globals = lenient(&args).ok();
+    let config_path = globals
+        .as_ref()
+        .and_then(|matches| matches.get_one::<String>("config").cloned());
+    let no_settings = globals
+        .as_ref()
+        .is_some_and(|matches| matches.get_flag("no_settings"));
+
+    let config = match config_path {
+        Some(path) => Config::load(&path)?,
+        None if Path::new(DEFAULT_CONFIG_PATH).exists() => Config::load(DEFAULT_CONFIG_PATH)?,
+        None => Config::default(),
+    };
+
+    if !no_settings {
+        if let Err(err) = settings::load() {
+            eprintln!("Could not load settings, starting over: {err}");
+        }
+    }
+
+    // Config values become clap defaults rather than flags, so like any default they lose to the
+    // command line and never clash with it through conflicts or requirements
+    let config_defaults: Vec<(&Arg, Vec<String>)> = config
+        .to_defaults(subcommand.get_name(), |flag| accepted.contains(&flag))
+        .into_iter()
+        .filter(|(flag, _)| !negated.contains(&flag.as_str()))
+        .filter_map(|(flag, values)| find_long(subcommand, &flag).map(|arg| (arg, values)))
+        .collect();
+
+    let subcommand_name = subcommand.get_name().to_string();
+    let parse = |defaults: &[(&Arg, Vec<String>)]| {
+        let defaults: Vec<(clap::Id, Vec<String>)> = defaults
+            .iter()
+            .map(|(arg, values)| (arg.get_id().clone(), values.clone()))
+            .collect();
+
+        command
+            .clone()
+            .mut_subcommand(&subcommand_name, |mut subcommand| {
+                for (id, values) in &defaults {
+                    subcommand = subcommand.mut_arg(id, |arg| arg.default_values(values.clone()));
+                }
+                subcommand
+            })
+            .try_get_matches_from(&args)
+    };
+
+    // Parsing without the remembered options tells whether a window opens, and so whether to
+    // use them. Errors and --help are left to clap.
+    let matches = parse(&config_defaults).unwrap_or_else(|err| err.exit());
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    let applies = cli.command.remembered_flags();
+
+    let Some(settings) = settings::get().filter(|_| !applies.is_empty()) else {
+        return Ok(cli);
+    };
+
+    // Remembered options lose to the config file and the command line, and are left out when
+    // they clash with a given option, e.g. a size with --input
+    let given_on_command_line = |arg: &&Arg| {
+        matches
+            .subcommand_matches(&subcommand_name)
+            .is_some_and(|matches| {
+                matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            })
+    };
+    let given: Vec<&Arg> = subcommand
+        .get_arguments()
+        .filter(given_on_command_line)
+        .chain(config_defaults.iter().map(|(arg, _)| *arg))
+        .collect();
+    let clashes = |arg: &Arg| {
+        given.iter().any(|&other| {
+            other.get_id() == arg.get_id()
+                || subcommand.get_arg_conflicts_with(arg).contains(&other)
+                || subcommand.get_arg_conflicts_with(other).contains(&arg)
+        })
+    };
+
+    let remembered = settings
+        .to_defaults(|flag| accepted.contains(&flag) && applies.contains(&flag))
+        .into_iter()
+        .filter_map(|(flag, values)| find_long(subcommand, &flag).map(|arg| (arg, values)))
+        .filter(|(arg, _)| !clashes(arg));
+    let defaults: Vec<(&Arg, Vec<String>)> =
+        config_defaults.iter().cloned().chain(remembered).collect();
+
+    let matches = parse(&defaults).unwrap_or_else(|err| err.exit());
+    Ok(Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit()))
+}
+
+fn find_long<'a>(command: &'a clap::Command, long: &str) -> Option<&'a Arg> {
+    command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(long))
+}
@@ -0,0 +1,35 @@
+//! Colors of themes and of drawn file formats. With the `gui` feature these are SFML's, drawn
+//! straight onto windows, otherwise a stand-in with the same fields and constructors.
+
+#[cfg(feature = "gui")]
+pub use sfml::graphics::Color;
+
+#[cfg(not(feature = "gui"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+#[cfg(not(feature = "gui"))]
+impl Color {
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+    pub const RED: Self = Self::rgb(255, 0, 0);
+    pub const GREEN: Self = Self::rgb(0, 255, 0);
+    pub const BLUE: Self = Self::rgb(0, 0, 255);
+    pub const YELLOW: Self = Self::rgb(255, 255, 0);
+    pub const MAGENTA: Self = Self::rgb(255, 0, 255);
+    pub const CYAN: Self = Self::rgb(0, 255, 255);
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs;
+
+pub const DEFAULT_CONFIG_PATH: &str = "maze.toml";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Bool(bool),
+    /// Numbers are kept as text and parsed by whatever consumes them
+    Number(String),
+    String(String),
+    Array(Vec<ConfigValue>),
+}
+
+impl ConfigValue {
+    fn from_toml(value: toml::Value) -> Result<Self, String> {
+        match value {
+            toml::Value::Boolean(v) => Ok(ConfigValue::Bool(v)),
+            toml::Value::Integer(v) => Ok(ConfigValue::Number(v.to_string())),
+            toml::Value::Float(v) => Ok(ConfigValue::Number(v.to_string())),
+            toml::Value::String(v) => Ok(ConfigValue::String(v)),
+            toml::Value::Array(values) => values
+                .into_iter()
+                .map(ConfigValue::from_toml)
+                .collect::<Result<_, _>>()
+                .map(ConfigValue::Array),
+            toml::Value::Table(_) => Err(String::from("only single level tables are supported")),
+            toml::Value::Datetime(_) => Err(String::from("dates are not supported")),
+        }
+    }
+
+    fn as_arg(&self) -> Option<String> {
+        match self {
+            ConfigValue::Bool(_) | ConfigValue::Array(_) => None,
+            ConfigValue::Number(v) | ConfigValue::String(v) => Some(v.clone()),
+        }
+    }
+}
+
+/// `key = value` settings from a TOML file. Strings, numbers, booleans and arrays of those are
+/// supported, either at the top level or in one level of `[table]`. Keys in a table are
+/// stored as `table.key`.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    entries: Vec<(String, ConfigValue)>,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+
+        Self::parse(&text).map_err(|err| format!("{path}: {err}"))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| err.to_string().trim_end().to_string())?;
+
+        let mut entries = vec![];
+
+        for (key, value) in table {
+            let values = match value {
+                toml::Value::Table(table) => table
+                    .into_iter()
+                    .map(|(inner, value)| (format!("{key}.{inner}"), value))
+                    .collect(),
+                value => vec![(key, value)],
+            };
+
+            for (key, value) in values {
+                let value =
+                    ConfigValue::from_toml(value).map_err(|reason| format!("{key}: {reason}"))?;
+                entries.push((key, value));
+            }
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ConfigValue> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn entries(&self) -> &[(String, ConfigValue)] {
+        &self.entries
+    }
+
+    /// The settings for `table` as default values of command line flags, e.g. `cell_size = 8`
+    /// gives `cell-size` the default `8`, `vsync = true` gives `vsync` the default `true` and
+    /// arrays give one default per element. `false` booleans are left out.
+    ///
+    /// Top-level keys are only used when `accepts` returns `true` for their flag name, so shared
+    /// settings can be given once for every command. Keys from `[table]` always come after them.
+    pub fn to_defaults(
+        &self,
+        table: &str,
+        accepts: impl Fn(&str) -> bool,
+    ) -> Vec<(String, Vec<String>)> {
+        let top_level = self
+            .entries
+            .iter()
+            .filter(|(key, _)| !key.contains('.'))
+            .map(|(key, value)| (key.replace('_', "-"), value))
+            .filter(|(flag, _)| accepts(flag));
+
+        let prefix = format!("{table}.");
+        let in_table = self.entries.iter().filter_map(|(key, value)| {
+            key.strip_prefix(&prefix)
+                .map(|key| (key.replace('_', "-"), value))
+        });
+
+        top_level
+            .chain(in_table)
+            .filter_map(|(name, value)| {
+                let values = match value {
+                    ConfigValue::Bool(true) => vec![String::from("true")],
+                    ConfigValue::Bool(false) => return None,
+                    ConfigValue::Array(values) => {
+                        values.iter().filter_map(ConfigValue::as_arg).collect()
+                    }
+                    value => vec![value.as_arg().unwrap()],
+                };
+
+                Some((name, values))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_escaped_quotes_in_strings_and_arrays() {
+        let config = Config::parse(
+            r##"
+            name = "a\"#b" # comment
+            recent = ["a\",b.dat", 'c\', "d\\"]
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.get("name"),
+            Some(&ConfigValue::String(String::from("a\"#b")))
+        );
+        assert_eq!(
+            config.get("recent"),
+            Some(&ConfigValue::Array(vec![
+                ConfigValue::String(String::from("a\",b.dat")),
+                ConfigValue::String(String::from("c\\")),
+                ConfigValue::String(String::from("d\\")),
+            ]))
+        );
+    }
+
+    #[test]
+    fn turns_settings_into_defaults() {
+        let config = Config::parse(
+            "width = 8\nvsync = true\nfast = false\n[solve]\nvia = [\"1,2\", \"3,4\"]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.to_defaults("solve", |flag| flag != "width"),
+            vec![
+                (String::from("vsync"), vec![String::from("true")]),
+                (
+                    String::from("via"),
+                    vec![String::from("1,2"), String::from("3,4")]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_nested_tables() {
+        assert!(Config::parse("[solve]\nvia = [\"1,2\"]\n[solve.inner]\nx = 1\n").is_err());
+        assert!(Config::parse("[solve]\nvia = [[1, 2]]\n").is_ok());
+    }
+}
@@ -0,0 +1,264 @@
+use std::fmt::Write;
+use std::fs;
+
+use super::runs;
+use crate::formats::{is_open, MazeFormat};
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Sizes of laser cutter and CNC files, in millimeters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CutOptions {
+    pub cell_size: f32,
+}
+
+impl CutOptions {
+    pub const DEFAULT: Self = Self { cell_size: 10. };
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cell_size.is_nan() || self.cell_size <= 0. {
+            return Err(String::from("cell size must be above 0"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for CutOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A straight wall along a grid line, between two corners of the maze with `from` above or left
+/// of `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+/// Every closed wall, each straight run of them merged into a single segment so it is cut in one
+/// go. Walls with an empty cell on both sides are left out, as nothing needs them.
+pub fn wall_segments(maze: &Maze) -> Vec<Segment> {
+    let (width, height) = maze.get_bounds();
+    // Outside the maze counts as not empty, so the border is always kept
+    let empty = |x: usize, y: usize| maze.try_get(x, y).is_some_and(|cell| cell.is_empty());
+
+    let mut segments = vec![];
+
+    for y in 0..=height {
+        let closed = |x| {
+            let open = if y < height {
+                is_open(maze, (x, y), Direction::UP)
+            } else {
+                is_open(maze, (x, height - 1), Direction::DOWN)
+            };
+
+            let solid = y > 0 && empty(x, y - 1) && empty(x, y);
+
+            !open && !solid
+        };
+
+        segments.extend(runs(width, closed).into_iter().map(|(start, end)| Segment {
+            from: (start, y),
+            to: (end, y),
+        }));
+    }
+
+    for x in 0..=width {
+        let closed = |y| {
+            let open = if x < width {
+                is_open(maze, (x, y), Direction::LEFT)
+            } else {
+                is_open(maze, (width - 1, y), Direction::RIGHT)
+            };
+
+            let solid = x > 0 && empty(x - 1, y) && empty(x, y);
+
+            !open && !solid
+        };
+
+        segments.extend(
+            runs(height, closed)
+                .into_iter()
+                .map(|(start, end)| Segment {
+                    from: (x, start),
+                    to: (x, end),
+                }),
+        );
+    }
+
+    segments
+}
+
+/// The segments joined into paths of corners, each cut without lifting the laser. Paths start at
+/// the corners in row order, snaking back along every other row, so the head moves little
+/// between them.
+pub fn cut_paths(maze: &Maze) -> Vec<Vec<(usize, usize)>> {
+    let (width, height) = maze.get_bounds();
+    let corner = |(x, y): (usize, usize)| y * (width + 1) + x;
+
+    let segments = wall_segments(maze);
+
+    // The segments ending at every corner, not yet in a path
+    let mut ends = vec![vec![]; (width + 1) * (height + 1)];
+    for (i, segment) in segments.iter().enumerate() {
+        ends[corner(segment.from)].push(i);
+        ends[corner(segment.to)].push(i);
+    }
+    let mut used = vec![false; segments.len()];
+
+    let mut paths = vec![];
+
+    for y in 0..=height {
+        let row: Box<dyn Iterator<Item = usize>> = match y % 2 {
+            0 => Box::new(0..=width),
+            _ => Box::new((0..=width).rev()),
+        };
+
+        for x in row {
+            // Every path from here, until no segment is left at this corner
+            while ends[corner((x, y))].iter().any(|&i| !used[i]) {
+                let mut at = (x, y);
+                let mut path = vec![at];
+
+                while let Some(i) = ends[corner(at)].iter().copied().find(|&i| !used[i]) {
+                    used[i] = true;
+
+                    let segment = segments[i];
+                    at = if segment.from == at {
+                        segment.to
+                    } else {
+                        segment.from
+                    };
+                    path.push(at);
+                }
+
+                paths.push(path);
+            }
+        }
+    }
+
+    paths
+}
+
+/// Walls as lines and polylines of an AutoCAD R12 DXF drawing in millimeters, with Y up. Export
+/// only.
+pub struct Dxf(pub CutOptions);
+
+impl MazeFormat for Dxf {
+    fn name(&self) -> &'static str {
+        "dxf"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dxf"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        _solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        self.0.validate()?;
+
+        fs::write(path, encode_dxf(maze, &self.0)).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+/// Walls as a single hairline SVG path in millimeters, for laser cutter software. Only picked by
+/// name, as `.svg` files are [`crate::formats::svg::Svg`] drawings. Export only.
+pub struct CutSvg(pub CutOptions);
+
+impl MazeFormat for CutSvg {
+    fn name(&self) -> &'static str {
+        "cut-svg"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        _solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        self.0.validate()?;
+
+        fs::write(path, encode_svg(maze, &self.0)).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+pub fn encode_dxf(maze: &Maze, options: &CutOptions) -> String {
+    let (_, height) = maze.get_bounds();
+    let cell = options.cell_size;
+    // DXF drawings have Y going up
+    let point = |(x, y): (usize, usize)| (x as f32 * cell, (height - y) as f32 * cell);
+
+    // Group codes and values alternate a line each. $INSUNITS 4 is millimeters.
+    let mut out = String::from("0\nSECTION\n2\nHEADER\n9\n$INSUNITS\n70\n4\n0\nENDSEC\n");
+    out.push_str("0\nSECTION\n2\nENTITIES\n");
+
+    for path in cut_paths(maze) {
+        if let [from, to] = path[..] {
+            let ((x0, y0), (x1, y1)) = (point(from), point(to));
+            write!(
+                out,
+                "0\nLINE\n8\nWALLS\n10\n{x0}\n20\n{y0}\n11\n{x1}\n21\n{y1}\n"
+            )
+            .unwrap();
+            continue;
+        }
+
+        // Flag 66 says vertices follow, flag 70 set to 1 closes the polyline
+        let closed = path.first() == path.last();
+        let vertices = if closed {
+            &path[..path.len() - 1]
+        } else {
+            &path[..]
+        };
+
+        write!(out, "0\nPOLYLINE\n8\nWALLS\n66\n1\n70\n{}\n", closed as u8).unwrap();
+        for &corner in vertices {
+            let (x, y) = point(corner);
+            write!(out, "0\nVERTEX\n8\nWALLS\n10\n{x}\n20\n{y}\n").unwrap();
+        }
+        out.push_str("0\nSEQEND\n8\nWALLS\n");
+    }
+
+    out.push_str("0\nENDSEC\n0\nEOF\n");
+
+    out
+}
+
+pub fn encode_svg(maze: &Maze, options: &CutOptions) -> String {
+    let (width, height) = maze.get_bounds();
+    let cell = options.cell_size;
+    let (total_width, total_height) = (width as f32 * cell, height as f32 * cell);
+    let point = |(x, y): (usize, usize)| (x as f32 * cell, y as f32 * cell);
+
+    let mut data = String::new();
+
+    for path in cut_paths(maze) {
+        for (i, &corner) in path.iter().enumerate() {
+            let (x, y) = point(corner);
+
+            match i {
+                0 => write!(data, "M{x} {y}").unwrap(),
+                _ if i == path.len() - 1 && path.len() > 2 && corner == path[0] => data.push('Z'),
+                _ => write!(data, "L{x} {y}").unwrap(),
+            }
+        }
+    }
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {total_width} {total_height}" width="{total_width}mm" height="{total_height}mm">
+<path d="{data}" stroke="red" stroke-width="0.01" fill="none"/>
+</svg>
+"#
+    )
+}
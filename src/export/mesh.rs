@@ -0,0 +1,339 @@
+use std::fmt::Write;
+use std::fs;
+
+use super::runs;
+use crate::formats::{is_open, MazeFormat};
+use crate::json::Json;
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Sizes of the extruded walls, in cell widths
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshOptions {
+    pub wall_height: f32,
+    pub wall_thickness: f32,
+}
+
+impl MeshOptions {
+    pub const DEFAULT: Self = Self {
+        wall_height: 1.,
+        wall_thickness: 0.1,
+    };
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.wall_height.is_nan() || self.wall_height <= 0. {
+            return Err(String::from("wall height must be above 0"));
+        }
+        if self.wall_thickness.is_nan() || self.wall_thickness <= 0. || self.wall_thickness >= 1. {
+            return Err(String::from("wall thickness must be between 0 and 1"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Triangles of the maze walls with flat normals, Y up and one unit per cell. The maze's top left
+/// corner is at the origin and rows go towards +Z.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// Counter-clockwise seen from outside
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Each straight run of closed walls becomes a single box, so a corridor costs the same no
+    /// matter how long it is
+    pub fn walls(maze: &Maze, options: &MeshOptions) -> Self {
+        let (width, height) = maze.get_bounds();
+        let mut mesh = Self::default();
+
+        for y in 0..=height {
+            let closed = |x| {
+                if y < height {
+                    !is_open(maze, (x, y), Direction::UP)
+                } else {
+                    !is_open(maze, (x, height - 1), Direction::DOWN)
+                }
+            };
+
+            for (start, end) in runs(width, closed) {
+                mesh.add_wall((start, y), (end, y), options);
+            }
+        }
+
+        for x in 0..=width {
+            let closed = |y| {
+                if x < width {
+                    !is_open(maze, (x, y), Direction::LEFT)
+                } else {
+                    !is_open(maze, (width - 1, y), Direction::RIGHT)
+                }
+            };
+
+            for (start, end) in runs(height, closed) {
+                mesh.add_wall((x, start), (x, end), options);
+            }
+        }
+
+        mesh
+    }
+
+    /// A wall along a grid line between two maze corners, widened to overlap its neighbors at the
+    /// corners
+    fn add_wall(&mut self, from: (usize, usize), to: (usize, usize), options: &MeshOptions) {
+        let half = options.wall_thickness / 2.;
+
+        let (x0, z0) = (from.0 as f32 - half, from.1 as f32 - half);
+        let (x1, z1) = (to.0 as f32 + half, to.1 as f32 + half);
+        let h = options.wall_height;
+
+        // The bottom face rests on the ground and is never seen
+        self.add_quad(
+            [[x0, h, z0], [x0, h, z1], [x1, h, z1], [x1, h, z0]],
+            [0., 1., 0.],
+        );
+        self.add_quad(
+            [[x1, 0., z0], [x1, h, z0], [x1, h, z1], [x1, 0., z1]],
+            [1., 0., 0.],
+        );
+        self.add_quad(
+            [[x0, 0., z0], [x0, 0., z1], [x0, h, z1], [x0, h, z0]],
+            [-1., 0., 0.],
+        );
+        self.add_quad(
+            [[x0, 0., z1], [x1, 0., z1], [x1, h, z1], [x0, h, z1]],
+            [0., 0., 1.],
+        );
+        self.add_quad(
+            [[x0, 0., z0], [x0, h, z0], [x1, h, z0], [x1, 0., z0]],
+            [0., 0., -1.],
+        );
+    }
+
+    fn add_quad(&mut self, corners: [[f32; 3]; 4], normal: [f32; 3]) {
+        let base = self.positions.len() as u32;
+
+        self.positions.extend(corners);
+        self.normals.extend([normal; 4]);
+        self.indices
+            .extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    pub fn to_obj(&self) -> String {
+        let mut out = String::from("# Maze walls\no maze\n");
+
+        for [x, y, z] in &self.positions {
+            writeln!(out, "v {x} {y} {z}").unwrap();
+        }
+        for [x, y, z] in &self.normals {
+            writeln!(out, "vn {x} {y} {z}").unwrap();
+        }
+
+        // Every vertex has its own normal, so both share the same 1-based index
+        for triangle in self.indices.chunks(3) {
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            writeln!(out, "f {a}//{a} {b}//{b} {c}//{c}").unwrap();
+        }
+
+        out
+    }
+
+    /// A self-contained glTF 2.0 file, with the buffer embedded as a base64 data URI
+    pub fn to_gltf(&self) -> String {
+        let mut buffer = vec![];
+
+        for value in self.positions.iter().chain(&self.normals).flatten() {
+            buffer.extend(value.to_le_bytes());
+        }
+        for index in &self.indices {
+            buffer.extend(index.to_le_bytes());
+        }
+
+        let vertices_len = self.positions.len() * 12;
+        let indices_len = self.indices.len() * 4;
+
+        let (min, max) = self.positions.iter().fold(
+            ([f32::MAX; 3], [f32::MIN; 3]),
+            |(mut min, mut max), position| {
+                for i in 0..3 {
+                    min[i] = min[i].min(position[i]);
+                    max[i] = max[i].max(position[i]);
+                }
+                (min, max)
+            },
+        );
+        let vec3 = |v: [f32; 3]| Json::Array(v.iter().map(|&c| (c as f64).into()).collect());
+
+        let buffer_view = |offset: usize, length: usize, target: usize| {
+            Json::object([
+                ("buffer", 0.into()),
+                ("byteOffset", offset.into()),
+                ("byteLength", length.into()),
+                ("target", target.into()),
+            ])
+        };
+
+        const FLOAT: usize = 5126;
+        const UNSIGNED_INT: usize = 5125;
+        const ARRAY_BUFFER: usize = 34962;
+        const ELEMENT_ARRAY_BUFFER: usize = 34963;
+
+        Json::object([
+            (
+                "asset",
+                Json::object([("version", "2.0".into()), ("generator", "maze".into())]),
+            ),
+            ("scene", 0.into()),
+            (
+                "scenes",
+                Json::Array(vec![Json::object([("nodes", vec![0usize].into())])]),
+            ),
+            (
+                "nodes",
+                Json::Array(vec![Json::object([
+                    ("mesh", 0.into()),
+                    ("name", "maze".into()),
+                ])]),
+            ),
+            (
+                "meshes",
+                Json::Array(vec![Json::object([(
+                    "primitives",
+                    Json::Array(vec![Json::object([
+                        (
+                            "attributes",
+                            Json::object([("POSITION", 0.into()), ("NORMAL", 1.into())]),
+                        ),
+                        ("indices", 2.into()),
+                    ])]),
+                )])]),
+            ),
+            (
+                "buffers",
+                Json::Array(vec![Json::object([
+                    ("byteLength", buffer.len().into()),
+                    (
+                        "uri",
+                        format!("data:application/octet-stream;base64,{}", base64(&buffer)).into(),
+                    ),
+                ])]),
+            ),
+            (
+                "bufferViews",
+                Json::Array(vec![
+                    buffer_view(0, vertices_len, ARRAY_BUFFER),
+                    buffer_view(vertices_len, vertices_len, ARRAY_BUFFER),
+                    buffer_view(vertices_len * 2, indices_len, ELEMENT_ARRAY_BUFFER),
+                ]),
+            ),
+            (
+                "accessors",
+                Json::Array(vec![
+                    Json::object([
+                        ("bufferView", 0.into()),
+                        ("componentType", FLOAT.into()),
+                        ("count", self.positions.len().into()),
+                        ("type", "VEC3".into()),
+                        ("min", vec3(min)),
+                        ("max", vec3(max)),
+                    ]),
+                    Json::object([
+                        ("bufferView", 1.into()),
+                        ("componentType", FLOAT.into()),
+                        ("count", self.normals.len().into()),
+                        ("type", "VEC3".into()),
+                    ]),
+                    Json::object([
+                        ("bufferView", 2.into()),
+                        ("componentType", UNSIGNED_INT.into()),
+                        ("count", self.indices.len().into()),
+                        ("type", "SCALAR".into()),
+                    ]),
+                ]),
+            ),
+        ])
+        .to_string()
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+/// Wavefront OBJ mesh of the walls. Export only.
+pub struct Obj(pub MeshOptions);
+
+impl MazeFormat for Obj {
+    fn name(&self) -> &'static str {
+        "obj"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["obj"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        _solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        self.0.validate()?;
+
+        fs::write(path, Mesh::walls(maze, &self.0).to_obj()).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+/// glTF 2.0 mesh of the walls, in a single .gltf file. Export only.
+pub struct Gltf(pub MeshOptions);
+
+impl MazeFormat for Gltf {
+    fn name(&self) -> &'static str {
+        "gltf"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["gltf"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        _solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        self.0.validate()?;
+
+        fs::write(path, Mesh::walls(maze, &self.0).to_gltf())
+            .map_err(|err| format!("{path}: {err}"))
+    }
+}
@@ -0,0 +1,23 @@
+//! Exports for other tools, beyond the 2D [`crate::formats`]
+
+pub mod cut;
+pub mod mesh;
+
+/// Start and end of each run of consecutive closed positions in `0..len`
+pub(crate) fn runs(len: usize, closed: impl Fn(usize) -> bool) -> Vec<(usize, usize)> {
+    let mut runs = vec![];
+    let mut start = None;
+
+    for i in 0..=len {
+        match (i < len && closed(i), start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                runs.push((from, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    runs
+}
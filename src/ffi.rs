@@ -0,0 +1,202 @@
+//! C bindings, built with `cargo build --release --no-default-features --features ffi`, which
+//! leaves out CSFML and writes the matching header to `include/maze.h`. Every `*mut Maze` and
+//! `*mut MazeSolution` handed out must be given back to its `_free` function exactly once.
+
+use std::ptr;
+use std::slice;
+
+use crate::maze::generators::{RandomDFS, Wilson};
+use crate::maze::solvers;
+use crate::maze::{Maze, MazeGenerator};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum MazeGeneratorKind {
+    Wilson,
+    RandomDfs,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum MazeAlgorithm {
+    Bfs,
+    Dfs,
+    AStar,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MazePoint {
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Cells from the start to the end of a solved maze
+pub struct MazeSolution {
+    points: Vec<MazePoint>,
+}
+
+/// An empty maze with every wall closed, or null if either side is 0
+#[no_mangle]
+pub extern "C" fn maze_new(width: u16, height: u16) -> *mut Maze {
+    if width == 0 || height == 0 {
+        return ptr::null_mut();
+    }
+
+    Box::into_raw(Box::new(Maze::new(width, height)))
+}
+
+/// Reads the native `.dat` format, returning null if it is invalid
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn maze_from_data(data: *const u8, len: usize) -> *mut Maze {
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+
+    match Maze::from_data(slice::from_raw_parts(data, len)) {
+        Ok(maze) => Box::into_raw(Box::new(maze)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `maze` must be null or come from this library, and must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn maze_free(maze: *mut Maze) {
+    if !maze.is_null() {
+        drop(Box::from_raw(maze));
+    }
+}
+
+/// # Safety
+///
+/// `maze` must be a live maze from this library
+#[no_mangle]
+pub unsafe extern "C" fn maze_width(maze: *const Maze) -> usize {
+    (*maze).get_bounds().0
+}
+
+/// # Safety
+///
+/// `maze` must be a live maze from this library
+#[no_mangle]
+pub unsafe extern "C" fn maze_height(maze: *const Maze) -> usize {
+    (*maze).get_bounds().1
+}
+
+/// The maze's own cell buffer, with its length in bytes written to `len`. Cells are in row-major
+/// order, two per byte with the even-indexed cell in the high nibble, each a bitmask of open
+/// directions: up 1, right 2, down 4, left 8. The pointer is valid until the maze is changed or
+/// freed.
+///
+/// # Safety
+///
+/// `maze` must be a live maze from this library and `len` must be null or writable
+#[no_mangle]
+pub unsafe extern "C" fn maze_cells(maze: *const Maze, len: *mut usize) -> *const u8 {
+    let cells = (*maze).packed_cells();
+
+    if !len.is_null() {
+        *len = cells.len();
+    }
+
+    cells.as_ptr()
+}
+
+/// Open directions of one cell, or 0 if it is outside the maze
+///
+/// # Safety
+///
+/// `maze` must be a live maze from this library
+#[no_mangle]
+pub unsafe extern "C" fn maze_get(maze: *const Maze, x: usize, y: usize) -> u8 {
+    (*maze).try_get(x, y).map_or(0, |cell| cell.bits())
+}
+
+/// Replaces the maze with a freshly generated one of the same size
+///
+/// # Safety
+///
+/// `maze` must be a live maze from this library
+#[no_mangle]
+pub unsafe extern "C" fn maze_generate(maze: *mut Maze, generator: MazeGeneratorKind) {
+    let maze = &mut *maze;
+    let bounds = maze.get_bounds();
+    *maze = Maze::new(bounds.0 as u16, bounds.1 as u16);
+
+    match generator {
+        MazeGeneratorKind::Wilson => {
+            let mut generator = Wilson::new(bounds);
+            while !generator.step(maze).is_done() {}
+        }
+        MazeGeneratorKind::RandomDfs => {
+            let mut generator = RandomDFS::new(bounds);
+            while !generator.step(maze).is_done() {}
+        }
+    }
+}
+
+/// Path of cells from `start` to `end`, or null if either is outside the maze or `end` cannot be
+/// reached
+///
+/// # Safety
+///
+/// `maze` must be a live maze from this library
+#[no_mangle]
+pub unsafe extern "C" fn maze_solve(
+    maze: *const Maze,
+    algorithm: MazeAlgorithm,
+    start: MazePoint,
+    end: MazePoint,
+) -> *mut MazeSolution {
+    let maze = &*maze;
+
+    let start = (start.x, start.y);
+    let end = (end.x, end.y);
+
+    let path = match algorithm {
+        MazeAlgorithm::Bfs => solvers::bfs(maze, start, end),
+        MazeAlgorithm::Dfs => solvers::dfs(maze, start, end),
+        MazeAlgorithm::AStar => solvers::a_star(maze, start, end),
+    };
+    let Some(path) = path else {
+        return ptr::null_mut();
+    };
+
+    Box::into_raw(Box::new(MazeSolution {
+        points: path.into_iter().map(|(x, y)| MazePoint { x, y }).collect(),
+    }))
+}
+
+/// # Safety
+///
+/// `solution` must be a live solution from this library
+#[no_mangle]
+pub unsafe extern "C" fn maze_solution_len(solution: *const MazeSolution) -> usize {
+    (*solution).points.len()
+}
+
+/// The solution's cells, valid until it is freed
+///
+/// # Safety
+///
+/// `solution` must be a live solution from this library
+#[no_mangle]
+pub unsafe extern "C" fn maze_solution_points(solution: *const MazeSolution) -> *const MazePoint {
+    (*solution).points.as_ptr()
+}
+
+/// # Safety
+///
+/// `solution` must be null or come from this library, and must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn maze_solution_free(solution: *mut MazeSolution) {
+    if !solution.is_null() {
+        drop(Box::from_raw(solution));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::analysis::{gradient, visible_cells};
+use crate::color::Color;
+use crate::maze::Maze;
+use crate::theme::theme;
+
+/// How much of a cell has been seen, which sets how brightly it is drawn
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Sight {
+    #[default]
+    Unseen,
+    /// In view at some point, but not now and never stood on
+    Seen,
+    /// In view from where the viewer is now
+    Visible,
+    /// Stood on by the viewer, which stays lit
+    Visited,
+}
+
+impl Sight {
+    /// Fraction of its color a cell is drawn with, the rest being the wall color
+    pub fn brightness(self) -> f32 {
+        match self {
+            Sight::Unseen => 0.12,
+            Sight::Seen => 0.45,
+            Sight::Visible | Sight::Visited => 1.,
+        }
+    }
+}
+
+/// Fog of war over a maze, lifted around a viewer such as the solver's head or the player as it
+/// moves. Only cells in view or already walked through are drawn at full brightness.
+pub struct Fog {
+    width: usize,
+    /// Steps around corners the viewer sees, on top of straight passages
+    steps: usize,
+    sight: Vec<Sight>,
+    /// Indices of the cells in view, to be dimmed once the viewer moves on
+    in_view: Vec<usize>,
+    viewer: Option<(usize, usize)>,
+}
+
+impl Fog {
+    pub fn new(bounds: (usize, usize), steps: usize) -> Self {
+        Self {
+            width: bounds.0,
+            steps,
+            sight: vec![Sight::Unseen; bounds.0 * bounds.1],
+            in_view: vec![],
+            viewer: None,
+        }
+    }
+
+    /// Moves the viewer to `at`, lighting the cells visible from there and dimming those left
+    /// behind. Returns `true` if anything changed.
+    pub fn look(&mut self, maze: &Maze, at: (usize, usize)) -> bool {
+        if self.viewer == Some(at) {
+            return false;
+        }
+        self.viewer = Some(at);
+
+        for &i in &self.in_view {
+            if self.sight[i] == Sight::Visible {
+                self.sight[i] = Sight::Seen;
+            }
+        }
+
+        self.in_view = visible_cells(maze, at, self.steps)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, visible)| visible.then_some(i))
+            .collect();
+
+        for &i in &self.in_view {
+            if self.sight[i] != Sight::Visited {
+                self.sight[i] = Sight::Visible;
+            }
+        }
+
+        self.sight[at.1 * self.width + at.0] = Sight::Visited;
+
+        true
+    }
+
+    /// The same fog over another window of a larger maze, for mazes shown a window at a time.
+    /// `offset` is where the top left cell of this window is in the new one of size `bounds`.
+    /// Cells outside the new window are dropped, and nothing is in view until the viewer looks
+    /// again.
+    pub fn shifted(&self, bounds: (usize, usize), offset: (i64, i64)) -> Self {
+        let mut fog = Self::new(bounds, self.steps);
+
+        for (i, &sight) in self.sight.iter().enumerate() {
+            let x = (i % self.width) as i64 + offset.0;
+            let y = (i / self.width) as i64 + offset.1;
+
+            if (0..bounds.0 as i64).contains(&x) && (0..bounds.1 as i64).contains(&y) {
+                fog.sight[y as usize * bounds.0 + x as usize] = match sight {
+                    Sight::Visible => Sight::Seen,
+                    sight => sight,
+                };
+            }
+        }
+
+        fog
+    }
+
+    pub fn sight(&self, (x, y): (usize, usize)) -> Sight {
+        self.sight[y * self.width + x]
+    }
+
+    /// Per-cell colors for [`crate::maze::render::MazeRenderer::set_colors`], dimmed toward the
+    /// [`crate::theme::Theme::wall`] color by how little of each cell was seen. Cells without a
+    /// color in `colors` are dimmed from [`crate::theme::Theme::cell`].
+    pub fn dim(&self, colors: Option<Vec<Option<Color>>>) -> Vec<Option<Color>> {
+        let theme = theme();
+        let colors = colors.unwrap_or_else(|| vec![None; self.sight.len()]);
+
+        colors
+            .into_iter()
+            .zip(&self.sight)
+            .map(|(color, sight)| match sight {
+                Sight::Visible | Sight::Visited => color,
+                sight => Some(gradient(
+                    &[theme.wall, color.unwrap_or(theme.cell)],
+                    sight.brightness(),
+                )),
+            })
+            .collect()
+    }
+}
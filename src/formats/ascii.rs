@@ -0,0 +1,140 @@
+use std::fs;
+
+use super::{is_open, MazeFormat};
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Plain text drawing with `+`, `-` and `|` walls, every cell two characters wide. Cells on the
+/// solution are filled with `**`, empty cells with `##`.
+///
+/// Reading it back carves every gap in the walls, so cells that are open only from one side come
+/// back open from both, and empty cells are indistinguishable from walled-in ones.
+pub struct Ascii;
+
+impl MazeFormat for Ascii {
+    fn name(&self) -> &'static str {
+        "ascii"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["txt", "ascii"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        fs::write(path, encode(maze, solution)).map_err(|err| format!("{path}: {err}"))
+    }
+
+    fn read(&self, path: &str) -> Result<Maze, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+
+        decode(&text).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+pub fn encode(maze: &Maze, solution: Option<&[(usize, usize)]>) -> String {
+    let (width, height) = maze.get_bounds();
+    let on_solution = |pos| solution.is_some_and(|path| path.contains(&pos));
+
+    let mut out = String::new();
+
+    for y in 0..height {
+        // Walls above the row
+        for x in 0..width {
+            out.push('+');
+            out.push_str(if is_open(maze, (x, y), Direction::UP) {
+                "  "
+            } else {
+                "--"
+            });
+        }
+        out.push_str("+\n");
+
+        for x in 0..width {
+            out.push(if is_open(maze, (x, y), Direction::LEFT) {
+                ' '
+            } else {
+                '|'
+            });
+            out.push_str(if on_solution((x, y)) {
+                "**"
+            } else if maze.get(x, y).is_empty() {
+                "##"
+            } else {
+                "  "
+            });
+        }
+        out.push(if is_open(maze, (width - 1, y), Direction::RIGHT) {
+            ' '
+        } else {
+            '|'
+        });
+        out.push('\n');
+    }
+
+    for x in 0..width {
+        out.push('+');
+        out.push_str(if is_open(maze, (x, height - 1), Direction::DOWN) {
+            "  "
+        } else {
+            "--"
+        });
+    }
+    out.push_str("+\n");
+
+    out
+}
+
+pub fn decode(text: &str) -> Result<Maze, String> {
+    let lines: Vec<&[u8]> = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r').as_bytes())
+        .collect();
+
+    let first = lines
+        .first()
+        .ok_or_else(|| String::from("empty maze drawing"))?;
+
+    if first.len() < 4
+        || !(first.len() - 1).is_multiple_of(3)
+        || lines.len() < 3
+        || lines.len().is_multiple_of(2)
+    {
+        return Err(String::from("not a maze drawing"));
+    }
+
+    let width = (first.len() - 1) / 3;
+    let height = (lines.len() - 1) / 2;
+
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(String::from("maze too large"));
+    }
+
+    // Trailing spaces may have been stripped by an editor
+    let is_gap = |row: usize, column: usize| lines[row].get(column).is_none_or(|c| *c == b' ');
+
+    let mut maze = Maze::new(width as u16, height as u16);
+
+    for y in 0..height {
+        for x in 0..width {
+            if is_gap(y * 2, x * 3 + 1) {
+                maze.carve(x, y, Direction::UP);
+            }
+            if is_gap(y * 2 + 1, x * 3) {
+                maze.carve(x, y, Direction::LEFT);
+            }
+            if x == width - 1 && is_gap(y * 2 + 1, x * 3 + 3) {
+                maze.carve(x, y, Direction::RIGHT);
+            }
+            if y == height - 1 && is_gap(y * 2 + 2, x * 3 + 1) {
+                maze.carve(x, y, Direction::DOWN);
+            }
+        }
+    }
+
+    Ok(maze)
+}
@@ -0,0 +1,391 @@
+use std::fs;
+
+use super::MazeFormat;
+use crate::maze::tags::TagSet;
+use crate::maze::Maze;
+
+/// The native binary format: a header, the width and height as big-endian `u16`s, and the cells
+/// packed two per byte. Solutions are written separately, see `--save-solution`.
+///
+/// The header is [`MAGIC`], a version byte, the length of the
+/// [metadata](crate::maze::metadata::Metadata) as a big-endian `u32` and the metadata itself as
+/// `key=value` lines, then the number of [tagged](crate::maze::tags::Tag) cells as a big-endian
+/// `u32` and for each its index as a big-endian `u32`, its tags as a bitmask byte and its
+/// [lock color](Maze::lock_color) as a byte.
+///
+/// Older files are still read. Before version 4 tagged cells had no lock color, before version 3
+/// the height was left out and guessed from the number of cells, version 1 headers stop after the
+/// metadata, and the first files had no header at all, only the width and the cells.
+pub struct Dat;
+
+pub const MAGIC: &[u8; 4] = b"MAZE";
+const VERSION: u8 = 4;
+
+impl MazeFormat for Dat {
+    fn name(&self) -> &'static str {
+        "dat"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dat"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        _solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        fs::write(path, encode(maze)?).map_err(|err| format!("{path}: {err}"))
+    }
+
+    fn read(&self, path: &str) -> Result<Maze, String> {
+        let data = fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+
+        decode(&data).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+pub fn encode(maze: &Maze) -> Result<Vec<u8>, String> {
+    let mut data = vec![];
+
+    let metadata: String = maze
+        .metadata()
+        .iter()
+        .map(|(key, value)| format!("{key}={value}\n"))
+        .collect();
+    let length: u32 = metadata
+        .len()
+        .try_into()
+        .map_err(|_| String::from("maze metadata too large"))?;
+
+    data.extend(MAGIC);
+    data.push(VERSION);
+    data.extend(length.to_be_bytes());
+    data.extend(metadata.as_bytes());
+
+    let (width, height) = maze.get_bounds();
+    let tagged: Vec<_> = maze.tagged().collect();
+    let count: u32 = tagged
+        .len()
+        .try_into()
+        .map_err(|_| String::from("too many tagged cells"))?;
+
+    data.extend(count.to_be_bytes());
+    for (x, y, tags) in tagged {
+        let i: u32 = (y * width + x)
+            .try_into()
+            .map_err(|_| String::from("maze too large to tag"))?;
+
+        data.extend(i.to_be_bytes());
+        data.push(tags.bits());
+        data.push(maze.lock_color(x, y));
+    }
+
+    let width: u16 = width
+        .try_into()
+        .map_err(|_| String::from("maze width too large"))?;
+    let height: u16 = height
+        .try_into()
+        .map_err(|_| String::from("maze height too large"))?;
+
+    data.extend(width.to_be_bytes());
+    data.extend(height.to_be_bytes());
+
+    // The in-memory layout already matches the file format
+    data.extend(maze.packed_cells());
+
+    Ok(data)
+}
+
+/// Reads a maze, never panicking on malformed data. Errors name the byte offset at which the
+/// problem was found.
+pub fn decode(data: &[u8]) -> Result<Maze, String> {
+    let mut reader = Reader { data, offset: 0 };
+
+    if !data.starts_with(MAGIC) {
+        return decode_cells(&mut reader, false);
+    }
+    reader.take(MAGIC.len(), "magic")?;
+
+    let version_offset = reader.offset;
+    let version = reader.byte("version")?;
+    if !(1..=VERSION).contains(&version) {
+        return Err(at(
+            version_offset,
+            format!("unsupported maze file version {version}"),
+        ));
+    }
+
+    let length = reader.u32("metadata length")? as usize;
+    let metadata_offset = reader.offset;
+    let metadata = reader.take(length, "metadata")?;
+    let metadata = std::str::from_utf8(metadata)
+        .map_err(|err| at(metadata_offset + err.valid_up_to(), "metadata is not UTF-8"))?;
+
+    let mut tags = vec![];
+    if version >= 2 {
+        let count = reader.u32("tag count")? as usize;
+        let size = if version >= 4 { 6 } else { 5 };
+
+        // Checked up front, so a huge count cannot reserve more than the file holds
+        if count > reader.remaining() / size {
+            return Err(at(
+                reader.offset,
+                format!(
+                    "{count} tagged cells need {} bytes, only {} left",
+                    count as u64 * size as u64,
+                    reader.remaining()
+                ),
+            ));
+        }
+
+        tags.reserve(count);
+        for _ in 0..count {
+            let offset = reader.offset;
+            let i = reader.u32("tagged cell")? as usize;
+            let bits = reader.byte("tags")?;
+            let color = match version >= 4 {
+                true => reader.byte("lock color")?,
+                false => 0,
+            };
+
+            tags.push((offset, i, TagSet::from_bits(bits), color));
+        }
+    }
+
+    let mut maze = decode_cells(&mut reader, version >= 3)?;
+
+    for (offset, i, tag_set, color) in tags {
+        if i >= maze.cell_count() {
+            return Err(at(offset, format!("tagged cell {i} outside of the maze")));
+        }
+
+        let (x, y) = maze.i_to_xy(i);
+        for tag in tag_set.iter() {
+            maze.tag(x, y, tag);
+        }
+        if color != 0 {
+            maze.set_lock_color(x, y, color)
+                .map_err(|err| at(offset, err))?;
+        }
+    }
+
+    let mut line_offset = metadata_offset;
+    for line in metadata.split_inclusive('\n') {
+        let offset = line_offset;
+        line_offset += line.len();
+
+        let line = line.trim_end_matches('\n');
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| at(offset, format!("invalid maze metadata line `{line}`")))?;
+
+        maze.metadata_mut()
+            .set(key, value.trim_end_matches('\r'))
+            .map_err(|err| at(offset, err))?;
+    }
+
+    Ok(maze)
+}
+
+/// Reads the width, the height if `has_height`, and the cells, which have to fill the maze
+/// exactly. Without a height it is guessed from the number of cells, as older files left it out.
+fn decode_cells(reader: &mut Reader, has_height: bool) -> Result<Maze, String> {
+    let width_offset = reader.offset;
+    let width = reader.u16("maze width")? as usize;
+    if width == 0 {
+        return Err(at(width_offset, "maze width is 0"));
+    }
+
+    let height = match has_height {
+        true => {
+            let height_offset = reader.offset;
+            let height = reader.u16("maze height")? as usize;
+            if height == 0 {
+                return Err(at(height_offset, "maze height is 0"));
+            }
+
+            height
+        }
+        false => guess_height(reader, width)?,
+    };
+
+    let cells_offset = reader.offset;
+    let cell_data = reader.rest();
+    let length = (width * height).div_ceil(2);
+
+    if cell_data.len() != length {
+        return Err(at(
+            cells_offset,
+            format!(
+                "{width}x{height} cells need {length} bytes, found {}",
+                cell_data.len()
+            ),
+        ));
+    }
+
+    let mut cells = cell_data.to_vec();
+
+    // Drop the padding nibble of a trailing half-filled byte
+    if (width * height) % 2 == 1 {
+        if let Some(last) = cells.last_mut() {
+            *last &= 0xf0;
+        }
+    }
+
+    Ok(Maze::from_cells(width, height, cells))
+}
+
+/// The height of a file without one, the number of whole rows in the rest of the data. Only a
+/// single column can end either in an empty cell or in padding, told apart by guessing padding
+/// for an empty last nibble.
+fn guess_height(reader: &Reader, width: usize) -> Result<usize, String> {
+    let cells_offset = reader.offset;
+    let length = reader.remaining();
+
+    if length == 0 {
+        return Err(at(cells_offset, "maze has no cells"));
+    }
+
+    let padded = reader.data[reader.data.len() - 1] & 0x0f == 0;
+    let cell_count = match (length * 2) % width {
+        0 if width > 1 || !padded => length * 2,
+        _ => length * 2 - 1,
+    };
+
+    if cell_count % width != 0 {
+        return Err(at(
+            cells_offset,
+            format!("{length} bytes of cells are not whole rows of {width}"),
+        ));
+    }
+
+    let height = cell_count / width;
+    if height > u16::MAX as usize {
+        return Err(at(
+            cells_offset,
+            format!("maze height {height} is above {}", u16::MAX),
+        ));
+    }
+
+    Ok(height)
+}
+
+/// Walks through the data, keeping track of the offset for error messages
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    fn take(&mut self, length: usize, what: &str) -> Result<&'a [u8], String> {
+        let Some(bytes) = self
+            .data
+            .get(self.offset..)
+            .and_then(|rest| rest.get(..length))
+        else {
+            return Err(at(
+                self.offset,
+                format!(
+                    "{what} needs {length} bytes, only {} left",
+                    self.remaining()
+                ),
+            ));
+        };
+        self.offset += length;
+
+        Ok(bytes)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.offset..];
+        self.offset = self.data.len();
+
+        rest
+    }
+
+    fn byte(&mut self, what: &str) -> Result<u8, String> {
+        Ok(self.take(1, what)?[0])
+    }
+
+    fn u16(&mut self, what: &str) -> Result<u16, String> {
+        let bytes = self.take(2, what)?;
+
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self, what: &str) -> Result<u32, String> {
+        let bytes = self.take(4, what)?;
+
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+fn at(offset: usize, reason: impl std::fmt::Display) -> String {
+    format!("at byte {offset}: {reason}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::fixtures::{assert_same_locks, locked_maze};
+    use crate::maze::tags::{Tag, LOCK_COLORS};
+    use crate::Direction;
+
+    #[test]
+    fn keeps_an_uncarved_last_cell() {
+        // Carved like a part way `--fill`, leaving the bottom right cell as it started
+        let mut maze = Maze::new(3, 3);
+        for y in 0..3 {
+            maze.carve(0, y, Direction::RIGHT);
+            maze.carve(1, y, Direction::RIGHT);
+        }
+        maze.uncarve(1, 2, Direction::RIGHT);
+        maze.carve(0, 0, Direction::DOWN);
+        maze.carve(0, 1, Direction::DOWN);
+        assert!(maze.get(2, 2).is_empty());
+
+        let decoded = decode(&encode(&maze).unwrap()).unwrap();
+
+        assert_eq!(decoded.get_bounds(), (3, 3));
+        assert!(decoded.cells().eq(maze.cells()));
+    }
+
+    #[test]
+    fn keeps_lock_colors() {
+        let maze = locked_maze();
+
+        assert_same_locks(&decode(&encode(&maze).unwrap()).unwrap(), &maze);
+    }
+
+    #[test]
+    fn rejects_lock_colors_past_the_keyring() {
+        // A 2x1 maze without metadata and with a key of `color` on its second cell
+        let keyed = |color: u8| {
+            let mut data = MAGIC.to_vec();
+            data.push(VERSION);
+            data.extend(0u32.to_be_bytes());
+            data.extend(1u32.to_be_bytes());
+            data.extend(1u32.to_be_bytes());
+            data.push(TagSet::from(Tag::Key).bits());
+            data.push(color);
+            data.extend(2u16.to_be_bytes());
+            data.extend(1u16.to_be_bytes());
+            data.extend(Maze::new(2, 1).packed_cells());
+
+            decode(&data)
+        };
+
+        assert_eq!(
+            keyed(LOCK_COLORS - 1).unwrap().lock_color(1, 0),
+            LOCK_COLORS - 1
+        );
+        assert!(keyed(LOCK_COLORS).is_err());
+    }
+}
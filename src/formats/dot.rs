@@ -0,0 +1,82 @@
+use std::fmt::Write;
+use std::fs;
+
+use super::{is_open, MazeFormat};
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Graphviz graph with a node per cell and an edge per passage, pinned to the maze layout for
+/// `neato -n`. Solution edges are red. Export only.
+pub struct Dot;
+
+impl MazeFormat for Dot {
+    fn name(&self) -> &'static str {
+        "dot"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dot", "gv"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        fs::write(path, encode(maze, solution)).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+pub fn encode(maze: &Maze, solution: Option<&[(usize, usize)]>) -> String {
+    let (width, height) = maze.get_bounds();
+    let solution = solution.unwrap_or_default();
+
+    let on_solution = |a: (usize, usize), b: (usize, usize)| {
+        solution
+            .windows(2)
+            .any(|step| (step[0], step[1]) == (a, b) || (step[0], step[1]) == (b, a))
+    };
+
+    let mut out = String::from("graph maze {\n    node [shape=point];\n");
+
+    for (x, y) in maze.iter_coords() {
+        writeln!(
+            out,
+            "    \"{x},{y}\" [pos=\"{},{}\"];",
+            x * 36,
+            (height - y) * 36
+        )
+        .unwrap();
+    }
+
+    // Only right and down passages, so every edge is listed once
+    for y in 0..height {
+        for x in 0..width {
+            for direction in [Direction::RIGHT, Direction::DOWN] {
+                let Some(next) = direction
+                    .travel(x, y)
+                    .filter(|&(nx, ny)| nx < width && ny < height)
+                else {
+                    continue;
+                };
+
+                if !is_open(maze, (x, y), direction) {
+                    continue;
+                }
+
+                let style = if on_solution((x, y), next) {
+                    " [color=red, penwidth=3]"
+                } else {
+                    ""
+                };
+
+                writeln!(out, "    \"{x},{y}\" -- \"{},{}\"{style};", next.0, next.1).unwrap();
+            }
+        }
+    }
+
+    out.push_str("}\n");
+
+    out
+}
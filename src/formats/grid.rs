@@ -0,0 +1,345 @@
+use std::fs;
+
+use super::{is_open, MazeFormat};
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Occupancy grid as comma separated values, a line per row of `0` for free and `1` for wall. See
+/// [`Grid`] for how they map to mazes.
+pub struct Csv;
+
+/// Occupancy grid as a grayscale PGM image like robot map servers use, pixels darker than half
+/// of the maximum value being walls. Written as binary `P5` with black walls, read from `P5` or
+/// plain `P2`. See [`Grid`] for how they map to mazes.
+pub struct Pgm;
+
+impl MazeFormat for Csv {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["csv"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        _solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        fs::write(path, Grid::of(maze).to_csv()).map_err(|err| format!("{path}: {err}"))
+    }
+
+    fn read(&self, path: &str) -> Result<Maze, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+
+        Grid::from_csv(&text)
+            .and_then(|grid| grid.to_maze())
+            .map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+impl MazeFormat for Pgm {
+    fn name(&self) -> &'static str {
+        "pgm"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["pgm"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        _solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        fs::write(path, Grid::of(maze).to_pgm()).map_err(|err| format!("{path}: {err}"))
+    }
+
+    fn read(&self, path: &str) -> Result<Maze, String> {
+        let data = fs::read(path).map_err(|err| format!("{path}: {err}"))?;
+
+        Grid::from_pgm(&data)
+            .and_then(|grid| grid.to_maze())
+            .map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+/// Squares that are either free or a wall. Read into a maze a cell per square, free cells are
+/// carved open to their free neighbors and walls are left empty, so free squares with no free
+/// neighbors can not be told from walls afterwards.
+///
+/// Mazes are written a square per cell when that shows all their walls, as it does for grids read
+/// back. Others have walls between open cells, so they are written like the PNG format, a square
+/// per cell, per wall and per corner making `2w + 1` by `2h + 1`.
+struct Grid {
+    width: usize,
+    height: usize,
+    /// Row by row
+    walls: Vec<bool>,
+}
+
+impl Grid {
+    fn of(maze: &Maze) -> Self {
+        let (width, height) = maze.get_bounds();
+        let open = |x, y| !maze.get(x, y).is_empty();
+
+        let joined = |x, y, direction: Direction| {
+            let Some((nx, ny)) = direction
+                .travel(x, y)
+                .filter(|&(nx, ny)| nx < width && ny < height)
+            else {
+                return true;
+            };
+
+            !(open(x, y) && open(nx, ny)) || is_open(maze, (x, y), direction)
+        };
+
+        let square_per_cell = (0..height).all(|y| {
+            (0..width).all(|x| joined(x, y, Direction::RIGHT) && joined(x, y, Direction::DOWN))
+        });
+
+        if square_per_cell {
+            return Self {
+                width,
+                height,
+                walls: maze.cells().map(|(_, _, cell)| cell.is_empty()).collect(),
+            };
+        }
+
+        let (grid_width, grid_height) = (width * 2 + 1, height * 2 + 1);
+        let mut walls = vec![true; grid_width * grid_height];
+
+        for (x, y, _) in maze.cells().filter(|(_, _, cell)| !cell.is_empty()) {
+            let i = (y * 2 + 1) * grid_width + x * 2 + 1;
+            walls[i] = false;
+
+            if x + 1 < width && is_open(maze, (x, y), Direction::RIGHT) {
+                walls[i + 1] = false;
+            }
+            if y + 1 < height && is_open(maze, (x, y), Direction::DOWN) {
+                walls[i + grid_width] = false;
+            }
+        }
+
+        Self {
+            width: grid_width,
+            height: grid_height,
+            walls,
+        }
+    }
+
+    fn to_maze(&self) -> Result<Maze, String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(String::from("empty grid"));
+        }
+        if self.width > u16::MAX as usize || self.height > u16::MAX as usize {
+            return Err(String::from("grid too large"));
+        }
+
+        let free = |x: usize, y: usize| !self.walls[y * self.width + x];
+        let mut maze = Maze::new(self.width as u16, self.height as u16);
+
+        for y in 0..self.height {
+            for x in (0..self.width).filter(|&x| free(x, y)) {
+                if x + 1 < self.width && free(x + 1, y) {
+                    maze.carve(x, y, Direction::RIGHT);
+                }
+                if y + 1 < self.height && free(x, y + 1) {
+                    maze.carve(x, y, Direction::DOWN);
+                }
+            }
+        }
+
+        Ok(maze)
+    }
+
+    fn to_csv(&self) -> String {
+        self.walls
+            .chunks(self.width)
+            .map(|row| {
+                let values: Vec<_> = row
+                    .iter()
+                    .map(|&wall| if wall { "1" } else { "0" })
+                    .collect();
+                values.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    fn from_csv(text: &str) -> Result<Self, String> {
+        let mut rows = vec![];
+
+        for (line_number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let row = line
+                .split(',')
+                .enumerate()
+                .map(|(column, value)| match value.trim() {
+                    "0" => Ok(false),
+                    "1" => Ok(true),
+                    value => Err(format!(
+                        "line {}, column {}: expected 0 or 1, found `{value}`",
+                        line_number + 1,
+                        column + 1
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(first) = rows
+                .first()
+                .map(Vec::len)
+                .filter(|&first| first != row.len())
+            {
+                return Err(format!(
+                    "line {}: {} values, the first row has {first}",
+                    line_number + 1,
+                    row.len()
+                ));
+            }
+
+            rows.push(row);
+        }
+
+        Ok(Self {
+            width: rows.first().map_or(0, Vec::len),
+            height: rows.len(),
+            walls: rows.concat(),
+        })
+    }
+
+    fn to_pgm(&self) -> Vec<u8> {
+        let mut data = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend(self.walls.iter().map(|&wall| if wall { 0 } else { 255 }));
+
+        data
+    }
+
+    fn from_pgm(data: &[u8]) -> Result<Self, String> {
+        let mut offset = 0;
+
+        let binary = match header_token(data, &mut offset) {
+            Some("P5") => true,
+            Some("P2") => false,
+            _ => return Err(String::from("not a PGM image")),
+        };
+
+        let width = header_number(data, &mut offset, "width")?;
+        let height = header_number(data, &mut offset, "height")?;
+        let max = header_number(data, &mut offset, "maximum value")?;
+
+        if !(1..=u16::MAX as usize).contains(&max) {
+            return Err(format!("maximum value {max} is not between 1 and 65535"));
+        }
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(String::from("image too large"));
+        }
+
+        let count = width * height;
+
+        let samples: Vec<usize> = if binary {
+            // A single whitespace byte ends the header
+            offset += 1;
+
+            let bytes = if max > 255 { 2 } else { 1 };
+            let raster = data
+                .get(offset..offset + count * bytes)
+                .ok_or_else(|| String::from("image data is cut short"))?;
+
+            raster
+                .chunks(bytes)
+                .map(|sample| sample.iter().fold(0, |v, &b| v << 8 | b as usize))
+                .collect()
+        } else {
+            (0..count)
+                .map(|_| header_number(data, &mut offset, "pixel"))
+                .collect::<Result<_, _>>()?
+        };
+
+        Ok(Self {
+            width,
+            height,
+            walls: samples.into_iter().map(|v| v * 2 < max).collect(),
+        })
+    }
+}
+
+/// The next whitespace separated token of a PGM header, skipping `#` comments
+fn header_token<'a>(data: &'a [u8], offset: &mut usize) -> Option<&'a str> {
+    loop {
+        while data.get(*offset).is_some_and(u8::is_ascii_whitespace) {
+            *offset += 1;
+        }
+
+        if data.get(*offset) != Some(&b'#') {
+            break;
+        }
+        while data.get(*offset).is_some_and(|&c| c != b'\n') {
+            *offset += 1;
+        }
+    }
+
+    let start = *offset;
+    while data.get(*offset).is_some_and(|c| !c.is_ascii_whitespace()) {
+        *offset += 1;
+    }
+
+    std::str::from_utf8(&data[start..*offset])
+        .ok()
+        .filter(|token| !token.is_empty())
+}
+
+fn header_number(data: &[u8], offset: &mut usize, name: &str) -> Result<usize, String> {
+    let token = header_token(data, offset).ok_or_else(|| format!("missing {name}"))?;
+
+    token
+        .parse()
+        .map_err(|_| format!("invalid {name} `{token}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::dat;
+
+    const CSV: &str = "0,0,0\n0,1,0\n0,0,1\n";
+
+    #[test]
+    fn walls_keep_every_row_through_dat() {
+        let maze = Grid::from_csv(CSV).unwrap().to_maze().unwrap();
+        assert!(maze.get(1, 1).is_empty());
+        assert!(maze.get(2, 2).is_empty());
+
+        let decoded = dat::decode(&dat::encode(&maze).unwrap()).unwrap();
+
+        assert_eq!(decoded.get_bounds(), (3, 3));
+        assert!(decoded.cells().eq(maze.cells()));
+    }
+
+    #[test]
+    fn writes_grids_read_back_a_square_per_cell() {
+        let maze = Grid::from_csv(CSV).unwrap().to_maze().unwrap();
+
+        assert_eq!(Grid::of(&maze).to_csv(), CSV);
+    }
+
+    #[test]
+    fn reads_binary_and_plain_pgm() {
+        let walls = Grid::from_csv(CSV).unwrap().walls;
+        let binary = Grid::from_csv(CSV).unwrap().to_pgm();
+        let plain = "P2\n# comment\n3 3\n15\n15 15 15\n15 0 15\n15 15 0\n";
+
+        assert_eq!(Grid::from_pgm(&binary).unwrap().walls, walls);
+        assert_eq!(Grid::from_pgm(plain.as_bytes()).unwrap().walls, walls);
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        assert!(Grid::from_csv("0,0\n0\n").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use std::fs;
+
+use super::MazeFormat;
+use crate::json::Json;
+use crate::maze::tags::Tag;
+use crate::maze::Maze;
+use crate::DirectionSet;
+
+/// `{"width": w, "height": h, "cells": [...], "metadata": {...}, "tags": [[x, y, "key"], ...],
+/// "solution": [[x, y], ...]}`, with one bitmask of open directions per cell in row-major order and
+/// one entry per tag. Keys and doors with a [lock color](Maze::lock_color) other than `0` have it
+/// as a fourth element, `[x, y, "door", 3]`. `metadata`, `tags` and `solution` are left out when
+/// there are none.
+pub struct JsonFormat;
+
+impl MazeFormat for JsonFormat {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        fs::write(path, encode(maze, solution).to_string()).map_err(|err| format!("{path}: {err}"))
+    }
+
+    fn read(&self, path: &str) -> Result<Maze, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+
+        Json::parse(&text)
+            .and_then(|json| decode(&json))
+            .map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+pub fn encode(maze: &Maze, solution: Option<&[(usize, usize)]>) -> Json {
+    let (width, height) = maze.get_bounds();
+
+    let cells = maze
+        .cells()
+        .map(|(_, _, cell)| (cell.bits() as usize).into())
+        .collect();
+
+    let mut entries = vec![
+        ("width", width.into()),
+        ("height", height.into()),
+        ("cells", Json::Array(cells)),
+    ];
+
+    if !maze.metadata().is_empty() {
+        let metadata = maze
+            .metadata()
+            .iter()
+            .map(|(key, value)| (key, value.into()));
+
+        entries.push(("metadata", Json::object(metadata)));
+    }
+
+    let tags: Vec<_> = maze
+        .tagged()
+        .flat_map(|(x, y, tags)| {
+            let color = maze.lock_color(x, y);
+
+            tags.iter().map(move |tag| {
+                let mut entry = vec![x.into(), y.into(), tag.name().into()];
+                if color != 0 && matches!(tag, Tag::Key | Tag::Door) {
+                    entry.push(usize::from(color).into());
+                }
+
+                Json::Array(entry)
+            })
+        })
+        .collect();
+
+    if !tags.is_empty() {
+        entries.push(("tags", Json::Array(tags)));
+    }
+
+    if let Some(solution) = solution {
+        let points = solution
+            .iter()
+            .map(|&(x, y)| Json::Array(vec![x.into(), y.into()]))
+            .collect();
+
+        entries.push(("solution", Json::Array(points)));
+    }
+
+    Json::object(entries)
+}
+
+pub fn decode(json: &Json) -> Result<Maze, String> {
+    let dimension = |key: &str| {
+        json.get(key)
+            .and_then(Json::as_usize)
+            .filter(|v| (1..=u16::MAX as usize).contains(v))
+            .ok_or_else(|| format!("`{key}` must be a number between 1 and {}", u16::MAX))
+    };
+
+    let width = dimension("width")?;
+    let height = dimension("height")?;
+
+    let cells = json
+        .get("cells")
+        .and_then(Json::as_array)
+        .ok_or_else(|| String::from("`cells` must be an array"))?;
+
+    if cells.len() != width * height {
+        return Err(format!(
+            "expected {} cells, found {}",
+            width * height,
+            cells.len()
+        ));
+    }
+
+    let mut maze = Maze::new(width as u16, height as u16);
+
+    for (i, cell) in cells.iter().enumerate() {
+        let bits = cell
+            .as_usize()
+            .filter(|v| *v <= DirectionSet::ALL.bits() as usize)
+            .ok_or_else(|| format!("invalid cell {i}"))?;
+
+        let (x, y) = (i % width, i / width);
+
+        for direction in DirectionSet::from_bits(bits as u8).iter() {
+            maze.open(x, y, direction);
+        }
+    }
+
+    match json.get("metadata") {
+        None | Some(Json::Null) => {}
+        Some(Json::Object(entries)) => {
+            for (key, value) in entries {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| format!("metadata `{key}` must be a string"))?;
+
+                maze.metadata_mut().set(key, value)?;
+            }
+        }
+        Some(_) => return Err(String::from("`metadata` must be an object")),
+    }
+
+    match json.get("tags") {
+        None | Some(Json::Null) => {}
+        Some(Json::Array(entries)) => {
+            for entry in entries {
+                let (tag, color) = match entry.as_array() {
+                    Some([x, y, Json::String(name), color @ ..]) => (
+                        x.as_usize()
+                            .zip(y.as_usize())
+                            .filter(|&(x, y)| x < width && y < height)
+                            .zip(Tag::from_name(name.as_str())),
+                        match color {
+                            [] => Some(0),
+                            [color] => color.as_usize().and_then(|c| u8::try_from(c).ok()),
+                            _ => None,
+                        },
+                    ),
+                    _ => (None, None),
+                };
+                let (Some(((x, y), tag)), Some(color)) = (tag, color) else {
+                    return Err(format!("invalid tag {entry}"));
+                };
+
+                maze.tag(x, y, tag);
+                if color != 0 {
+                    if !matches!(tag, Tag::Key | Tag::Door) {
+                        return Err(format!(
+                            "invalid tag {entry}: only keys and doors have a color"
+                        ));
+                    }
+
+                    maze.set_lock_color(x, y, color)
+                        .map_err(|err| format!("invalid tag {entry}: {err}"))?;
+                }
+            }
+        }
+        Some(_) => return Err(String::from("`tags` must be an array")),
+    }
+
+    Ok(maze)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::fixtures::{assert_same_locks, locked_maze};
+    use crate::maze::tags::LOCK_COLORS;
+
+    #[test]
+    fn keeps_lock_colors() {
+        let maze = locked_maze();
+        let text = encode(&maze, None).to_string();
+
+        assert_same_locks(&decode(&Json::parse(&text).unwrap()).unwrap(), &maze);
+    }
+
+    #[test]
+    fn rejects_bad_lock_colors() {
+        let tagged = |tag: &str| {
+            let text = format!(r#"{{"width": 1, "height": 1, "cells": [0], "tags": [{tag}]}}"#);
+            decode(&Json::parse(&text).unwrap())
+        };
+
+        assert!(tagged(r#"[0, 0, "door", 1]"#).is_ok());
+        assert!(tagged(&format!(r#"[0, 0, "door", {LOCK_COLORS}]"#)).is_err());
+        assert!(tagged(r#"[0, 0, "trap", 1]"#).is_err());
+    }
+}
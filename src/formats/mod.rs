@@ -0,0 +1,111 @@
+pub mod ascii;
+pub mod dat;
+pub mod dot;
+pub mod grid;
+pub mod json;
+#[cfg(feature = "gui")]
+pub mod png;
+pub mod printable;
+pub mod share;
+pub mod svg;
+
+use std::path::Path;
+
+use crate::export::cut::{CutOptions, CutSvg, Dxf};
+use crate::export::mesh::{Gltf, MeshOptions, Obj};
+use crate::maze::Maze;
+use crate::Direction;
+
+/// A file format mazes can be written to, and possibly read back from
+pub trait MazeFormat {
+    fn name(&self) -> &'static str;
+
+    /// File extensions recognized as this format, without the dot
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// `solution` is drawn over the maze by formats that can show it and ignored by the rest
+    fn write(
+        &self,
+        maze: &Maze,
+        solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String>;
+
+    /// Export-only formats keep the default, which always fails
+    fn read(&self, _path: &str) -> Result<Maze, String> {
+        Err(format!("{} files can only be written", self.name()))
+    }
+}
+
+pub const FORMATS: &[&dyn MazeFormat] = &[
+    &dat::Dat,
+    &json::JsonFormat,
+    &ascii::Ascii,
+    #[cfg(feature = "gui")]
+    &png::Png::DEFAULT,
+    &svg::Svg(svg::SvgStyle::DEFAULT),
+    &dot::Dot,
+    &grid::Csv,
+    &grid::Pgm,
+    &Obj(MeshOptions::DEFAULT),
+    &Gltf(MeshOptions::DEFAULT),
+    &Dxf(CutOptions::DEFAULT),
+    &CutSvg(CutOptions::DEFAULT),
+];
+
+pub fn by_name(name: &str) -> Option<&'static dyn MazeFormat> {
+    FORMATS.iter().copied().find(|format| format.name() == name)
+}
+
+/// Picks the format from the extension of `path`
+pub fn for_path(path: &str) -> Option<&'static dyn MazeFormat> {
+    let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+
+    FORMATS
+        .iter()
+        .copied()
+        .find(|format| format.extensions().contains(&extension.as_str()))
+}
+
+/// Reads a maze in whichever format its extension says, falling back to the `.dat` format
+pub fn read(path: &str) -> Result<Maze, String> {
+    for_path(path).unwrap_or(&dat::Dat).read(path)
+}
+
+/// Whether the wall on the `direction` side of `(x, y)` is open from either side, which is how
+/// the maze is drawn
+pub(crate) fn is_open(maze: &Maze, (x, y): (usize, usize), direction: Direction) -> bool {
+    maze.get(x, y).contains(direction)
+        || direction
+            .travel(x, y)
+            .and_then(|(nx, ny)| maze.try_get(nx, ny))
+            .is_some_and(|cell| cell.contains(direction.opposite()))
+}
+
+/// Mazes shared by the round trip tests of several formats
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::maze::tags::{Tag, LOCK_COLORS};
+    use crate::maze::Maze;
+
+    /// Keys and doors of the default and other lock colors, one door also a trap
+    pub fn locked_maze() -> Maze {
+        let mut maze = Maze::new(3, 2);
+        maze.tag(0, 0, Tag::Key);
+        maze.tag(2, 0, Tag::Key);
+        maze.tag(1, 1, Tag::Door);
+        maze.tag(2, 1, Tag::Door);
+        maze.tag(2, 1, Tag::Trap);
+        maze.set_lock_color(2, 0, 3).unwrap();
+        maze.set_lock_color(2, 1, LOCK_COLORS - 1).unwrap();
+
+        maze
+    }
+
+    pub fn assert_same_locks(decoded: &Maze, maze: &Maze) {
+        assert!(decoded.tagged().eq(maze.tagged()));
+        for (x, y, _) in maze.tagged() {
+            assert_eq!(decoded.lock_color(x, y), maze.lock_color(x, y));
+        }
+    }
+}
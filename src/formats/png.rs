@@ -0,0 +1,307 @@
+use sfml::graphics::{Color, Image};
+
+use super::{is_open, MazeFormat};
+use crate::analysis::{distance_colors, gradient, HeatMap, HEAT_COLORS, RAINBOW_COLORS};
+use crate::consts::{wall_insets, wall_width, CELL_COLOR, EMPTY_CELL_COLOR, WALL_COLOR};
+use crate::maze::Maze;
+use crate::Direction;
+
+/// One pixel per cell and per wall, so a `w` by `h` maze becomes a `2w + 1` by `2h + 1` image.
+/// The solution is drawn in red.
+///
+/// When reading, anything that is not [`WALL_COLOR`] counts as open.
+pub struct Png {
+    /// Tint the cells with a rainbow by their distance from the start, see [`distance_colors`]
+    pub color_by_distance: bool,
+    /// Pixels per cell, drawing the maze like the window does instead of a pixel per cell and per
+    /// wall. Such images can not be read back.
+    pub cell_size: Option<usize>,
+    /// Pixels of wall between cells drawn with `cell_size`, see [`wall_width`]
+    pub wall_width: Option<usize>,
+}
+
+impl Png {
+    pub const DEFAULT: Self = Self {
+        color_by_distance: false,
+        cell_size: None,
+        wall_width: None,
+    };
+}
+
+impl MazeFormat for Png {
+    fn name(&self) -> &'static str {
+        "png"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["png"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        if let Some(cell_size) = self.cell_size {
+            return self.write_drawn(maze, solution, path, cell_size);
+        }
+
+        let (width, height) = maze.get_bounds();
+
+        let mut image =
+            Image::new_solid((width * 2 + 1) as u32, (height * 2 + 1) as u32, WALL_COLOR)
+                .map_err(|err| format!("could not create image: {err}"))?;
+
+        let mut set_pixel = |(px, py): (usize, usize), color: Color| {
+            image.set_pixel(px as u32, py as u32, color).unwrap();
+        };
+
+        let colors = self
+            .color_by_distance
+            .then(|| distance_colors(maze, &RAINBOW_COLORS));
+
+        for y in 0..height {
+            for x in 0..width {
+                let center = (x * 2 + 1, y * 2 + 1);
+
+                if maze.get(x, y).is_empty() {
+                    set_pixel(center, EMPTY_CELL_COLOR);
+                    continue;
+                }
+
+                let color = colors
+                    .as_ref()
+                    .and_then(|colors| colors[y * width + x])
+                    .unwrap_or(CELL_COLOR);
+                set_pixel(center, color);
+
+                for direction in Direction::ALL {
+                    if is_open(maze, (x, y), direction) {
+                        set_pixel(wall_pixel(center, direction), color);
+                    }
+                }
+            }
+        }
+
+        for step in solution.unwrap_or_default().windows(2) {
+            let from = (step[0].0 * 2 + 1, step[0].1 * 2 + 1);
+            let to = (step[1].0 * 2 + 1, step[1].1 * 2 + 1);
+
+            set_pixel(from, Color::RED);
+            set_pixel(((from.0 + to.0) / 2, (from.1 + to.1) / 2), Color::RED);
+            set_pixel(to, Color::RED);
+        }
+
+        image
+            .save_to_file(path)
+            .map_err(|err| format!("{path}: {err}"))
+    }
+
+    fn read(&self, path: &str) -> Result<Maze, String> {
+        let image = Image::from_file(path).map_err(|err| format!("{path}: {err}"))?;
+        let size = image.size();
+
+        if size.x < 3 || size.y < 3 || size.x.is_multiple_of(2) || size.y.is_multiple_of(2) {
+            return Err(format!("{path}: not a maze image"));
+        }
+
+        let width = (size.x as usize - 1) / 2;
+        let height = (size.y as usize - 1) / 2;
+
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(format!("{path}: maze too large"));
+        }
+
+        let is_open = |(px, py): (usize, usize)| {
+            image
+                .pixel_at(px as u32, py as u32)
+                .is_some_and(|color| color != WALL_COLOR)
+        };
+
+        let mut maze = Maze::new(width as u16, height as u16);
+
+        for y in 0..height {
+            for x in 0..width {
+                let center = (x * 2 + 1, y * 2 + 1);
+
+                for direction in Direction::ALL {
+                    if is_open(wall_pixel(center, direction)) {
+                        maze.open(x, y, direction);
+                    }
+                }
+            }
+        }
+
+        Ok(maze)
+    }
+}
+
+impl Png {
+    /// Cells of `cell_size` pixels inset by their walls, laid out like [`crate::maze::render`]
+    /// draws them in the window
+    fn write_drawn(
+        &self,
+        maze: &Maze,
+        solution: Option<&[(usize, usize)]>,
+        path: &str,
+        cell_size: usize,
+    ) -> Result<(), String> {
+        let (width, height) = maze.get_bounds();
+        let wall = wall_width(cell_size, self.wall_width);
+        let (before, after) = wall_insets(wall);
+
+        let mut image = Image::new_solid(
+            (width * cell_size) as u32,
+            (height * cell_size) as u32,
+            WALL_COLOR,
+        )
+        .map_err(|err| format!("could not create image: {err}"))?;
+
+        let mut fill = |(left, top, w, h): (usize, usize, usize, usize), color: Color| {
+            for py in top..top + h {
+                for px in left..left + w {
+                    image.set_pixel(px as u32, py as u32, color).unwrap();
+                }
+            }
+        };
+
+        let colors = self
+            .color_by_distance
+            .then(|| distance_colors(maze, &RAINBOW_COLORS));
+
+        for (x, y, cell) in maze.cells() {
+            let (left, top) = (x * cell_size, y * cell_size);
+            let center = (
+                left + before,
+                top + before,
+                cell_size - wall,
+                cell_size - wall,
+            );
+
+            if cell.is_empty() {
+                fill(center, EMPTY_CELL_COLOR);
+                continue;
+            }
+
+            let color = colors
+                .as_ref()
+                .and_then(|colors| colors[y * width + x])
+                .unwrap_or(CELL_COLOR);
+            fill(center, color);
+
+            // Passages to the right and down cover the wall on both sides
+            if x + 1 < width && is_open(maze, (x, y), Direction::RIGHT) {
+                fill(
+                    (
+                        left + cell_size - after,
+                        top + before,
+                        wall,
+                        cell_size - wall,
+                    ),
+                    color,
+                );
+            }
+            if y + 1 < height && is_open(maze, (x, y), Direction::DOWN) {
+                fill(
+                    (
+                        left + before,
+                        top + cell_size - after,
+                        cell_size - wall,
+                        wall,
+                    ),
+                    color,
+                );
+            }
+        }
+
+        // A line a third of the open part of a cell thick through the cell centers
+        let thickness = ((cell_size - wall) / 3).max(1);
+        let offset = (cell_size - thickness) / 2;
+
+        for step in solution.unwrap_or_default().windows(2) {
+            let (from, to) = (step[0].min(step[1]), step[0].max(step[1]));
+            let (left, top) = (from.0 * cell_size + offset, from.1 * cell_size + offset);
+
+            fill(
+                (
+                    left,
+                    top,
+                    (to.0 - from.0) * cell_size + thickness,
+                    (to.1 - from.1) * cell_size + thickness,
+                ),
+                Color::RED,
+            );
+        }
+
+        image
+            .save_to_file(path)
+            .map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+/// Like [`Png`], but every cell and its passages are colored by how often runs visited them, and
+/// each pixel becomes a `scale` by `scale` square
+pub fn write_heatmap(maze: &Maze, heat: &HeatMap, scale: usize, path: &str) -> Result<(), String> {
+    let (width, height) = maze.get_bounds();
+    let scale = scale.max(1);
+
+    let mut image = Image::new_solid(
+        ((width * 2 + 1) * scale) as u32,
+        ((height * 2 + 1) * scale) as u32,
+        WALL_COLOR,
+    )
+    .map_err(|err| format!("could not create image: {err}"))?;
+
+    let mut fill = |(px, py): (usize, usize), color: Color| {
+        for dy in 0..scale {
+            for dx in 0..scale {
+                image
+                    .set_pixel((px * scale + dx) as u32, (py * scale + dy) as u32, color)
+                    .unwrap();
+            }
+        }
+    };
+
+    for (x, y, cell) in maze.cells() {
+        let center = (x * 2 + 1, y * 2 + 1);
+
+        if cell.is_empty() {
+            fill(center, EMPTY_CELL_COLOR);
+            continue;
+        }
+
+        fill(center, gradient(&HEAT_COLORS, heat.heat((x, y))));
+
+        for direction in Direction::ALL {
+            if !is_open(maze, (x, y), direction) {
+                continue;
+            }
+
+            // Passages take the average heat of the cells on both sides
+            let neighbor = direction
+                .travel(x, y)
+                .filter(|&(nx, ny)| nx < width && ny < height)
+                .unwrap_or((x, y));
+            let passage = (heat.heat((x, y)) + heat.heat(neighbor)) / 2.;
+
+            fill(
+                wall_pixel(center, direction),
+                gradient(&HEAT_COLORS, passage),
+            );
+        }
+    }
+
+    image
+        .save_to_file(path)
+        .map_err(|err| format!("{path}: {err}"))
+}
+
+fn wall_pixel((px, py): (usize, usize), direction: Direction) -> (usize, usize) {
+    match direction {
+        Direction::UP => (px, py - 1),
+        Direction::RIGHT => (px + 1, py),
+        Direction::DOWN => (px, py + 1),
+        Direction::LEFT => (px - 1, py),
+    }
+}
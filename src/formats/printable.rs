@@ -0,0 +1,307 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use super::is_open;
+use crate::maze::Maze;
+use crate::Direction;
+
+const MARGIN: f32 = 48.;
+const TITLE_SIZE: f32 = 24.;
+const WALL_WIDTH: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paper {
+    A4,
+    Letter,
+}
+
+impl Paper {
+    /// Width and height in points
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            Paper::A4 => (595.28, 841.89),
+            Paper::Letter => (612., 792.),
+        }
+    }
+}
+
+/// A page-sized puzzle with an entrance in the top wall of the top left cell and an exit in the
+/// bottom wall of the bottom right one, optionally followed by the solution
+pub struct Sheet<'a> {
+    pub maze: &'a Maze,
+    pub title: &'a str,
+    pub paper: Paper,
+    /// Drawn on a second page
+    pub solution: Option<&'a [(usize, usize)]>,
+}
+
+/// A line from one point to another, in points from the top left of the page
+type Line = ((f32, f32), (f32, f32));
+
+struct Page {
+    title: String,
+    walls: Vec<Line>,
+    solution: Vec<(f32, f32)>,
+}
+
+impl Sheet<'_> {
+    /// Writes a PDF or SVG depending on the extension of `path`. SVG has no pages, so the
+    /// solution goes to `<name>.solution.svg` next to it.
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|v| v.to_str())
+            .map(str::to_lowercase);
+
+        let write = |path: &str, data: Vec<u8>| {
+            fs::write(path, data).map_err(|err| format!("{path}: {err}"))
+        };
+
+        match extension.as_deref() {
+            Some("pdf") => write(path, self.to_pdf()),
+            Some("svg") => {
+                let pages = self.pages();
+                write(path, self.page_to_svg(&pages[0]).into_bytes())?;
+
+                if let Some(page) = pages.get(1) {
+                    let solution_path = Path::new(path).with_extension("solution.svg");
+                    write(
+                        &solution_path.to_string_lossy(),
+                        self.page_to_svg(page).into_bytes(),
+                    )?;
+                }
+
+                Ok(())
+            }
+            _ => Err(format!("{path}: printable sheets must be .pdf or .svg")),
+        }
+    }
+
+    pub fn to_pdf(&self) -> Vec<u8> {
+        let (page_width, page_height) = self.paper.size();
+        let pages = self.pages();
+
+        // Objects 1 to 3 are the catalog, page tree and font, then a page and its content stream
+        // for every page
+        let mut objects = vec![
+            String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} /MediaBox [0 0 {page_width} {page_height}] >>",
+                (0..pages.len())
+                    .map(|i| format!("{} 0 R", 4 + i * 2))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                pages.len()
+            ),
+            String::from("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>"),
+        ];
+
+        for (i, page) in pages.iter().enumerate() {
+            let mut content = String::new();
+
+            // PDF puts the origin at the bottom left
+            let flip = |(x, y): (f32, f32)| (x, page_height - y);
+
+            let (x, y) = flip((MARGIN, MARGIN + TITLE_SIZE));
+            writeln!(
+                content,
+                "BT /F1 {TITLE_SIZE} Tf {x} {y} Td ({}) Tj ET",
+                pdf_string(&page.title)
+            )
+            .unwrap();
+
+            writeln!(content, "{WALL_WIDTH} w 2 J 0 G").unwrap();
+            for (from, to) in &page.walls {
+                let (from, to) = (flip(*from), flip(*to));
+                writeln!(content, "{} {} m {} {} l", from.0, from.1, to.0, to.1).unwrap();
+            }
+            content.push_str("S\n");
+
+            if let Some((first, rest)) = page.solution.split_first() {
+                let first = flip(*first);
+                writeln!(content, "{} w 1 J 1 j 1 0 0 RG", WALL_WIDTH * 2.).unwrap();
+                writeln!(content, "{} {} m", first.0, first.1).unwrap();
+                for point in rest {
+                    let point = flip(*point);
+                    writeln!(content, "{} {} l", point.0, point.1).unwrap();
+                }
+                content.push_str("S\n");
+            }
+
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                5 + i * 2
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{content}endstream",
+                content.len()
+            ));
+        }
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = vec![];
+
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n{object}\nendobj\n", i + 1).bytes());
+        }
+
+        let xref = out.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            writeln!(trailer, "{offset:010} 00000 n ").unwrap();
+        }
+        write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .unwrap();
+        out.extend(trailer.bytes());
+
+        out
+    }
+
+    fn page_to_svg(&self, page: &Page) -> String {
+        let (page_width, page_height) = self.paper.size();
+        let mut out = String::new();
+
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {page_width} {page_height}" width="{page_width}pt" height="{page_height}pt">"#
+        )
+        .unwrap();
+        writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+        writeln!(
+            out,
+            r#"<text x="{MARGIN}" y="{}" font-family="Helvetica, Arial, sans-serif" font-size="{TITLE_SIZE}">{}</text>"#,
+            MARGIN + TITLE_SIZE,
+            xml_escape(&page.title)
+        )
+        .unwrap();
+
+        let mut walls = String::new();
+        for (from, to) in &page.walls {
+            write!(walls, "M{} {}L{} {}", from.0, from.1, to.0, to.1).unwrap();
+        }
+        writeln!(
+            out,
+            r#"<path d="{walls}" stroke="black" stroke-width="{WALL_WIDTH}" stroke-linecap="square" fill="none"/>"#
+        )
+        .unwrap();
+
+        if !page.solution.is_empty() {
+            let points: Vec<String> = page
+                .solution
+                .iter()
+                .map(|(x, y)| format!("{x},{y}"))
+                .collect();
+
+            writeln!(
+                out,
+                r#"<polyline points="{}" stroke="red" stroke-width="{}" stroke-linejoin="round" stroke-linecap="round" fill="none"/>"#,
+                points.join(" "),
+                WALL_WIDTH * 2.
+            )
+            .unwrap();
+        }
+
+        out.push_str("</svg>\n");
+
+        out
+    }
+
+    fn pages(&self) -> Vec<Page> {
+        let (width, height) = self.maze.get_bounds();
+        let (page_width, page_height) = self.paper.size();
+
+        // Fit the maze below the title, centered horizontally
+        let top = MARGIN + TITLE_SIZE * 2.;
+        let cell = ((page_width - MARGIN * 2.) / width as f32)
+            .min((page_height - top - MARGIN) / height as f32);
+        let left = (page_width - cell * width as f32) / 2.;
+
+        let point = |x: usize, y: usize| (left + x as f32 * cell, top + y as f32 * cell);
+        let center = |(x, y): (usize, usize)| {
+            (
+                left + (x as f32 + 0.5) * cell,
+                top + (y as f32 + 0.5) * cell,
+            )
+        };
+
+        let entrance = (0, 0);
+        let exit = (width - 1, height - 1);
+
+        let is_closed = |pos: (usize, usize), direction| {
+            let is_opening = (pos == entrance && direction == Direction::UP)
+                || (pos == exit && direction == Direction::DOWN);
+
+            !is_opening && !is_open(self.maze, pos, direction)
+        };
+
+        // Each cell draws its top and left walls, the last row and column also their far side
+        let mut walls = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                if is_closed((x, y), Direction::UP) {
+                    walls.push((point(x, y), point(x + 1, y)));
+                }
+                if is_closed((x, y), Direction::LEFT) {
+                    walls.push((point(x, y), point(x, y + 1)));
+                }
+                if x == width - 1 && is_closed((x, y), Direction::RIGHT) {
+                    walls.push((point(x + 1, y), point(x + 1, y + 1)));
+                }
+                if y == height - 1 && is_closed((x, y), Direction::DOWN) {
+                    walls.push((point(x, y + 1), point(x + 1, y + 1)));
+                }
+            }
+        }
+
+        let mut pages = vec![Page {
+            title: self.title.to_string(),
+            walls: walls.clone(),
+            solution: vec![],
+        }];
+
+        if let Some(solution) = self.solution.filter(|path| !path.is_empty()) {
+            // Lead the line in through the entrance and out through the exit
+            let (entrance_x, entrance_y) = center(entrance);
+            let (exit_x, exit_y) = center(exit);
+
+            let solution = std::iter::once((entrance_x, entrance_y - cell))
+                .chain(solution.iter().map(|pos| center(*pos)))
+                .chain(std::iter::once((exit_x, exit_y + cell)))
+                .collect();
+
+            pages.push(Page {
+                title: format!("{} - Solution", self.title),
+                walls,
+                solution,
+            });
+        }
+
+        pages
+    }
+}
+
+/// Escapes a PDF literal string. The standard fonts only cover Latin-1, so anything else is
+/// replaced with `?`.
+fn pdf_string(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{c}"),
+            c if (' '..='~').contains(&c) => c.to_string(),
+            c if (c as u32) < 256 && (c as u32) >= 0xa0 => format!("\\{:03o}", c as u32),
+            _ => String::from("?"),
+        })
+        .collect()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,136 @@
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use super::dat;
+use crate::maze::Maze;
+
+/// Share codes are the `.dat` bytes, deflated and written in URL-safe base64 without padding
+/// after this prefix, whose number is bumped if that ever changes. Whitespace is ignored when
+/// reading, so codes survive being wrapped by chat clients.
+pub const PREFIX: &str = "maze1:";
+
+/// Largest maze a code is read into, so a short code can not inflate into gigabytes
+const MAX_DECODED: u64 = 16 << 20;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub fn encode(maze: &Maze) -> Result<String, String> {
+    let mut encoder = DeflateEncoder::new(vec![], Compression::best());
+    encoder
+        .write_all(&dat::encode(maze)?)
+        .and_then(|_| encoder.finish())
+        .map(|deflated| format!("{PREFIX}{}", to_base64(&deflated)))
+        .map_err(|err| format!("could not compress maze: {err}"))
+}
+
+pub fn decode(code: &str) -> Result<Maze, String> {
+    let code: String = code.split_whitespace().collect();
+    let Some(code) = code.strip_prefix(PREFIX) else {
+        return Err(format!("share codes start with `{PREFIX}`"));
+    };
+
+    let deflated = from_base64(code)?;
+
+    let mut data = vec![];
+    DeflateDecoder::new(deflated.as_slice())
+        .take(MAX_DECODED + 1)
+        .read_to_end(&mut data)
+        .map_err(|err| format!("share code is damaged: {err}"))?;
+
+    if data.len() as u64 > MAX_DECODED {
+        return Err(String::from("share code holds a maze too large to read"));
+    }
+
+    dat::decode(&data)
+}
+
+fn to_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | (byte as u32) << (16 - 8 * i)
+        });
+
+        // Each byte takes one character and a bit, rounded up
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+
+    out
+}
+
+fn from_base64(code: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(code.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut count = 0;
+
+    for c in code.chars() {
+        let value = ALPHABET
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or_else(|| format!("`{c}` can not be in a share code"))?;
+
+        bits = bits << 6 | value as u32;
+        count += 6;
+
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Direction;
+
+    fn assert_round_trip(maze: &Maze) {
+        let decoded = decode(&encode(maze).unwrap()).unwrap();
+
+        assert_eq!(decoded.get_bounds(), maze.get_bounds());
+        assert!(decoded.cells().eq(maze.cells()));
+    }
+
+    #[test]
+    fn keeps_odd_sized_mazes() {
+        let mut maze = Maze::new(5, 3);
+        for y in 0..3 {
+            for x in 0..4 {
+                maze.carve(x, y, Direction::RIGHT);
+            }
+        }
+        maze.carve(0, 0, Direction::DOWN);
+        maze.carve(4, 1, Direction::DOWN);
+
+        assert_round_trip(&maze);
+    }
+
+    #[test]
+    fn keeps_an_uncarved_last_cell() {
+        let mut maze = Maze::new(2, 2);
+        maze.carve(0, 0, Direction::RIGHT);
+        maze.carve(0, 0, Direction::DOWN);
+        assert!(maze.get(1, 1).is_empty());
+
+        assert_round_trip(&maze);
+    }
+
+    #[test]
+    fn ignores_whitespace_and_rejects_other_prefixes() {
+        let maze = Maze::new(3, 3);
+        let code = encode(&maze).unwrap();
+        let (head, tail) = code.split_at(code.len() / 2);
+
+        assert!(decode(&format!("{head}\n  {tail}")).is_ok());
+        assert!(decode(&code.replacen(PREFIX, "maze0:", 1)).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::fs;
+
+use super::{is_open, MazeFormat};
+use crate::analysis::{distance_colors, RAINBOW_COLORS};
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Look of an SVG export. Sizes are in millimeters, colors anything SVG understands, like `red`
+/// or `#336699`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgStyle {
+    pub cell_size: f32,
+    pub wall_width: f32,
+    pub wall_color: Cow<'static, str>,
+    pub solution_width: f32,
+    pub solution_color: Cow<'static, str>,
+    /// Round the turns of the solution into arcs instead of sharp corners
+    pub rounded: bool,
+    pub dashed: bool,
+    /// Tint the cells with a rainbow by their distance from the start, see [`distance_colors`]
+    pub color_by_distance: bool,
+}
+
+impl SvgStyle {
+    pub const DEFAULT: Self = Self {
+        cell_size: 4.,
+        wall_width: 0.5,
+        wall_color: Cow::Borrowed("black"),
+        solution_width: 1.,
+        solution_color: Cow::Borrowed("red"),
+        rounded: true,
+        dashed: false,
+        color_by_distance: false,
+    };
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, size) in [
+            ("cell size", self.cell_size),
+            ("wall width", self.wall_width),
+            ("solution width", self.solution_width),
+        ] {
+            if size.is_nan() || size <= 0. {
+                return Err(format!("{name} must be above 0"));
+            }
+        }
+
+        for (name, color) in [
+            ("wall color", &self.wall_color),
+            ("solution color", &self.solution_color),
+        ] {
+            let valid = !color.is_empty()
+                && color
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "#(),.% ".contains(c));
+
+            if !valid {
+                return Err(format!("invalid {name} `{color}`"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Vector drawing of the walls, with the solution as a line. Export only.
+pub struct Svg(pub SvgStyle);
+
+impl MazeFormat for Svg {
+    fn name(&self) -> &'static str {
+        "svg"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["svg"]
+    }
+
+    fn write(
+        &self,
+        maze: &Maze,
+        solution: Option<&[(usize, usize)]>,
+        path: &str,
+    ) -> Result<(), String> {
+        self.0.validate()?;
+
+        fs::write(path, encode(maze, solution, &self.0)).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+pub fn encode(maze: &Maze, solution: Option<&[(usize, usize)]>, style: &SvgStyle) -> String {
+    let (width, height) = maze.get_bounds();
+    let cell = style.cell_size;
+    let margin = style.wall_width;
+    let (total_width, total_height) = (
+        width as f32 * cell + margin * 2.,
+        height as f32 * cell + margin * 2.,
+    );
+
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-{margin} -{margin} {total_width} {total_height}" width="{total_width}mm" height="{total_height}mm">"#,
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<rect x="-{margin}" y="-{margin}" width="100%" height="100%" fill="white"/>"#
+    )
+    .unwrap();
+
+    if style.color_by_distance {
+        let colors = distance_colors(maze, &RAINBOW_COLORS);
+
+        for (x, y, _) in maze.cells() {
+            let Some(color) = colors[y * width + x] else {
+                continue;
+            };
+
+            writeln!(
+                out,
+                r##"<rect x="{}" y="{}" width="{cell}" height="{cell}" fill="#{:02x}{:02x}{:02x}"/>"##,
+                x as f32 * cell,
+                y as f32 * cell,
+                color.r,
+                color.g,
+                color.b
+            )
+            .unwrap();
+        }
+    }
+
+    // Empty cells, like those removed by sparsifying, are solid
+    let mut solid = String::new();
+    for (x, y, value) in maze.cells() {
+        if value.is_empty() {
+            let (left, top) = (x as f32 * cell, y as f32 * cell);
+
+            write!(solid, "M{left} {top}h{cell}v{cell}h-{cell}z").unwrap();
+        }
+    }
+    if !solid.is_empty() {
+        writeln!(out, r#"<path d="{solid}" fill="{}"/>"#, style.wall_color).unwrap();
+    }
+
+    // Each cell draws its top and left walls, the last row and column also their far side
+    let mut walls = String::new();
+    for y in 0..height {
+        for x in 0..width {
+            let (left, top) = (x as f32 * cell, y as f32 * cell);
+
+            if !is_open(maze, (x, y), Direction::UP) {
+                write!(walls, "M{left} {top}h{cell}").unwrap();
+            }
+            if !is_open(maze, (x, y), Direction::LEFT) {
+                write!(walls, "M{left} {top}v{cell}").unwrap();
+            }
+            if x == width - 1 && !is_open(maze, (x, y), Direction::RIGHT) {
+                write!(walls, "M{} {top}v{cell}", left + cell).unwrap();
+            }
+            if y == height - 1 && !is_open(maze, (x, y), Direction::DOWN) {
+                write!(walls, "M{left} {}h{cell}", top + cell).unwrap();
+            }
+        }
+    }
+    writeln!(
+        out,
+        r#"<path d="{walls}" stroke="{}" stroke-width="{}" stroke-linecap="square" fill="none"/>"#,
+        style.wall_color, style.wall_width
+    )
+    .unwrap();
+
+    if let Some(solution) = solution.filter(|path| !path.is_empty()) {
+        let dashes = if style.dashed {
+            format!(
+                r#" stroke-dasharray="{} {}""#,
+                style.solution_width * 2.,
+                style.solution_width * 1.5
+            )
+        } else {
+            String::new()
+        };
+
+        writeln!(
+            out,
+            r#"<path d="{}" stroke="{}" stroke-width="{}" stroke-linejoin="{}" stroke-linecap="round" fill="none"{dashes}/>"#,
+            solution_path(solution, cell, style.rounded),
+            style.solution_color,
+            style.solution_width,
+            if style.rounded { "round" } else { "miter" },
+        )
+        .unwrap();
+    }
+
+    out.push_str("</svg>\n");
+
+    out
+}
+
+/// Path data through the centers of the cells. Rounded turns start half way into the cell before
+/// the turn and bend through its center towards the cell after it.
+fn solution_path(solution: &[(usize, usize)], cell: f32, rounded: bool) -> String {
+    let center = |(x, y): (usize, usize)| ((x as f32 + 0.5) * cell, (y as f32 + 0.5) * cell);
+
+    let (x, y) = center(solution[0]);
+    let mut data = format!("M{x} {y}");
+
+    for (i, &pos) in solution.iter().enumerate().skip(1) {
+        let (x, y) = center(pos);
+        let previous = center(solution[i - 1]);
+        let next = solution.get(i + 1).map(|&next| center(next));
+
+        match next {
+            // A turn, as neither coordinate is shared by the cells on both sides
+            Some(next) if rounded && previous.0 != next.0 && previous.1 != next.1 => {
+                let from = ((previous.0 + x) / 2., (previous.1 + y) / 2.);
+                let to = ((x + next.0) / 2., (y + next.1) / 2.);
+
+                write!(data, "L{} {}Q{x} {y} {} {}", from.0, from.1, to.0, to.1).unwrap();
+            }
+            _ => write!(data, "L{x} {y}").unwrap(),
+        }
+    }
+
+    data
+}
@@ -1,32 +1,119 @@
+pub mod analysis;
+#[cfg(feature = "gui")]
+pub mod camera;
+pub mod color;
+pub mod config;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fog;
+pub mod formats;
+pub mod json;
 pub mod maze;
+#[cfg(feature = "gui")]
+pub mod minimap;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod settings;
+pub mod theme;
+#[cfg(feature = "gui")]
+pub mod ui;
 
 pub mod consts {
     use std::sync::{LazyLock, RwLock};
 
-    use sfml::graphics::Color;
+    #[cfg(feature = "gui")]
+    use sfml::window::VideoMode;
+
+    use crate::color::Color;
 
     pub const DEFAULT_SPEED: u32 = 60;
+    /// Frames per second without V-Sync, independent of the steps per second
+    pub const FRAMERATE_LIMIT: u32 = 60;
+
+    pub const DEFAULT_IMAGE_BIAS: f32 = 8.;
+    pub const DEFAULT_IMAGE_THRESHOLD: f32 = 0.5;
+    pub const DEFAULT_CAVE_FILL: f32 = 0.45;
 
     pub const DEFAULT_MAZE_WIDTH: u16 = 32;
     pub const DEFAULT_MAZE_HEIGHT: u16 = 32;
-    pub const PREFERRED_SCREEN_SIZE: usize = 512;
-    pub const WALL_WIDTH: usize = 1;
+    /// Smallest width and height taken for a maze to generate, as the generators need a start
+    /// and an end cell
+    pub const MIN_MAZE_SIZE: u16 = 2;
+    /// Share of the desktop a maze window is fitted into
+    pub const SCREEN_FILL: f32 = 0.8;
+    /// Desktop size assumed when the system does not report one
+    pub const FALLBACK_SCREEN_SIZE: (u32, u32) = (1280, 720);
+    pub const MIN_CELL_SIZE: usize = 3;
+    pub const MAX_CELL_SIZE: usize = 48;
+    /// Share of a cell taken up by the wall between it and its neighbor, unless set with
+    /// `--wall-width`
+    pub const WALL_SHARE: f32 = 0.125;
     
     pub const WALL_COLOR: Color = Color::rgb(0, 0, 0);
     pub const CELL_COLOR: Color = Color::rgb(255, 255, 255);
     pub const EMPTY_CELL_COLOR: Color = Color::rgb(64, 64, 64);
     
     pub static CELL_SIZE: LazyLock<RwLock<usize>> = LazyLock::new(|| RwLock::new(16));
+    /// Pixels of wall between cells, `None` to follow the cell size
+    pub static WALL_WIDTH: LazyLock<RwLock<Option<usize>>> = LazyLock::new(|| RwLock::new(None));
 
     pub fn get_cell_size() -> usize {
         *CELL_SIZE.read().unwrap()
     }
 
-    pub fn update_cell_size(bounds: &(usize, usize)) {
-        let average_size = (bounds.0 + bounds.1) / 2;
+    pub fn set_cell_size(size: usize) {
+        *CELL_SIZE.write().unwrap() = size;
+    }
+
+    pub fn set_wall_width(width: Option<usize>) {
+        *WALL_WIDTH.write().unwrap() = width;
+    }
+
+    pub fn get_wall_width() -> usize {
+        wall_width(get_cell_size(), *WALL_WIDTH.read().unwrap())
+    }
+
+    /// Pixels of wall between cells of `cell_size`, `fixed` if given, otherwise [`WALL_SHARE`] of
+    /// the cell. Always at least a pixel, so walls never vanish, and at most all but a pixel of the
+    /// cell.
+    pub fn wall_width(cell_size: usize, fixed: Option<usize>) -> usize {
+        let width = fixed.unwrap_or((cell_size as f32 * WALL_SHARE).round() as usize);
+        width.clamp(1, cell_size.saturating_sub(1).max(1))
+    }
+
+    /// Pixels of a wall `width` wide on the left or top side of a cell and on its right or bottom
+    /// side. Odd widths leave the extra pixel on the right or bottom, so cells start and end on
+    /// whole pixels.
+    pub fn wall_insets(width: usize) -> (usize, usize) {
+        (width / 2, width - width / 2)
+    }
+
+    /// Part of the desktop a window may cover, in pixels
+    #[cfg(feature = "gui")]
+    pub fn screen_size() -> (f32, f32) {
+        let mode = VideoMode::desktop_mode();
+        let (width, height) = if mode.width == 0 || mode.height == 0 {
+            FALLBACK_SCREEN_SIZE
+        } else {
+            (mode.width, mode.height)
+        };
+
+        (width as f32 * SCREEN_FILL, height as f32 * SCREEN_FILL)
+    }
+
+    /// Sets the cell size for a maze of `bounds` cells, `fixed` if given, otherwise the largest
+    /// that fits the maze on the screen within [`MIN_CELL_SIZE`] and [`MAX_CELL_SIZE`]
+    #[cfg(feature = "gui")]
+    pub fn update_cell_size(bounds: &(usize, usize), fixed: Option<usize>) {
+        let size = fixed.unwrap_or_else(|| {
+            let (width, height) = screen_size();
+            let fit = (width / bounds.0.max(1) as f32).min(height / bounds.1.max(1) as f32);
+
+            (fit as usize).clamp(MIN_CELL_SIZE, MAX_CELL_SIZE)
+        });
 
-        let mut w = CELL_SIZE.write().unwrap();
-        *w = (PREFERRED_SCREEN_SIZE / average_size).max(5);
+        set_cell_size(size);
     }
 }
 
@@ -39,6 +126,13 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::UP,
+        Direction::RIGHT,
+        Direction::DOWN,
+        Direction::LEFT,
+    ];
+
     pub fn opposite(&self) -> Self {
         match self {
             Direction::UP => Direction::DOWN,
@@ -48,12 +142,121 @@ impl Direction {
         }
     }
 
-    pub fn travel(&self, x: usize, y: usize) -> (usize, usize) {
+    pub fn clockwise(&self) -> Self {
+        match self {
+            Direction::UP => Direction::RIGHT,
+            Direction::RIGHT => Direction::DOWN,
+            Direction::DOWN => Direction::LEFT,
+            Direction::LEFT => Direction::UP,
+        }
+    }
+
+    pub fn counter_clockwise(&self) -> Self {
+        self.clockwise().opposite()
+    }
+
+    /// The letter of the direction in URDL solutions
+    pub fn letter(&self) -> char {
         match self {
-            Direction::UP => (x, y - 1),
-            Direction::DOWN => (x, y + 1),
-            Direction::LEFT => (x - 1, y),
-            Direction::RIGHT => (x + 1, y),
+            Direction::UP => 'U',
+            Direction::RIGHT => 'R',
+            Direction::DOWN => 'D',
+            Direction::LEFT => 'L',
         }
     }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        Direction::ALL
+            .into_iter()
+            .find(|direction| direction.letter() == letter)
+    }
+
+    /// The direction from `from` to an orthogonally adjacent `to`
+    pub fn between(from: (usize, usize), to: (usize, usize)) -> Option<Self> {
+        if from.1 == to.1 && to.0 == from.0 + 1 {
+            Some(Direction::RIGHT)
+        } else if from.1 == to.1 && from.0 == to.0 + 1 {
+            Some(Direction::LEFT)
+        } else if from.0 == to.0 && to.1 == from.1 + 1 {
+            Some(Direction::DOWN)
+        } else if from.0 == to.0 && from.1 == to.1 + 1 {
+            Some(Direction::UP)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `None` when travelling off the top or left edge. Right and bottom edges depend on
+    /// the maze, so callers must still check the result against its bounds.
+    pub fn travel(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        match self {
+            Direction::UP => Some((x, y.checked_sub(1)?)),
+            Direction::DOWN => Some((x, y.checked_add(1)?)),
+            Direction::LEFT => Some((x.checked_sub(1)?, y)),
+            Direction::RIGHT => Some((x.checked_add(1)?, y)),
+        }
+    }
+}
+
+/// A set of [`Direction`]s, stored as the same 4-bit mask used for maze cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DirectionSet(u8);
+
+impl DirectionSet {
+    pub const EMPTY: Self = Self(0);
+    pub const ALL: Self = Self(0b1111);
+
+    /// Bits outside of the lower nibble are ignored
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & 0b1111)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(&self, direction: Direction) -> bool {
+        self.0 & direction as u8 != 0
+    }
+
+    pub fn insert(&mut self, direction: Direction) {
+        self.0 |= direction as u8;
+    }
+
+    pub fn remove(&mut self, direction: Direction) {
+        self.0 &= !(direction as u8);
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Iterates in the order of [`Direction::ALL`]
+    pub fn iter(&self) -> impl Iterator<Item = Direction> {
+        let set = *self;
+
+        Direction::ALL.into_iter().filter(move |d| set.contains(*d))
+    }
+}
+
+impl From<Direction> for DirectionSet {
+    fn from(direction: Direction) -> Self {
+        Self(direction as u8)
+    }
+}
+
+impl FromIterator<Direction> for DirectionSet {
+    fn from_iter<T: IntoIterator<Item = Direction>>(iter: T) -> Self {
+        let mut set = Self::EMPTY;
+
+        for direction in iter {
+            set.insert(direction);
+        }
+
+        set
+    }
 }
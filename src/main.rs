@@ -1,275 +1,1192 @@
-use std::fmt::Display;
+mod cli;
+mod panel;
+mod progress;
+mod referee;
+mod run;
+mod server;
+mod sound;
+mod trace;
+mod viewer;
+mod watch;
+
 use std::fs;
-use std::time::Instant;
+use std::time::Duration;
 
-use clap::{Parser, ValueEnum};
-use maze::maze::{generators::*, solvers::*, MazeSolver};
-use maze::maze::{Maze, MazeGenerator};
+use clap::ValueEnum;
+use cli::*;
+use maze::analysis::{maze_stats, HeatMap, MazeStats};
+use maze::export::cut::{CutOptions, CutSvg, Dxf};
+use maze::export::mesh::{Gltf, MeshOptions, Obj};
+use maze::formats::png::Png;
+use maze::formats::printable::Sheet;
+use maze::formats::svg::{Svg, SvgStyle};
+use maze::formats::{self, png, MazeFormat};
+use maze::json::Json;
+use maze::maze::agents::{Agent, Pledge, Race, RandomMouse, Tremaux, WallFollower};
+use maze::maze::infinite::InfiniteMaze;
+use maze::maze::picture::WeightMap;
+use maze::maze::symmetry::{Symmetric, Symmetry};
+use maze::maze::tags::Tag;
+use maze::maze::Maze;
+use maze::maze::{generators::*, solvers::*, MazeSolver, SolveStep};
+use maze::settings;
 use maze::{consts::*, Direction};
-use sfml::window::{ContextSettings, Key, VideoMode};
-use sfml::{
-    graphics::{Color, RenderTarget, RenderWindow},
-    window::{Event, Style},
-};
-
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
-enum AlgorithmArg {
-    /// Depth-First Search
-    DFS,
-    /// Breadth-First Search
-    BFS,
-    /// A*
-    AStar,
-}
-
-impl Display for AlgorithmArg {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                AlgorithmArg::BFS => "bfs",
-                AlgorithmArg::DFS => "dfs",
-                AlgorithmArg::AStar => "a-star",
+use progress::ProgressBar;
+use referee::ExternalAgent;
+use run::{PhaseStats, Run};
+use server::Server;
+use trace::Trace;
+use viewer::Reload;
+use watch::FileWatcher;
+
+/// Human readable messages, moved to stderr when stdout is reserved for --json-stats
+macro_rules! report {
+    ($json:expr, $($arg:tt)*) => {
+        if $json {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+fn parse_output_filename(filename: &str) -> (String, String) {
+    let segments: Vec<_> = filename
+        .split("/")
+        .last()
+        .unwrap_or("maze.dat")
+        .split(".")
+        .collect();
+
+    let name = segments[..segments.len() - 1].join(".");
+
+    (format!("{name}.dat"), format!("{name}.solution.dat"))
+}
+
+fn make_generator(args: &GeneratorArgs, bounds: (usize, usize)) -> Result<Generator, String> {
+    if args.fill.is_some_and(|fill| !(0.0..=1.0).contains(&fill)) {
+        return Err(String::from("Fill must be between 0 and 1"));
+    }
+
+    let Some(symmetry) = args.symmetry.map(Symmetry::from) else {
+        return make_base_generator(args, bounds);
+    };
+
+    let region = symmetry.region(bounds);
+    if region.0 * region.1 < 2 {
+        return Err(format!(
+            "Maze is too small for {} symmetry",
+            symmetry.name()
+        ));
+    }
+
+    let base = make_base_generator(args, region)?;
+    Ok(Generator::Symmetric(Box::new(Symmetric::new(
+        base, symmetry, bounds,
+    ))))
+}
+
+fn make_base_generator(args: &GeneratorArgs, bounds: (usize, usize)) -> Result<Generator, String> {
+    let seed = args.seed.unwrap_or_else(random_seed);
+
+    if args.cave {
+        if !(0.0..1.0).contains(&args.cave_fill) {
+            return Err(String::from("Cave fill must be at least 0 and below 1"));
+        }
+
+        return Ok(Generator::Cave(Cave::with_seed(
+            bounds,
+            args.cave_fill,
+            seed,
+        )));
+    }
+
+    match &args.from_image {
+        None => Ok(Generator::Wilson(Wilson::with_seed(bounds, seed))),
+        Some(path) => {
+            if !args.image_bias.is_finite() || args.image_bias < 0. {
+                return Err(String::from("Image bias must be at least 0"));
             }
-        )
+            if !(0.0..=1.0).contains(&args.image_threshold) {
+                return Err(String::from("Image threshold must be between 0 and 1"));
+            }
+
+            let weights = WeightMap::from_image(path, bounds, args.image_threshold)
+                .map_err(|err| format!("Could not use image: {err}"))?;
+
+            Ok(Generator::Picture(PictureDFS::with_seed(
+                weights,
+                args.image_bias,
+                seed,
+            )))
+        }
     }
 }
 
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None, disable_help_flag = true)]
-struct Cli {
-    /// Input maze data path
-    #[arg(short, long)]
-    input: Option<String>,
+fn report_seed(json: bool, generator: &Generator) {
+    report!(
+        json,
+        "Generating with {} from seed {}",
+        generator.name(),
+        generator.seed()
+    );
+}
 
-    /// Output maze data path
-    #[arg(short, long)]
-    output: Option<String>,
+fn make_solve_algorithm(args: &SolverArgs, bounds: (usize, usize)) -> Result<Algorithm, String> {
+    if let Some(point) = args
+        .via
+        .iter()
+        .find(|(x, y)| *x >= bounds.0 || *y >= bounds.1)
+    {
+        return Err(format!("Waypoint {point:?} is outside of the maze"));
+    }
+    if args.heuristic_weight.is_nan() || args.heuristic_weight < 0. {
+        return Err(String::from("Heuristic weight must be at least 0"));
+    }
 
-    /// Save solution to <OUTPUT>.solution.dat
-    #[arg(long)]
-    save_solution: bool,
+    let start = (0, 0);
+    let end = (bounds.0 - 1, bounds.1 - 1);
 
-    /// See generation live
-    #[arg(short, long)]
-    debug: bool,
+    if args.dynamic.is_some() {
+        return Ok(Algorithm::Replanning(ReplanningSolver::between(
+            bounds, start, end,
+        )));
+    }
 
-    /// Do not solve the maze, just generate/load a maze
-    #[arg(long)]
-    no_solve: bool,
+    if args.via.is_empty() {
+        return Ok(args.alg.solver(bounds, start, end));
+    }
 
-    /// FPS / steps per second of the maze generation/solver
-    #[arg(long, default_value_t = DEFAULT_SPEED)]
-    speed: u32,
+    let points: Vec<_> = std::iter::once(start)
+        .chain(args.via.iter().copied())
+        .chain(std::iter::once(end))
+        .collect();
 
-    /// V-Sync
-    #[arg(long)]
-    vsync: bool,
+    Ok(Algorithm::MultiGoal(MultiGoalSolver::new(
+        points
+            .windows(2)
+            .map(|leg| args.alg.solver(bounds, leg[0], leg[1]))
+            .collect(),
+    )))
+}
 
-    /// Which algorithm to use
-    #[arg(short, long, default_value_t = AlgorithmArg::DFS)]
-    alg: AlgorithmArg,
+/// Moves each racer may take per maze cell before it gives up
+const RACE_MOVES_PER_CELL: usize = 100;
 
-    /// Instantly solve the maze
-    #[arg(long, default_value_t = false)]
-    instant: bool,
+fn make_race(bounds: (usize, usize)) -> Race {
+    let start = (0, 0);
 
-    /// Maze width
-    #[arg(short, long, default_value_t = DEFAULT_MAZE_WIDTH)]
-    width: u16,
+    Race::new(
+        vec![
+            Box::new(WallFollower::new(start)),
+            Box::new(RandomMouse::new(start)),
+            Box::new(Tremaux::new(bounds, start)),
+            Box::new(Pledge::new(start, Direction::DOWN)),
+        ],
+        (bounds.0 - 1, bounds.1 - 1),
+        bounds.0 * bounds.1 * RACE_MOVES_PER_CELL,
+    )
+}
 
-    /// Maze height
-    #[arg(short, long, default_value_t = DEFAULT_MAZE_HEIGHT)]
-    height: u16,
+fn solution_to_string(solution: &[(usize, usize)]) -> String {
+    solution
+        .windows(2)
+        .map(|step| match Direction::between(step[0], step[1]) {
+            Some(direction) => direction.letter(),
+            None => unreachable!("solution steps are between adjacent cells"),
+        })
+        .collect()
+}
 
-    /// Display help
-    #[clap(long, action = clap::ArgAction::HelpLong)]
-    help: Option<bool>,
+/// `entries` were already checked by [`parse_meta`]
+fn apply_meta(maze: &mut Maze, entries: &[(String, String)]) {
+    for (key, value) in entries {
+        maze.metadata_mut().set(key, value).unwrap();
+    }
 }
 
-fn parse_output_filename(filename: &str) -> (String, String) {
-    let segments: Vec<_> = filename
-        .split("/")
-        .last()
-        .unwrap_or("maze.dat")
-        .split(".")
-        .collect();
+/// Remembers the options of a command with a window as the defaults of the next one
+fn remember_options(window: &WindowArgs, size: Option<&SizeArgs>, alg: Option<AlgorithmArg>) {
+    settings::update(|settings| {
+        settings.theme = window
+            .theme
+            .to_possible_value()
+            .map(|theme| theme.get_name().to_string());
 
-    let name = segments[..segments.len() - 1].join(".");
+        if let Some(size) = size {
+            settings.size = Some((size.width, size.height));
+        }
+        if let Some(alg) = alg {
+            settings.alg = Some(alg.to_string());
+        }
+    });
+}
 
-    (format!("{name}.dat"), format!("{name}.solution.dat"))
+fn remember_file(path: &str) {
+    settings::update(|settings| settings.add_recent(path));
 }
 
-fn main() {
-    let cli: Cli = Cli::parse();
+fn save_maze(json: bool, maze: &Maze, path: &str) {
+    match fs::write(path, maze.as_str().unwrap()) {
+        Ok(_) => {
+            report!(json, "Wrote maze data to {path}");
+            remember_file(path);
+        }
+        Err(err) => report!(json, "Could not save maze: {err}"),
+    };
+}
 
-    let mut generated = false;
-    let mut solution: Option<Vec<(usize, usize)>> = None;
+fn print_share_code(json: bool, maze: &Maze) {
+    match maze.to_share_code() {
+        Ok(code) => report!(json, "Share code: {code}"),
+        Err(err) => report!(json, "Could not make a share code: {err}"),
+    }
+}
 
-    let mut maze = match &cli.input {
-        None => Maze::new(cli.width, cli.height),
-        Some(path) => {
-            let data = fs::read(path).unwrap();
+fn save_solution(json: bool, solution: Option<&Vec<(usize, usize)>>, path: &str) {
+    match solution {
+        Some(solution) => match fs::write(path, solution_to_string(solution)) {
+            Ok(_) => report!(json, "Wrote maze data to {path}"),
+            Err(err) => report!(json, "Could not save solution: {err}"),
+        },
+        None => report!(json, "Could not save solution: did not finish solving"),
+    }
+}
+
+fn finish_trace(json: bool, run: &mut Run) {
+    let Some(trace) = run.take_trace() else {
+        return;
+    };
+
+    let path = trace.path().to_string();
+    match trace.finish() {
+        Ok(()) => report!(json, "Wrote trace to {path}"),
+        Err(err) => report!(json, "Could not write trace: {err}"),
+    }
+}
+
+fn save_printable(
+    json: bool,
+    args: &PrintArgs,
+    maze: &Maze,
+    solution: Option<&Vec<(usize, usize)>>,
+) {
+    let Some(path) = &args.printable else {
+        return;
+    };
+
+    let solution = match (args.print_solution, solution) {
+        (false, _) => None,
+        (true, Some(solution)) => Some(solution.clone()),
+        (true, None) => {
+            let bounds = maze.get_bounds();
+            let solver = BFSSolver::between(bounds, (0, 0), (bounds.0 - 1, bounds.1 - 1));
 
-            generated = true;
+            let mut run = Run::new(
+                maze.clone(),
+                None,
+                Some(Algorithm::BreadthFirstSearch(solver)),
+            );
+            run.finish_solve();
 
-            Maze::from_data(&data).unwrap()
+            run.solution
         }
     };
 
-    update_cell_size(&maze.get_bounds());
+    let sheet = Sheet {
+        maze,
+        title: &args.title,
+        paper: args.paper.into(),
+        solution: solution.as_deref(),
+    };
+
+    match sheet.write(path) {
+        Ok(_) => report!(json, "Wrote printable sheet to {path}"),
+        Err(err) => report!(json, "Could not save printable sheet: {err}"),
+    }
+}
+
+fn report_unsolvable(json: bool, run: &Run) {
+    if run.is_unsolvable() {
+        report!(json, "The end can not be reached from the start");
+    }
+}
+
+fn report_phase(json: bool, phase: &str, stats: Option<&PhaseStats>) {
+    if let Some(stats) = stats {
+        report!(
+            json,
+            "{phase} maze took {} steps and {:?}",
+            stats.steps,
+            stats.duration
+        );
+    }
+}
+
+fn report_memory(json: bool, run: &Run) {
+    let Some(memory) = run.solver().map(Algorithm::memory) else {
+        return;
+    };
+
+    report!(
+        json,
+        "Solver memory: {} visited cells, open peak of {}, {} closed and about {:.1} KiB",
+        memory.visited,
+        memory.open_peak,
+        memory.closed,
+        memory.bytes as f64 / 1024.
+    );
+}
+
+fn report_race(json: bool, race: &Race) {
+    report!(json, "Race results:");
+
+    for (place, racer) in race.standings().into_iter().enumerate() {
+        let name = racer.agent.name();
+
+        if racer.finished {
+            report!(
+                json,
+                "  {}. {name} reached the exit in {} moves and {:?}",
+                place + 1,
+                racer.moves,
+                racer.time
+            );
+        } else if let Some(failure) = racer.agent.failure() {
+            report!(
+                json,
+                "  -  {name} was disqualified after {} moves: {failure}",
+                racer.moves
+            );
+        } else if race.is_done() {
+            report!(json, "  -  {name} gave up after {} moves", racer.moves);
+        } else {
+            report!(
+                json,
+                "  -  {name} was still going after {} moves",
+                racer.moves
+            );
+        }
+    }
+}
+
+fn race_json(race: &Race) -> Json {
+    Json::Array(
+        race.standings()
+            .into_iter()
+            .map(|racer| {
+                Json::object([
+                    ("agent", racer.agent.name().into()),
+                    ("moves", racer.moves.into()),
+                    ("finished", racer.finished.into()),
+                    ("time_ms", (racer.time.as_secs_f64() * 1000.).into()),
+                    ("failure", racer.agent.failure().into()),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn stats_json(run: &Run, algorithm: Option<String>) -> Json {
+    let bounds = run.maze.get_bounds();
+
+    let solve = run.solve_stats.as_ref().map(|stats| {
+        let mut entries = stats.to_json();
+        entries.push((
+            "path_length",
+            run.solution.as_ref().map(|path| path.len()).into(),
+        ));
+        entries.push(("unsolvable", run.is_unsolvable().into()));
+        entries.push((
+            "visited_cells",
+            run.solver().map(Algorithm::visited_count).into(),
+        ));
+        entries.push((
+            "memory",
+            run.solver()
+                .map(|solver| {
+                    let memory = solver.memory();
+
+                    Json::object([
+                        ("visited", memory.visited.into()),
+                        ("open_peak", memory.open_peak.into()),
+                        ("closed", memory.closed.into()),
+                        ("bytes", memory.bytes.into()),
+                    ])
+                })
+                .into(),
+        ));
+
+        Json::object(entries)
+    });
+
+    Json::object([
+        ("width", bounds.0.into()),
+        ("height", bounds.1.into()),
+        (
+            "seed",
+            run.generator()
+                .map(|generator| generator.seed() as usize)
+                .into(),
+        ),
+        ("generator", run.generator().map(Generator::name).into()),
+        ("algorithm", algorithm.into()),
+        (
+            "generation",
+            run.generation_stats
+                .as_ref()
+                .map(|stats| Json::object(stats.to_json()))
+                .into(),
+        ),
+        ("solve", solve.into()),
+        ("race", run.race().map(race_json).into()),
+        (
+            "metadata",
+            Json::object(run.maze.metadata().iter().map(|(k, v)| (k, v.into()))),
+        ),
+    ])
+}
+
+/// Times serial BFS on the maze just solved by parallel BFS, returning how many times faster the
+/// parallel one was
+fn parallel_speedup(run: &Run) -> Option<f64> {
+    if !matches!(run.solver(), Some(Algorithm::ParallelBFS(_))) {
+        return None;
+    }
+    let parallel = run.solve_stats.as_ref()?.duration;
+
+    let path = run.solution.as_ref()?;
+    let (start, end) = (*path.first()?, *path.last()?);
+    let serial = BFSSolver::between(run.maze.get_bounds(), start, end);
+
+    let mut serial = Run::new(
+        run.maze.clone(),
+        None,
+        Some(Algorithm::BreadthFirstSearch(serial)),
+    );
+    let serial = serial.finish_solve()?.duration;
+
+    Some(serial.as_secs_f64() / parallel.as_secs_f64().max(f64::EPSILON))
+}
+
+fn generate(args: GenerateCommand) -> Result<(), String> {
+    let json = args.json_stats;
+
+    let mut maze = Maze::new(args.size.width, args.size.height);
+    apply_meta(&mut maze, &args.meta);
+    update_cell_size(&maze.get_bounds(), args.window.cell_size());
+
+    // Lets the finished animation be rewound with Ctrl+Z
+    if args.debug {
+        maze.record_history();
+    }
+
+    let trace = args.trace.as_deref().map(Trace::create).transpose()?;
+
+    let generator = make_generator(&args.generator, maze.get_bounds())?;
+    report_seed(json, &generator);
+
+    let mut run = Run::new(maze, Some(generator), None)
+        .with_sparsify(args.generator.sparsify)
+        .with_fill(args.generator.fill)
+        .with_trace(trace)
+        .with_progress(ProgressBar::stderr());
+
+    // --fast generates in the window too, only without waiting between steps
+    let live = args.debug || args.window.fast;
+
+    if !live {
+        report_phase(json, "Generating", run.finish_generation());
+    }
+
+    // Without an output file there is nothing to do but look at the maze
+    if live || (args.output.is_none() && args.print.printable.is_none()) {
+        let size = args.generator.from_image.is_none().then_some(&args.size);
+        remember_options(&args.window, size, None);
+
+        viewer::show(&mut run, &args.window, None);
+    }
 
-    let mut generator = Wilson::new(maze.get_bounds());
+    if !run.is_generated() {
+        if args.output.is_some() || args.print.printable.is_some() || args.share {
+            report!(json, "Could not save maze: did not finish generating");
+        }
+    } else {
+        if let Some(path) = &args.output {
+            save_maze(json, &run.maze, &parse_output_filename(path).0);
+        }
+        if args.share {
+            print_share_code(json, &run.maze);
+        }
+
+        save_printable(json, &args.print, &run.maze, None);
+    }
+
+    finish_trace(json, &mut run);
 
+    if json {
+        println!("{}", stats_json(&run, None));
+    }
+
+    Ok(())
+}
+
+fn solve(args: SolveCommand) -> Result<(), String> {
+    let json = args.json_stats;
+
+    let mut maze = match (&args.input, &args.input_code) {
+        (Some(path), _) => formats::read(path)?,
+        (None, Some(code)) => {
+            Maze::from_share_code(code).map_err(|err| format!("--input-code: {err}"))?
+        }
+        (None, None) => Maze::new(args.size.width, args.size.height),
+    };
+    apply_meta(&mut maze, &args.meta);
     let bounds = maze.get_bounds();
+    update_cell_size(&bounds, args.window.cell_size());
+
+    let trace = args.trace.as_deref().map(Trace::create).transpose()?;
 
-    let mut solver: Algorithm = match cli.alg {
-        AlgorithmArg::BFS => Algorithm::BreadthFirstSearch(BFSSolver::new(bounds)),
-        AlgorithmArg::DFS => Algorithm::DepthFirstSearch(DFSSolver::new(bounds)),
-        AlgorithmArg::AStar => Algorithm::AStar(AStarSolver::new(bounds)),
+    let loaded = args.input.is_some() || args.input_code.is_some();
+    let generator = match loaded {
+        true => None,
+        false => Some(make_generator(&args.generator, bounds)?),
     };
+    if let Some(generator) = &generator {
+        report_seed(json, generator);
+    }
+    let solver = match args.race {
+        true => None,
+        false => Some(make_solve_algorithm(&args.solver, bounds)?),
+    };
+
+    let mut run = Run::new(maze, generator, solver)
+        .with_sparsify(args.generator.sparsify)
+        .with_fill(args.generator.fill)
+        .with_algorithm((!args.race).then_some(args.solver.alg))
+        .with_tie_break(args.solver.tie_break.into())
+        .with_heuristic_weight(args.solver.heuristic_weight)
+        .with_trail(args.window.trail)
+        .with_smoothing(args.solver.smooth)
+        .with_dynamic(args.solver.dynamic)
+        .with_race(args.race.then(|| make_race(bounds)))
+        .with_trace(trace)
+        .with_progress(ProgressBar::stderr());
+
+    if args.instant || !(args.debug || args.window.fast) {
+        report_phase(json, "Generating", run.finish_generation());
+    }
 
-    if (!generated) && (cli.instant || !cli.debug) {
-        let mut step_count: usize = 0;
+    let mut speedup = None;
 
-        let start = Instant::now();
-        while !generator.step(&mut maze) {
-            step_count += 1;
+    if args.instant {
+        report_phase(json, "Solving", run.finish_solve());
+        report_memory(json, &run);
+        report_unsolvable(json, &run);
+
+        if let Some(race) = run.race() {
+            report_race(json, race);
         }
-        let duration = start.elapsed();
 
-        println!(
-            "Generating maze took {} steps and {:?}",
-            step_count, duration
-        );
+        speedup = parallel_speedup(&run);
+        if let Some(speedup) = speedup {
+            report!(json, "Parallel BFS was {speedup:.2}x as fast as serial BFS");
+        }
+    }
 
-        generated = true
+    let generated = !loaded && args.generator.from_image.is_none();
+    remember_options(
+        &args.window,
+        generated.then_some(&args.size),
+        (!args.race).then_some(args.solver.alg),
+    );
+    if let Some(path) = &args.input {
+        remember_file(path);
     }
 
-    let mut window = RenderWindow::new(
-        {
+    let mut reload = Reload {
+        watcher: args
+            .input
+            .as_deref()
+            .filter(|_| args.watch)
+            .map(FileWatcher::new),
+        make_run: Box::new(|mut maze: Maze| {
+            apply_meta(&mut maze, &args.meta);
             let bounds = maze.get_bounds();
+            let solver = match args.race {
+                true => None,
+                false => Some(make_solve_algorithm(&args.solver, bounds)?),
+            };
+
+            let mut run = Run::new(maze, None, solver)
+                .with_algorithm((!args.race).then_some(args.solver.alg))
+                .with_tie_break(args.solver.tie_break.into())
+                .with_heuristic_weight(args.solver.heuristic_weight)
+                .with_trail(args.window.trail)
+                .with_smoothing(args.solver.smooth)
+                .with_dynamic(args.solver.dynamic)
+                .with_race(args.race.then(|| make_race(bounds)));
+
+            if args.instant {
+                report_phase(json, "Solving", run.finish_solve());
+                report_memory(json, &run);
+            }
+
+            Ok(run)
+        }),
+    };
 
-            VideoMode::new(
-                (bounds.0 * get_cell_size()) as u32,
-                (bounds.1 * get_cell_size()) as u32,
-                32,
+    viewer::show(&mut run, &args.window, Some(&mut reload));
+
+    if let Some(race) = run.race().filter(|_| !args.instant) {
+        report_race(json, race);
+    }
+    if !args.instant {
+        report_unsolvable(json, &run);
+    }
+
+    if let Some(path) = &args.output {
+        let (output_file, output_solution_file) = parse_output_filename(path);
+
+        save_maze(json, &run.maze, &output_file);
+
+        if args.save_solution {
+            save_solution(json, run.solution.as_ref(), &output_solution_file);
+        }
+    }
+
+    if run.is_generated() {
+        if args.share {
+            print_share_code(json, &run.maze);
+        }
+
+        save_printable(json, &args.print, &run.maze, run.solution.as_ref());
+    }
+
+    finish_trace(json, &mut run);
+
+    if json {
+        let algorithm = if args.race {
+            String::from("race")
+        } else if args.solver.dynamic.is_some() {
+            String::from("replanning")
+        } else {
+            args.solver.alg.to_string()
+        };
+
+        let mut stats = stats_json(&run, Some(algorithm));
+
+        if let (Json::Object(entries), Some(speedup)) = (&mut stats, speedup) {
+            if let Some((_, Json::Object(solve))) = entries.iter_mut().find(|(k, _)| k == "solve") {
+                solve.push(("speedup".into(), speedup.into()));
+            }
+        }
+
+        println!("{stats}");
+    }
+
+    Ok(())
+}
+
+fn view(args: ViewCommand) -> Result<(), String> {
+    let maze = formats::read(&args.input)?;
+
+    if args.stats {
+        let (width, height) = maze.get_bounds();
+        println!("{}: {width}x{height}", args.input);
+
+        for (key, value) in maze.metadata().iter() {
+            println!("  {key}: {value}");
+        }
+
+        return Ok(());
+    }
+
+    update_cell_size(&maze.get_bounds(), args.window.cell_size());
+
+    remember_options(&args.window, None, None);
+    remember_file(&args.input);
+
+    if args.play {
+        return viewer::play(&maze, &args.window);
+    }
+
+    let mut reload = Reload {
+        watcher: args.watch.then(|| FileWatcher::new(&args.input)),
+        make_run: Box::new(|maze| Ok(Run::new(maze, None, None))),
+    };
+
+    viewer::show(
+        &mut Run::new(maze, None, None),
+        &args.window,
+        Some(&mut reload),
+    );
+
+    Ok(())
+}
+
+fn convert(args: ConvertCommand) -> Result<(), String> {
+    let mut maze = formats::read(&args.input)?;
+    apply_meta(&mut maze, &args.meta);
+
+    let format = match &args.to {
+        Some(name) => formats::by_name(name).unwrap(),
+        None => formats::for_path(&args.output).ok_or_else(|| {
+            format!(
+                "Could not tell the format of {} from its extension, use --to",
+                args.output
             )
-        },
-        "Maze",
-        Style::CLOSE,
-        &ContextSettings::default(),
-    )
-    .unwrap();
+        })?,
+    };
+
+    // Meshes, cut files, png and svg are the only formats with options of their own
+    let mesh = MeshOptions {
+        wall_height: args.wall_height,
+        wall_thickness: args.wall_thickness,
+    };
+    let (obj, gltf) = (Obj(mesh), Gltf(mesh));
+    let cut = CutOptions {
+        cell_size: args.cut_cell_size,
+    };
+    let (dxf, cut_svg) = (Dxf(cut), CutSvg(cut));
+    let png = Png {
+        color_by_distance: args.color_by_distance,
+        cell_size: args.cell_size.map(|size| size as usize),
+        wall_width: args.wall_width.map(|width| width as usize),
+    };
+    let svg = Svg(SvgStyle {
+        color_by_distance: args.color_by_distance,
+        ..args.svg.into()
+    });
+    let format: &dyn MazeFormat = match format.name() {
+        "obj" => &obj,
+        "gltf" => &gltf,
+        "dxf" => &dxf,
+        "cut-svg" => &cut_svg,
+        "png" => &png,
+        "svg" => &svg,
+        _ => format,
+    };
+
+    let solution = if args.solution {
+        let bounds = maze.get_bounds();
+        let solver = args
+            .alg
+            .solver(bounds, (0, 0), (bounds.0 - 1, bounds.1 - 1));
 
-    if cli.vsync {
-        window.set_framerate_limit(cli.speed);
+        let mut run = Run::new(maze.clone(), None, Some(solver));
+        run.finish_solve();
+
+        run.solution
     } else {
-        window.set_vertical_sync_enabled(true);
+        None
+    };
+
+    format.write(&maze, solution.as_deref(), &args.output)?;
+    println!("Wrote {} maze to {}", format.name(), args.output);
+
+    Ok(())
+}
+
+fn serve(args: ServeCommand) -> Result<(), String> {
+    let mut maze = match &args.input {
+        Some(path) => formats::read(path)?,
+        None => Maze::new(args.size.width, args.size.height),
+    };
+    maze.record_history();
+    update_cell_size(&maze.get_bounds(), args.window.cell_size());
+
+    let mut server = match &args.listen {
+        Some(address) => Server::listen(address, args.export_dir.as_deref())?,
+        None => Server::stdin(),
+    };
+
+    viewer::show(
+        &mut Run::new(maze, None, None),
+        &args.window,
+        Some(&mut server),
+    );
+
+    Ok(())
+}
+
+fn bench(args: BenchCommand) -> Result<(), String> {
+    let json = args.json_stats;
+    let samples = args.samples.max(1);
+
+    let mut generation = PhaseStats {
+        steps: 0,
+        duration: Duration::ZERO,
+    };
+    let mut solves: Vec<(PhaseStats, usize)> = args
+        .alg
+        .iter()
+        .map(|_| {
+            (
+                PhaseStats {
+                    steps: 0,
+                    duration: Duration::ZERO,
+                },
+                0,
+            )
+        })
+        .collect();
+
+    for sample in 0..samples {
+        let maze = Maze::new(args.size.width, args.size.height);
+        let bounds = maze.get_bounds();
+
+        let mut run = Run::new(maze, Some(Generator::Wilson(Wilson::new(bounds))), None);
+        let stats = run.finish_generation().unwrap();
+        generation.steps += stats.steps;
+        generation.duration += stats.duration;
+
+        for (alg, (total, visited)) in args.alg.iter().zip(&mut solves) {
+            let solver = alg.solver(bounds, (0, 0), (bounds.0 - 1, bounds.1 - 1));
+            let mut run = Run::new(run.maze.clone(), None, Some(solver));
+
+            let stats = run.finish_solve().unwrap();
+            total.steps += stats.steps;
+            total.duration += stats.duration;
+            *visited += run.solver().unwrap().visited_count();
+        }
+
+        report!(json, "Finished sample {}/{samples}", sample + 1);
+    }
+
+    let mean = |stats: &PhaseStats| PhaseStats {
+        steps: stats.steps / samples,
+        duration: stats.duration / samples as u32,
+    };
+
+    let generation = mean(&generation);
+    report!(
+        json,
+        "Generating: {} steps and {:?} on average",
+        generation.steps,
+        generation.duration
+    );
+
+    for (alg, (total, visited)) in args.alg.iter().zip(&solves) {
+        let stats = mean(total);
+        report!(
+            json,
+            "{alg}: {} steps, {} visited cells and {:?} on average",
+            stats.steps,
+            visited / samples,
+            stats.duration
+        );
     }
 
-    if cli.instant && !cli.no_solve {
-        let mut step_count: usize = 0;
+    if json {
+        let algorithms = args
+            .alg
+            .iter()
+            .zip(&solves)
+            .map(|(alg, (total, visited))| {
+                let mut entries = vec![("algorithm", alg.to_string().into())];
+                entries.extend(mean(total).to_json());
+                entries.push(("visited_cells", (visited / samples).into()));
 
-        let start = Instant::now();
-        let mut result = None;
-        while result.is_none() {
-            result = solver.step(&maze);
-            step_count += 1;
+                Json::object(entries)
+            })
+            .collect();
+
+        let stats = Json::object([
+            ("width", (args.size.width as usize).into()),
+            ("height", (args.size.height as usize).into()),
+            ("samples", samples.into()),
+            ("generation", Json::object(generation.to_json())),
+            ("algorithms", Json::Array(algorithms)),
+        ]);
+
+        println!("{stats}");
+    }
+
+    Ok(())
+}
+
+/// Averages of [`MazeStats`] over the mazes of one generator and size
+struct StatsRow {
+    generator: GeneratorArg,
+    size: u16,
+    mazes: usize,
+    /// Mazes with a path from corner to corner, which the solution averages are over
+    solvable: usize,
+    dead_ends: f64,
+    diameter: f64,
+    solution_length: Option<f64>,
+    difficulty: Option<f64>,
+}
+
+impl StatsRow {
+    fn new(generator: GeneratorArg, size: u16, stats: &[MazeStats]) -> Self {
+        let mean = |values: Vec<usize>| {
+            (!values.is_empty()).then(|| values.iter().sum::<usize>() as f64 / values.len() as f64)
+        };
+        let all = |value: fn(&MazeStats) -> usize| mean(stats.iter().map(value).collect());
+        let solved =
+            |value: fn(&MazeStats) -> Option<usize>| mean(stats.iter().filter_map(value).collect());
+
+        Self {
+            generator,
+            size,
+            mazes: stats.len(),
+            solvable: stats
+                .iter()
+                .filter(|stats| stats.solution_length.is_some())
+                .count(),
+            dead_ends: all(|stats| stats.dead_ends).unwrap_or_default(),
+            diameter: all(|stats| stats.diameter).unwrap_or_default(),
+            solution_length: solved(|stats| stats.solution_length),
+            difficulty: solved(|stats| stats.difficulty),
         }
+    }
+}
 
-        solution = Some(result.unwrap().clone());
+fn stats_table(rows: &[StatsRow], format: TableFormatArg) -> String {
+    let number = |value: Option<f64>| value.map_or(String::new(), |value| format!("{value:.1}"));
 
-        let duration = start.elapsed();
+    let mut lines = match format {
+        TableFormatArg::Markdown => vec![
+            String::from("| Generator | Size | Mazes | Solvable | Dead ends | Diameter | Solution length | Difficulty |"),
+            String::from("| --- | --- | ---: | ---: | ---: | ---: | ---: | ---: |"),
+        ],
+        TableFormatArg::Csv => vec![String::from(
+            "generator,size,mazes,solvable,dead_ends,diameter,solution_length,difficulty",
+        )],
+    };
 
-        println!("Solving maze took {step_count} steps and {duration:?}")
+    for row in rows {
+        let cells = [
+            row.generator.to_string(),
+            format!("{0}x{0}", row.size),
+            row.mazes.to_string(),
+            row.solvable.to_string(),
+            number(Some(row.dead_ends)),
+            number(Some(row.diameter)),
+            number(row.solution_length),
+            number(row.difficulty),
+        ];
+
+        lines.push(match format {
+            TableFormatArg::Markdown => format!("| {} |", cells.join(" | ")),
+            TableFormatArg::Csv => cells.join(","),
+        });
     }
 
-    'mainloop: loop {
-        while let Some(ev) = window.poll_event() {
-            match ev {
-                Event::Closed => break 'mainloop,
-                Event::KeyPressed { code, ctrl, .. } => {
-                    if code == Key::Q || (code == Key::C && ctrl) {
-                        break 'mainloop;
+    lines.join("\n") + "\n"
+}
+
+fn compare_stats(args: CompareStatsCommand) -> Result<(), String> {
+    let seed = args.seed.unwrap_or_else(random_seed);
+    let samples = args.samples.get();
+    eprintln!("Generating from seed {seed}");
+
+    let total = args.gens.len() * args.sizes.len() * samples;
+    // A maze takes long enough to look at the clock after each
+    let mut progress = ProgressBar::stderr().map(|bar| bar.with_unit("mazes").with_check_every(1));
+    let mut rows = vec![];
+
+    for &generator in &args.gens {
+        for &size in &args.sizes {
+            let stats: Vec<MazeStats> = (0..samples)
+                .map(|sample| {
+                    let maze = Maze::new(size, size);
+                    let seed = (seed + sample as u64) % SEED_LIMIT;
+                    let generator = generator.generator(maze.get_bounds(), seed);
+
+                    let mut run = Run::new(maze, Some(generator), None);
+                    run.finish_generation();
+
+                    if let Some(progress) = &mut progress {
+                        progress.update(total - rows.len() * samples - sample - 1, total);
                     }
-                }
-                _ => {}
-            }
+
+                    maze_stats(&run.maze)
+                })
+                .collect();
+
+            rows.push(StatsRow::new(generator, size, &stats));
         }
+    }
 
-        if !generated {
-            generated = generator.step(&mut maze);
-        } else if !cli.no_solve && solution.is_none() {
-            let result = solver.step(&maze);
+    if let Some(progress) = progress {
+        progress.finish();
+    }
 
-            if let Some(v) = result {
-                solution = Some(v.clone())
-            }
+    let table = stats_table(&rows, args.format);
+    match &args.output {
+        Some(path) => {
+            fs::write(path, table).map_err(|err| format!("{path}: {err}"))?;
+            println!("Wrote statistics of {total} mazes to {path}");
         }
+        None => print!("{table}"),
+    }
 
-        window.clear(Color::BLACK);
+    Ok(())
+}
 
-        window.draw(&maze);
+fn verify(args: VerifyCommand) -> Result<(), String> {
+    let maze = formats::read(&args.maze)?;
+    let moves =
+        fs::read_to_string(&args.solution).map_err(|err| format!("{}: {err}", args.solution))?;
 
-        if !generated {
-            window.draw(&generator);
-        } else if !cli.no_solve {
-            window.draw(&solver);
+    let bounds = maze.get_bounds();
+    let start = args.start.unwrap_or((0, 0));
+    let end = args.end.unwrap_or((bounds.0 - 1, bounds.1 - 1));
+
+    for (name, (x, y)) in [("start", start), ("end", end)] {
+        if maze.try_get(x, y).is_none() {
+            return Err(format!("The {name} ({x}, {y}) is outside of the maze"));
         }
+    }
 
-        window.display();
+    let (pos, count) = maze
+        .replay_solution(start, &moves)
+        .map_err(|(count, err)| format!("Invalid solution: move {}: {err}", count + 1))?;
+
+    if pos != end {
+        return Err(format!(
+            "Invalid solution: ends on ({}, {}) instead of ({}, {})",
+            pos.0, pos.1, end.0, end.1
+        ));
     }
 
-    if let Some(path) = &cli.output {
-        let (output_file, output_solution_file) = parse_output_filename(path);
+    println!("Valid solution: {count} moves");
 
-        match fs::write(&output_file, maze.as_str().unwrap()) {
-            Ok(_) => println!("Wrote maze data to {}", &output_file),
-            Err(err) => println!("Could not save maze: {err}"),
-        };
+    Ok(())
+}
+
+fn referee(args: RefereeCommand) -> Result<(), String> {
+    let json = args.json_stats;
+
+    let maze = formats::read(&args.maze)?;
+    let bounds = maze.get_bounds();
+    update_cell_size(&bounds, args.window.cell_size());
+
+    let start = maze.find_tag(Tag::Start).next().unwrap_or((0, 0));
+    let end = maze
+        .find_tag(Tag::End)
+        .next()
+        .unwrap_or((bounds.0 - 1, bounds.1 - 1));
+    let move_timeout = Duration::from_millis(args.move_timeout);
+
+    let mut agents: Vec<Box<dyn Agent>> = vec![];
+    for program in &args.programs {
+        agents.push(Box::new(ExternalAgent::spawn(
+            program,
+            &maze,
+            start,
+            end,
+            move_timeout,
+        )?));
+    }
+
+    let race = Race::new(agents, end, bounds.0 * bounds.1 * RACE_MOVES_PER_CELL);
+    let mut run = Run::new(maze, None, None).with_race(Some(race));
+
+    if args.instant {
+        run.finish_solve();
+    } else {
+        viewer::show(&mut run, &args.window, None);
+    }
+
+    if let Some(race) = run.race() {
+        report_race(json, race);
+    }
 
-        if cli.save_solution {
-            match &solution {
-                Some(solution) => {
-                    let directions: Vec<Direction> = (0..solution.len() - 1)
-                        .map(|i| {
-                            let j = i + 1;
-
-                            let from = solution[i];
-                            let to = solution[j];
-
-                            if to.0 > from.0 {
-                                Direction::RIGHT
-                            } else if to.0 < from.0 {
-                                Direction::LEFT
-                            } else if to.1 > from.1 {
-                                Direction::DOWN
-                            } else {
-                                Direction::UP
-                            }
-                        })
-                        .collect();
-
-                    let data: Vec<u8> = directions
-                        .iter()
-                        .map(|dir| match dir {
-                            Direction::UP => 'U',
-                            Direction::RIGHT => 'R',
-                            Direction::DOWN => 'D',
-                            Direction::LEFT => 'L',
-                        } as u8)
-                        .collect();
-
-                    match fs::write(&output_solution_file, data) {
-                        Ok(_) => println!("Wrote maze data to {}", &output_solution_file),
-                        Err(err) => println!("Could not save solution: {err}"),
-                    };
+    if json {
+        println!("{}", stats_json(&run, Some(String::from("referee"))));
+    }
+
+    Ok(())
+}
+
+fn heatmap(args: HeatmapCommand) -> Result<(), String> {
+    let maze = formats::read(&args.input)?;
+    let bounds = maze.get_bounds();
+    let max_steps = bounds.0 * bounds.1 * RACE_MOVES_PER_CELL;
+
+    let mut heat = HeatMap::new(bounds);
+    let mut unfinished = 0;
+
+    for _ in 0..args.runs.get() {
+        let mut solver = args
+            .alg
+            .solver(bounds, (0, 0), (bounds.0 - 1, bounds.1 - 1));
+
+        let mut finished = false;
+        for _ in 0..max_steps {
+            match solver.step(&maze) {
+                SolveStep::InProgress => {}
+                step => {
+                    finished = matches!(step, SolveStep::Found(_));
+                    break;
                 }
-                None => println!("Could not save solution: did not finish solving"),
             }
         }
+        if !finished {
+            unfinished += 1;
+        }
+
+        heat.add_run(solver.visited());
+    }
+
+    if unfinished > 0 {
+        println!("{unfinished} of {} runs did not reach the end", heat.runs());
+    }
+
+    png::write_heatmap(&maze, &heat, args.scale, &args.output)?;
+    println!(
+        "Wrote heat map of {} {} runs to {}",
+        heat.runs(),
+        args.alg,
+        args.output
+    );
+
+    Ok(())
+}
+
+fn explore(args: ExploreCommand) -> Result<(), String> {
+    let mut maze = InfiniteMaze::new(args.seed.unwrap_or_else(random_seed));
+    println!("Exploring from seed {}", maze.seed());
+
+    remember_options(&args.window, None, None);
+    viewer::explore(&mut maze, args.radius as usize, &args.window);
+
+    Ok(())
+}
+
+fn main() {
+    let cli: Cli = match parse_cli() {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("Could not load config: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let result = match cli.command {
+        Command::Generate(args) => generate(args),
+        Command::Solve(args) => solve(args),
+        Command::View(args) => view(args),
+        Command::Convert(args) => convert(args),
+        Command::Serve(args) => serve(args),
+        Command::Bench(args) => bench(args),
+        Command::CompareStats(args) => compare_stats(args),
+        Command::Verify(args) => verify(args),
+        Command::Referee(args) => referee(args),
+        Command::Heatmap(args) => heatmap(args),
+        Command::Explore(args) => explore(args),
+    };
+
+    if let Err(err) = settings::save() {
+        eprintln!("Could not save settings: {err}");
+    }
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
     }
 }
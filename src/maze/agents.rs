@@ -0,0 +1,429 @@
+use std::time::{Duration, Instant};
+
+use rand::{rng, Rng};
+#[cfg(feature = "gui")]
+use sfml::graphics::{Drawable, RectangleShape, Shape, Transformable};
+
+use crate::color::Color;
+#[cfg(feature = "gui")]
+use crate::consts::*;
+use crate::maze::grid::VisitedGrid;
+use crate::maze::Maze;
+use crate::Direction;
+
+/// Walks the maze one cell at a time knowing only the walls around it, unlike a
+/// [`super::MazeSolver`], which searches the whole maze for a path
+pub trait Agent {
+    fn name(&self) -> &str;
+
+    fn position(&self) -> (usize, usize);
+
+    /// Moves to a neighboring cell, staying put only when walled in
+    fn step(&mut self, maze: &Maze);
+
+    /// Goes back to `start`, forgetting everything learned about the maze
+    fn reset(&mut self, bounds: (usize, usize), start: (usize, usize));
+
+    /// Whether the last step stayed put to wait for where to go, e.g. for an external program to
+    /// answer. Such steps are not moves.
+    fn is_waiting(&self) -> bool {
+        false
+    }
+
+    /// Why the agent stopped for good before reaching the exit, for agents that can fail
+    fn failure(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Keeps its right hand on the wall. Starting on the outer wall it always reaches an exit on it,
+/// but from inside a braided maze it may circle an island forever.
+pub struct WallFollower {
+    position: (usize, usize),
+    facing: Direction,
+}
+
+impl WallFollower {
+    pub fn new(start: (usize, usize)) -> Self {
+        Self {
+            position: start,
+            facing: Direction::RIGHT,
+        }
+    }
+}
+
+impl Agent for WallFollower {
+    fn name(&self) -> &str {
+        "wall-follower"
+    }
+
+    fn reset(&mut self, _bounds: (usize, usize), start: (usize, usize)) {
+        *self = Self::new(start);
+    }
+
+    fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    fn step(&mut self, maze: &Maze) {
+        let turns = [
+            self.facing.clockwise(),
+            self.facing,
+            self.facing.counter_clockwise(),
+            self.facing.opposite(),
+        ];
+
+        if let Some((direction, next)) = turns
+            .into_iter()
+            .find_map(|direction| Some((direction, maze.travel(self.position, direction)?)))
+        {
+            self.facing = direction;
+            self.position = next;
+        }
+    }
+}
+
+/// Follows passages and picks a random way at every junction, only turning back at dead ends
+pub struct RandomMouse {
+    position: (usize, usize),
+    facing: Option<Direction>,
+}
+
+impl RandomMouse {
+    pub fn new(start: (usize, usize)) -> Self {
+        Self {
+            position: start,
+            facing: None,
+        }
+    }
+}
+
+impl Agent for RandomMouse {
+    fn name(&self) -> &str {
+        "random-mouse"
+    }
+
+    fn reset(&mut self, _bounds: (usize, usize), start: (usize, usize)) {
+        *self = Self::new(start);
+    }
+
+    fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    fn step(&mut self, maze: &Maze) {
+        let back = self.facing.map(|facing| facing.opposite());
+
+        let open: Vec<_> = Direction::ALL
+            .into_iter()
+            .filter_map(|direction| Some((direction, maze.travel(self.position, direction)?)))
+            .collect();
+        let forward: Vec<_> = open
+            .iter()
+            .copied()
+            .filter(|(direction, _)| Some(*direction) != back)
+            .collect();
+
+        let choices = if forward.is_empty() { open } else { forward };
+
+        if !choices.is_empty() {
+            let (direction, next) = choices[rng().random_range(..choices.len())];
+
+            self.facing = Some(direction);
+            self.position = next;
+        }
+    }
+}
+
+/// Marks every passage it walks and never walks one more than twice, which finds the exit of any
+/// maze, loops included
+pub struct Tremaux {
+    position: (usize, usize),
+    came_by: Option<Direction>,
+    width: usize,
+    visited: VisitedGrid,
+    /// Times each passage was walked, two per cell for its right and bottom walls
+    marks: Vec<u8>,
+}
+
+impl Tremaux {
+    pub fn new(bounds: (usize, usize), start: (usize, usize)) -> Self {
+        Self {
+            position: start,
+            came_by: None,
+            width: bounds.0,
+            visited: VisitedGrid::new(bounds),
+            marks: vec![0; bounds.0 * bounds.1 * 2],
+        }
+    }
+
+    /// `direction` must lead to a cell inside the maze
+    fn passage(&self, (x, y): (usize, usize), direction: Direction) -> usize {
+        match direction {
+            Direction::RIGHT => (y * self.width + x) * 2,
+            Direction::DOWN => (y * self.width + x) * 2 + 1,
+            Direction::LEFT => (y * self.width + x - 1) * 2,
+            Direction::UP => ((y - 1) * self.width + x) * 2 + 1,
+        }
+    }
+}
+
+impl Agent for Tremaux {
+    fn name(&self) -> &str {
+        "tremaux"
+    }
+
+    fn reset(&mut self, bounds: (usize, usize), start: (usize, usize)) {
+        *self = Self::new(bounds, start);
+    }
+
+    fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    fn step(&mut self, maze: &Maze) {
+        let first_visit = self.visited.insert(self.position);
+        let back = self.came_by.map(|direction| direction.opposite());
+        let marks = |direction| self.marks[self.passage(self.position, direction)];
+
+        let open: Vec<_> = Direction::ALL
+            .into_iter()
+            .filter(|&direction| maze.travel(self.position, direction).is_some())
+            .collect();
+        let unmarked: Vec<_> = open
+            .iter()
+            .copied()
+            .filter(|&direction| Some(direction) != back && marks(direction) == 0)
+            .collect();
+
+        let direction = match back {
+            // Came down a new passage into a known junction: treat it as a dead end
+            Some(back) if !first_visit && marks(back) == 1 => Some(back),
+            _ if !unmarked.is_empty() => Some(unmarked[rng().random_range(..unmarked.len())]),
+            Some(back) if marks(back) < 2 => Some(back),
+            _ => open
+                .iter()
+                .copied()
+                .filter(|&direction| marks(direction) < 2)
+                .min_by_key(|&direction| marks(direction)),
+        };
+
+        let Some(direction) = direction else {
+            return;
+        };
+
+        let passage = self.passage(self.position, direction);
+        self.marks[passage] = self.marks[passage].saturating_add(1);
+
+        self.position = maze.travel(self.position, direction).unwrap();
+        self.came_by = Some(direction);
+    }
+}
+
+/// Heads in one preferred direction, and around any wall in the way with its right hand on it,
+/// counting the turns. It only lets go of the wall once the turns cancel out, so unlike a plain
+/// wall follower it cannot circle an island forever and escapes braided mazes too.
+pub struct Pledge {
+    position: (usize, usize),
+    preferred: Direction,
+    /// Quarter turns taken since leaving the preferred direction, clockwise positive
+    turns: i32,
+}
+
+impl Pledge {
+    pub fn new(start: (usize, usize), preferred: Direction) -> Self {
+        Self {
+            position: start,
+            preferred,
+            turns: 0,
+        }
+    }
+
+    fn facing(&self) -> Direction {
+        (0..self.turns.rem_euclid(4)).fold(self.preferred, |facing, _| facing.clockwise())
+    }
+}
+
+impl Agent for Pledge {
+    fn name(&self) -> &str {
+        "pledge"
+    }
+
+    fn reset(&mut self, _bounds: (usize, usize), start: (usize, usize)) {
+        *self = Self::new(start, self.preferred);
+    }
+
+    fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    fn step(&mut self, maze: &Maze) {
+        // Free of the wall it goes straight on, otherwise it keeps its right hand on it
+        let turns: &[i32] = if self.turns == 0 {
+            &[0, -1, -2]
+        } else {
+            &[1, 0, -1, -2]
+        };
+
+        let facing = self.facing();
+
+        for &turn in turns {
+            let direction = match turn {
+                1 => facing.clockwise(),
+                0 => facing,
+                -1 => facing.counter_clockwise(),
+                _ => facing.opposite(),
+            };
+
+            if let Some(next) = maze.travel(self.position, direction) {
+                self.turns += turn;
+                self.position = next;
+                return;
+            }
+        }
+    }
+}
+
+pub struct Racer {
+    pub agent: Box<dyn Agent>,
+    pub color: Color,
+    pub moves: usize,
+    pub finished: bool,
+    /// Spent getting moves from the agent, which for external programs is the wait for them
+    pub time: Duration,
+    /// When the agent started [waiting](Agent::is_waiting) for its next move
+    waiting_since: Option<Instant>,
+}
+
+/// Agents walking the same maze side by side, one move each per step, until all of them reach
+/// the exit or run out of moves
+pub struct Race {
+    racers: Vec<Racer>,
+    /// Where each racer set off from
+    starts: Vec<(usize, usize)>,
+    end: (usize, usize),
+    max_moves: usize,
+}
+
+const RACER_COLORS: [Color; 6] = [
+    Color::RED,
+    Color::BLUE,
+    Color::rgb(0, 160, 0),
+    Color::MAGENTA,
+    Color::rgb(255, 140, 0),
+    Color::CYAN,
+];
+
+impl Race {
+    pub fn new(agents: Vec<Box<dyn Agent>>, end: (usize, usize), max_moves: usize) -> Self {
+        let starts = agents.iter().map(|agent| agent.position()).collect();
+        let racers = agents
+            .into_iter()
+            .zip(RACER_COLORS.into_iter().cycle())
+            .map(|(agent, color)| Racer {
+                finished: agent.position() == end,
+                agent,
+                color,
+                moves: 0,
+                time: Duration::ZERO,
+                waiting_since: None,
+            })
+            .collect();
+
+        Self {
+            racers,
+            starts,
+            end,
+            max_moves,
+        }
+    }
+
+    pub fn racers(&self) -> &[Racer] {
+        &self.racers
+    }
+
+    /// Sends every racer back to its start
+    pub fn reset(&mut self, bounds: (usize, usize)) {
+        for (racer, start) in self.racers.iter_mut().zip(&self.starts) {
+            racer.agent.reset(bounds, *start);
+            racer.moves = 0;
+            racer.time = Duration::ZERO;
+            racer.waiting_since = None;
+            racer.finished = *start == self.end;
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.racers.iter().all(|racer| self.is_out(racer))
+    }
+
+    fn is_out(&self, racer: &Racer) -> bool {
+        racer.finished || racer.moves >= self.max_moves || racer.agent.failure().is_some()
+    }
+
+    /// Returns `true` once the race is over
+    pub fn step(&mut self, maze: &Maze) -> bool {
+        for i in 0..self.racers.len() {
+            if self.is_out(&self.racers[i]) {
+                continue;
+            }
+
+            let racer = &mut self.racers[i];
+            let start = racer.waiting_since.take().unwrap_or_else(Instant::now);
+
+            racer.agent.step(maze);
+
+            if racer.agent.is_waiting() {
+                racer.waiting_since = Some(start);
+                continue;
+            }
+            racer.time += start.elapsed();
+
+            // A failed move is not a move
+            if racer.agent.failure().is_some() {
+                continue;
+            }
+
+            racer.moves += 1;
+            racer.finished = racer.agent.position() == self.end;
+        }
+
+        self.is_done()
+    }
+
+    /// Finished racers by fewest moves, then the ones that gave up
+    pub fn standings(&self) -> Vec<&Racer> {
+        let mut standings: Vec<_> = self.racers.iter().collect();
+        standings.sort_by_key(|racer| (!racer.finished, racer.moves));
+
+        standings
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for Race {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let cell_size = get_cell_size() as f32;
+        let size = cell_size / 2.;
+
+        // Each racer keeps to its own quarter of a cell, so racers on the same cell stay visible
+        let mut rect = RectangleShape::with_size((size, size).into());
+
+        for (i, racer) in self.racers.iter().enumerate() {
+            let (x, y) = racer.agent.position();
+            let quarter = ((i % 2) as f32 * size, (i / 2 % 2) as f32 * size);
+
+            rect.set_fill_color(racer.color);
+            rect.set_position((
+                x as f32 * cell_size + quarter.0,
+                y as f32 * cell_size + quarter.1,
+            ));
+
+            target.draw_rectangle_shape(&rect, rs);
+        }
+    }
+}
@@ -1,30 +1,179 @@
+use std::collections::VecDeque;
 use std::vec;
 
+use rand::seq::SliceRandom;
 use rand::{rng, Rng};
+#[cfg(feature = "gui")]
 use sfml::graphics::{
     CircleShape, Color, Drawable, PrimitiveType, Shape, Transformable, Vertex, VertexBuffer,
     VertexBufferUsage,
 };
 
+#[cfg(feature = "gui")]
 use crate::consts::*;
-use crate::maze::{Maze, MazeGenerator};
+use crate::maze::grid::{ParentGrid, VisitedGrid};
+use crate::maze::metadata::timestamp;
+use crate::maze::picture::WeightMap;
+#[cfg(feature = "gui")]
+use crate::maze::render::{draw_marks, Mark};
+use crate::maze::seed::{SeedRng, Stream};
+use crate::maze::symmetry::Symmetric;
+use crate::maze::{GenStep, GenerationProgress, Maze, MazeGenerator};
 use crate::Direction;
 
+/// Random seeds stay below this, so they survive a round trip through JSON numbers
+pub const SEED_LIMIT: u64 = 1 << 53;
+
+pub fn random_seed() -> u64 {
+    rng().random_range(..SEED_LIMIT)
+}
+
+pub enum Generator {
+    Wilson(Wilson),
+    RandomDFS(RandomDFS),
+    Picture(PictureDFS),
+    Cave(Cave),
+    Kruskal(Kruskal),
+    /// Another generator mirrored onto both halves of the maze
+    Symmetric(Box<Symmetric>),
+}
+
+impl Generator {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Wilson(_) => "wilson",
+            Self::RandomDFS(_) => "random-dfs",
+            Self::Picture(_) => "picture",
+            Self::Cave(_) => "cave",
+            Self::Kruskal(_) => "kruskal",
+            Self::Symmetric(v) => v.base().name(),
+        }
+    }
+
+    /// Starts over with a new random seed, to generate another maze
+    pub fn reset(&mut self, bounds: (usize, usize)) {
+        match self {
+            Self::Wilson(v) => v.reset(bounds),
+            Self::RandomDFS(v) => v.reset(bounds),
+            Self::Picture(v) => v.reset(bounds),
+            Self::Cave(v) => v.reset(bounds),
+            Self::Kruskal(v) => v.reset(bounds),
+            Self::Symmetric(v) => v.reset(bounds),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        match self {
+            Self::Wilson(v) => v.seed,
+            Self::RandomDFS(v) => v.seed,
+            Self::Picture(v) => v.seed,
+            Self::Cave(v) => v.seed,
+            Self::Kruskal(v) => v.seed,
+            Self::Symmetric(v) => v.base().seed(),
+        }
+    }
+
+    /// Once the maze is done, records the generator and seed in its metadata so it can be made
+    /// again
+    pub fn step(&mut self, maze: &mut Maze) -> GenStep {
+        let step = match self {
+            Self::Wilson(v) => v.step(maze),
+            Self::RandomDFS(v) => v.step(maze),
+            Self::Picture(v) => v.step(maze),
+            Self::Cave(v) => v.step(maze),
+            Self::Kruskal(v) => v.step(maze),
+            Self::Symmetric(v) => v.step(maze),
+        };
+
+        if step.is_done() {
+            let metadata = maze.metadata_mut();
+
+            metadata.set("generator", self.name()).unwrap();
+            metadata.set("seed", &self.seed().to_string()).unwrap();
+            metadata.set("version", env!("CARGO_PKG_VERSION")).unwrap();
+            metadata.set("created", &timestamp()).unwrap();
+
+            if let Self::Symmetric(v) = self {
+                metadata.set("symmetry", v.symmetry().name()).unwrap();
+            }
+        }
+
+        step
+    }
+}
+
+impl GenerationProgress for Generator {
+    fn remaining(&self) -> usize {
+        match self {
+            Self::Wilson(v) => v.remaining(),
+            Self::RandomDFS(v) => v.remaining(),
+            Self::Picture(v) => v.remaining(),
+            Self::Cave(v) => v.remaining(),
+            Self::Kruskal(v) => v.remaining(),
+            Self::Symmetric(v) => v.remaining(),
+        }
+    }
+
+    fn total(&self) -> usize {
+        match self {
+            Self::Wilson(v) => v.total(),
+            Self::RandomDFS(v) => v.total(),
+            Self::Picture(v) => v.total(),
+            Self::Cave(v) => v.total(),
+            Self::Kruskal(v) => v.total(),
+            Self::Symmetric(v) => v.total(),
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for Generator {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        match self {
+            Self::Wilson(v) => v.draw(target, rs),
+            Self::RandomDFS(v) => v.draw(target, rs),
+            Self::Picture(v) => v.draw(target, rs),
+            Self::Cave(v) => v.draw(target, rs),
+            Self::Kruskal(v) => v.draw(target, rs),
+            Self::Symmetric(v) => v.draw(target, rs),
+        };
+    }
+}
+
 pub struct RandomDFS {
     stack: Vec<(usize, usize)>,
+    /// Every cell is visited once, so this counts down from all but the first
+    remaining: usize,
+    total: usize,
+
+    seed: u64,
+    rng: SeedRng,
 }
 
 impl RandomDFS {
     pub fn new(bounds: (usize, usize)) -> Self {
+        Self::with_seed(bounds, random_seed())
+    }
+
+    pub fn with_seed(bounds: (usize, usize), seed: u64) -> Self {
+        let mut rng = Stream::Generation.rng(seed);
+
         Self {
-            stack: vec![(
-                rng().random_range(0..bounds.0),
-                rng().random_range(0..bounds.1),
-            )],
+            stack: vec![(rng.random_range(0..bounds.0), rng.random_range(0..bounds.1))],
+            remaining: bounds.0 * bounds.1 - 1,
+            total: bounds.0 * bounds.1,
+
+            seed,
+            rng,
         }
     }
 }
 
+#[cfg(feature = "gui")]
 impl Drawable for RandomDFS {
     fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
         &'a self,
@@ -62,11 +211,15 @@ impl Drawable for RandomDFS {
 }
 
 impl MazeGenerator for RandomDFS {
-    fn step(&mut self, maze: &mut super::Maze) -> bool {
+    fn reset(&mut self, bounds: (usize, usize)) {
+        *self = Self::new(bounds);
+    }
+
+    fn step(&mut self, maze: &mut super::Maze) -> GenStep {
         let last_pos = self.stack.last();
 
         if last_pos.is_none() {
-            return true;
+            return GenStep::Done;
         }
 
         let pos = *last_pos.unwrap();
@@ -74,21 +227,163 @@ impl MazeGenerator for RandomDFS {
         let neighbors = maze.get_neighbors(pos);
         let possible_next: Vec<_> = neighbors
             .iter()
-            .filter(|(x, y, _)| maze.get(*x, *y) == 0)
+            .filter(|(x, y, _)| maze.get(*x, *y).is_empty())
             .collect();
 
         if possible_next.is_empty() {
             self.stack.pop();
 
-            self.stack.is_empty()
+            match self.stack.is_empty() {
+                true => GenStep::Done,
+                false => self.in_progress(),
+            }
         } else {
-            let next = possible_next[rng().random_range(..possible_next.len())];
+            let next = possible_next[self.rng.random_range(..possible_next.len())];
 
             self.stack.push((next.0, next.1));
             maze.carve(pos.0, pos.1, next.2);
+            self.remaining -= 1;
+
+            self.in_progress()
+        }
+    }
+}
+
+impl GenerationProgress for RandomDFS {
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Randomized DFS that prefers carving into darker cells of a [`WeightMap`], so corridors follow
+/// the dark regions of the source picture. `bias` of `0.0` behaves like [`RandomDFS`].
+pub struct PictureDFS {
+    stack: Vec<(usize, usize)>,
+    weights: WeightMap,
+    bias: f32,
+    remaining: usize,
+
+    seed: u64,
+    rng: SeedRng,
+}
+
+impl PictureDFS {
+    pub fn new(weights: WeightMap, bias: f32) -> Self {
+        Self::with_seed(weights, bias, random_seed())
+    }
+
+    pub fn with_seed(weights: WeightMap, bias: f32, seed: u64) -> Self {
+        Self {
+            stack: vec![weights.darkest()],
+            remaining: weights.cell_count() - 1,
+            weights,
+            bias,
+
+            seed,
+            rng: Stream::Generation.rng(seed),
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for PictureDFS {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let cell_size = get_cell_size();
+
+        let mut polyline = VertexBuffer::new(
+            PrimitiveType::LINE_STRIP,
+            self.stack.len(),
+            VertexBufferUsage::DYNAMIC,
+        )
+        .unwrap();
+
+        let points: Vec<Vertex> = self
+            .stack
+            .iter()
+            .map(|(x, y)| {
+                Vertex::with_pos_color(
+                    (
+                        ((*x * 2 + 1) * cell_size / 2) as f32,
+                        ((*y * 2 + 1) * cell_size / 2) as f32,
+                    )
+                        .into(),
+                    Color::RED,
+                )
+            })
+            .collect();
+
+        polyline.update(&points, 0).unwrap();
+
+        target.draw_vertex_buffer(&polyline, rs);
+    }
+}
+
+impl MazeGenerator for PictureDFS {
+    /// The picture decides the bounds, so `bounds` is ignored
+    fn reset(&mut self, _bounds: (usize, usize)) {
+        *self = Self::new(self.weights.clone(), self.bias);
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> GenStep {
+        let Some(&pos) = self.stack.last() else {
+            return GenStep::Done;
+        };
+
+        let neighbors = maze.get_neighbors(pos);
+        let possible_next: Vec<_> = neighbors
+            .iter()
+            .filter(|(x, y, _)| maze.get(*x, *y).is_empty())
+            .map(|next| {
+                let weight = 1. + self.bias * self.weights.get((next.0, next.1));
+                // Every open cell stays possible, and the weights a valid range to pick from
+                (next, weight.max(f32::MIN_POSITIVE))
+            })
+            .collect();
+
+        if possible_next.is_empty() {
+            self.stack.pop();
 
-            false
+            return match self.stack.is_empty() {
+                true => GenStep::Done,
+                false => self.in_progress(),
+            };
         }
+
+        let total: f32 = possible_next.iter().map(|(_, weight)| weight).sum();
+        let mut choice = self.rng.random_range(0.0..total);
+
+        let next = possible_next
+            .iter()
+            .find(|(_, weight)| {
+                choice -= weight;
+                choice < 0.
+            })
+            .unwrap_or(possible_next.last().unwrap())
+            .0;
+
+        self.stack.push((next.0, next.1));
+        maze.carve(pos.0, pos.1, next.2);
+        self.remaining -= 1;
+
+        self.in_progress()
+    }
+}
+
+impl GenerationProgress for PictureDFS {
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn total(&self) -> usize {
+        self.weights.cell_count()
     }
 }
 
@@ -98,20 +393,41 @@ pub struct Wilson {
     opposite_of_last_direction: Option<Direction>,
 
     current_walk_steps: usize,
+    /// Counted again whenever a walk starts, which looks through the empty cells anyway
+    remaining: usize,
+    total: usize,
+
+    seed: u64,
+    rng: SeedRng,
 }
 
 impl Wilson {
     pub fn new(bounds: (usize, usize)) -> Self {
-        let start = (
-            rng().random_range(..bounds.0),
-            rng().random_range(..bounds.1),
-        );
+        Self::with_seed(bounds, random_seed())
+    }
+
+    pub fn with_seed(bounds: (usize, usize), seed: u64) -> Self {
+        let mut rng = Stream::Generation.rng(seed);
+
+        let start = (rng.random_range(..bounds.0), rng.random_range(..bounds.1));
+
+        // A single cell has nothing to walk to, and no passages to carve
+        if bounds.0 * bounds.1 == 1 {
+            return Self {
+                walk: vec![],
+                first_walk_target: None,
+                opposite_of_last_direction: None,
+                current_walk_steps: 0,
+                remaining: 0,
+                total: 1,
+
+                seed,
+                rng,
+            };
+        }
 
         let end = loop {
-            let next = (
-                rng().random_range(..bounds.0),
-                rng().random_range(..bounds.1),
-            );
+            let next = (rng.random_range(..bounds.0), rng.random_range(..bounds.1));
 
             if next.0 == start.0 && next.1 == start.1 {
                 continue;
@@ -125,6 +441,11 @@ impl Wilson {
             first_walk_target: Some(end),
             opposite_of_last_direction: None,
             current_walk_steps: 0,
+            remaining: bounds.0 * bounds.1,
+            total: bounds.0 * bounds.1,
+
+            seed,
+            rng,
         }
     }
 
@@ -163,30 +484,31 @@ impl Wilson {
         }
     }
 
-    /// Returns `true` if no possible starting positions can be made
-    fn create_new_walk(&mut self, maze: &Maze) -> bool {
+    /// Returns [`GenStep::Done`] if no possible starting positions can be made
+    fn create_new_walk(&mut self, maze: &Maze) -> GenStep {
         self.walk.clear();
 
-        let mut possible_next: Vec<(usize, usize)> = vec![];
-        for (i, cell) in maze.cells.iter().enumerate() {
-            if *cell == 0 {
-                possible_next.push(maze.i_to_xy(i));
-            }
-        }
+        let possible_next: Vec<(usize, usize)> = maze
+            .cells()
+            .filter(|(_, _, cell)| cell.is_empty())
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        self.remaining = possible_next.len();
 
         if possible_next.is_empty() {
-            return true;
+            return GenStep::Done;
         }
 
         self.walk.clear();
         self.walk
-            .push(possible_next[rng().random_range(0..possible_next.len())]);
+            .push(possible_next[self.rng.random_range(0..possible_next.len())]);
         self.opposite_of_last_direction = None;
 
-        false
+        self.in_progress()
     }
 }
 
+#[cfg(feature = "gui")]
 impl Drawable for Wilson {
     fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
         &'a self,
@@ -238,21 +560,27 @@ impl Drawable for Wilson {
 }
 
 impl MazeGenerator for Wilson {
-    fn step(&mut self, maze: &mut Maze) -> bool {
+    fn reset(&mut self, bounds: (usize, usize)) {
+        *self = Self::new(bounds);
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> GenStep {
         let pos = self.walk.last();
 
         if pos.is_none() {
-            return true;
+            return GenStep::Done;
         }
 
         let pos = *pos.unwrap();
 
         let neighbors = maze.get_neighbors(pos);
+        // Turning back is the only way out of the end of a maze one cell wide
+        let dead_end = neighbors.len() == 1;
 
         let next = loop {
-            let next = neighbors[rng().random_range(0..neighbors.len())];
+            let next = neighbors[self.rng.random_range(0..neighbors.len())];
 
-            if let Some(v) = self.opposite_of_last_direction {
+            if let Some(v) = self.opposite_of_last_direction.filter(|_| !dead_end) {
                 if v == next.2 {
                     continue;
                 }
@@ -263,14 +591,18 @@ impl MazeGenerator for Wilson {
 
         if let Some(break_index) = self.pos_in_stack((next.0, next.1)) {
             self.walk.drain(break_index + 1..);
-            return false;
+            if dead_end {
+                // Or it would head straight back into the dead end
+                self.opposite_of_last_direction = None;
+            }
+            return self.in_progress();
         }
 
         self.opposite_of_last_direction = Some(next.2.opposite());
         self.walk.push((next.0, next.1));
         self.current_walk_steps += 1;
 
-        if maze.get(next.0, next.1) != 0 {
+        if !maze.get(next.0, next.1).is_empty() {
             self.first_walk_target = None;
             self.finish_walk(maze);
             return self.create_new_walk(maze);
@@ -282,6 +614,587 @@ impl MazeGenerator for Wilson {
             }
         }
 
-        false
+        self.in_progress()
+    }
+}
+
+impl GenerationProgress for Wilson {
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Smoothing passes of [`Cave`] before the caves are carved
+const CAVE_SMOOTHING_PASSES: usize = 5;
+
+enum CavePhase {
+    /// Passes made so far
+    Smoothing(usize),
+    /// Next row to carve
+    Carving(usize),
+    Done,
+}
+
+/// Organic caves instead of a maze. Cells start out as rock with a chance of `fill`, and each
+/// smoothing pass turns cells with more than four rocky neighbors into rock and those with less
+/// into floor, out of bounds counting as rock. Separate caves are then joined by tunnels through
+/// the rock, the first and last cells always being part of them, before the floor is carved a row
+/// per step. Rock is left empty, like the cells removed by [`Maze::sparsify`].
+pub struct Cave {
+    bounds: (usize, usize),
+    fill: f32,
+    floor: VisitedGrid,
+    phase: CavePhase,
+
+    seed: u64,
+}
+
+impl Cave {
+    pub fn new(bounds: (usize, usize), fill: f32) -> Self {
+        Self::with_seed(bounds, fill, random_seed())
+    }
+
+    pub fn with_seed(bounds: (usize, usize), fill: f32, seed: u64) -> Self {
+        let mut rng = Stream::Generation.rng(seed);
+
+        let mut floor = VisitedGrid::new(bounds);
+        for y in 0..bounds.1 {
+            for x in 0..bounds.0 {
+                if !rng.random_bool(fill as f64) {
+                    floor.insert((x, y));
+                }
+            }
+        }
+
+        Self {
+            bounds,
+            fill,
+            floor,
+            phase: CavePhase::Smoothing(0),
+
+            seed,
+        }
+    }
+
+    fn rocky_neighbors(&self, (x, y): (usize, usize)) -> usize {
+        let mut count = 0;
+
+        for ny in y as isize - 1..=y as isize + 1 {
+            for nx in x as isize - 1..=x as isize + 1 {
+                if (nx, ny) == (x as isize, y as isize) {
+                    continue;
+                }
+
+                let inside = nx >= 0
+                    && ny >= 0
+                    && (nx as usize) < self.bounds.0
+                    && (ny as usize) < self.bounds.1;
+
+                if !inside || !self.floor.contains((nx as usize, ny as usize)) {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    fn smooth(&mut self) {
+        let mut floor = self.floor.clone();
+
+        for y in 0..self.bounds.1 {
+            for x in 0..self.bounds.0 {
+                match self.rocky_neighbors((x, y)) {
+                    5.. => floor.remove((x, y)),
+                    ..4 => floor.insert((x, y)),
+                    4 => continue,
+                };
+            }
+        }
+
+        self.floor = floor;
+    }
+
+    /// Floods out from the cave of the first cell, through rock as well as floor. Reaching a cave
+    /// not joined yet digs a tunnel back along the way the flood came, so every tunnel is as short
+    /// as the caves joined before it allow.
+    fn connect(&mut self) {
+        let end = (self.bounds.0 - 1, self.bounds.1 - 1);
+        self.floor.insert((0, 0));
+        self.floor.insert(end);
+
+        let mut seen = VisitedGrid::new(self.bounds);
+        let mut parents = ParentGrid::new(self.bounds);
+        let mut queue = VecDeque::new();
+
+        self.join_cave((0, 0), &mut seen, &mut queue);
+
+        while let Some(pos) = queue.pop_front() {
+            for (x, y, _) in neighbors(pos, self.bounds) {
+                if !seen.insert((x, y)) {
+                    continue;
+                }
+                parents.set((x, y), pos);
+
+                if !self.floor.contains((x, y)) {
+                    queue.push_back((x, y));
+                    continue;
+                }
+
+                // Dig through the rock the flood crossed to get here
+                let mut tunnel = pos;
+                while !self.floor.contains(tunnel) {
+                    self.floor.insert(tunnel);
+                    tunnel = parents.get(tunnel).unwrap();
+                }
+
+                seen.remove((x, y));
+                self.join_cave((x, y), &mut seen, &mut queue);
+            }
+        }
+    }
+
+    /// Marks every floor cell connected to `start` as seen, queueing them to flood on from
+    fn join_cave(
+        &self,
+        start: (usize, usize),
+        seen: &mut VisitedGrid,
+        queue: &mut VecDeque<(usize, usize)>,
+    ) {
+        let mut stack = vec![start];
+        seen.insert(start);
+
+        while let Some(pos) = stack.pop() {
+            queue.push_back(pos);
+
+            for (x, y, _) in neighbors(pos, self.bounds) {
+                if self.floor.contains((x, y)) && seen.insert((x, y)) {
+                    stack.push((x, y));
+                }
+            }
+        }
+    }
+}
+
+/// The cells next to `(x, y)` inside `bounds`, and the direction to each
+fn neighbors(
+    (x, y): (usize, usize),
+    bounds: (usize, usize),
+) -> impl Iterator<Item = (usize, usize, Direction)> {
+    Direction::ALL.into_iter().filter_map(move |direction| {
+        direction
+            .travel(x, y)
+            .filter(|&(x, y)| x < bounds.0 && y < bounds.1)
+            .map(|(x, y)| (x, y, direction))
+    })
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for Cave {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        // The floor still to carve
+        let first_row = match self.phase {
+            CavePhase::Smoothing(_) => 0,
+            CavePhase::Carving(row) => row,
+            CavePhase::Done => return,
+        };
+
+        draw_marks(
+            target,
+            rs,
+            self.floor.iter().filter(|&(_, y)| y >= first_row),
+            Mark::Frontier,
+            false,
+        );
+    }
+}
+
+impl MazeGenerator for Cave {
+    fn reset(&mut self, bounds: (usize, usize)) {
+        *self = Self::new(bounds, self.fill);
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> GenStep {
+        match self.phase {
+            CavePhase::Smoothing(passes) => {
+                if passes < CAVE_SMOOTHING_PASSES {
+                    self.smooth();
+                    self.phase = CavePhase::Smoothing(passes + 1);
+                } else {
+                    self.connect();
+                    self.phase = CavePhase::Carving(0);
+                }
+            }
+            CavePhase::Carving(y) => {
+                for x in 0..self.bounds.0 {
+                    if !self.floor.contains((x, y)) {
+                        continue;
+                    }
+
+                    for direction in [Direction::RIGHT, Direction::DOWN] {
+                        let next = direction.travel(x, y).unwrap();
+
+                        if next.0 < self.bounds.0
+                            && next.1 < self.bounds.1
+                            && self.floor.contains(next)
+                        {
+                            maze.carve(x, y, direction);
+                        }
+                    }
+                }
+
+                self.phase = match y + 1 < self.bounds.1 {
+                    true => CavePhase::Carving(y + 1),
+                    false => CavePhase::Done,
+                };
+            }
+            CavePhase::Done => {}
+        }
+
+        match self.phase {
+            CavePhase::Done => GenStep::Done,
+            _ => self.in_progress(),
+        }
+    }
+}
+
+impl GenerationProgress for Cave {
+    fn remaining(&self) -> usize {
+        let rows = match self.phase {
+            CavePhase::Smoothing(_) => self.bounds.1,
+            CavePhase::Carving(row) => self.bounds.1 - row,
+            CavePhase::Done => 0,
+        };
+
+        rows * self.bounds.0
+    }
+
+    fn total(&self) -> usize {
+        self.bounds.0 * self.bounds.1
+    }
+}
+
+/// Randomized Kruskal's algorithm. Walls are knocked down in a random order whenever they part
+/// cells not joined yet, making many short dead ends.
+pub struct Kruskal {
+    width: usize,
+    /// Walls still to look at, each as a cell and the direction of its right or bottom wall, the
+    /// next one last
+    walls: Vec<(usize, usize, Direction)>,
+    /// Disjoint sets of the cells joined so far, as the parent of every cell in row-major order
+    sets: Vec<usize>,
+    /// The cells joined by the last wall knocked down
+    last: Option<[(usize, usize); 2]>,
+    /// A passage is carved per step, one fewer than there are cells
+    remaining: usize,
+    total: usize,
+
+    seed: u64,
+}
+
+impl Kruskal {
+    pub fn new(bounds: (usize, usize)) -> Self {
+        Self::with_seed(bounds, random_seed())
+    }
+
+    pub fn with_seed(bounds: (usize, usize), seed: u64) -> Self {
+        let mut walls = vec![];
+        for y in 0..bounds.1 {
+            for x in 0..bounds.0 {
+                if x + 1 < bounds.0 {
+                    walls.push((x, y, Direction::RIGHT));
+                }
+                if y + 1 < bounds.1 {
+                    walls.push((x, y, Direction::DOWN));
+                }
+            }
+        }
+        walls.shuffle(&mut Stream::Generation.rng(seed));
+
+        Self {
+            width: bounds.0,
+            walls,
+            sets: (0..bounds.0 * bounds.1).collect(),
+            last: None,
+            remaining: (bounds.0 * bounds.1).saturating_sub(1),
+            total: bounds.0 * bounds.1,
+
+            seed,
+        }
+    }
+
+    /// The set `pos` is in, halving the way to it for later lookups
+    fn find(&mut self, (x, y): (usize, usize)) -> usize {
+        let mut cell = y * self.width + x;
+
+        while self.sets[cell] != cell {
+            self.sets[cell] = self.sets[self.sets[cell]];
+            cell = self.sets[cell];
+        }
+
+        cell
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for Kruskal {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        if let Some(last) = self.last.filter(|_| self.remaining > 0) {
+            draw_marks(target, rs, last, Mark::Frontier, false);
+        }
+    }
+}
+
+impl MazeGenerator for Kruskal {
+    fn reset(&mut self, bounds: (usize, usize)) {
+        *self = Self::new(bounds);
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> GenStep {
+        while self.remaining > 0 {
+            let Some((x, y, direction)) = self.walls.pop() else {
+                break;
+            };
+            let next = direction.travel(x, y).unwrap();
+
+            let (set, other) = (self.find((x, y)), self.find(next));
+            if set == other {
+                continue;
+            }
+
+            self.sets[set] = other;
+            maze.carve(x, y, direction);
+            self.last = Some([(x, y), next]);
+            self.remaining -= 1;
+
+            return self.in_progress();
+        }
+
+        GenStep::Done
+    }
+}
+
+impl GenerationProgress for Kruskal {
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Continuously mutates a perfect maze with the origin shift algorithm. Every cell points towards
+/// a single origin cell; each mutation moves the origin to a random neighbor, carving the passage
+/// towards it and closing the neighbor's old passage, so the maze stays perfect.
+pub struct OriginShift {
+    origin: (usize, usize),
+    pointers: ParentGrid,
+
+    rng: SeedRng,
+}
+
+impl OriginShift {
+    pub fn new(maze: &Maze) -> Self {
+        Self::with_seed(maze, random_seed())
+    }
+
+    pub fn with_seed(maze: &Maze, seed: u64) -> Self {
+        let mut rng = Stream::Mutation.rng(seed);
+        let bounds = maze.get_bounds();
+        let origin = (rng.random_range(..bounds.0), rng.random_range(..bounds.1));
+
+        // Point every reachable cell towards the origin along the existing passages
+        let mut pointers = ParentGrid::new(bounds);
+        let mut visited = VisitedGrid::new(bounds);
+        let mut queue = VecDeque::from([origin]);
+        visited.insert(origin);
+
+        while let Some(pos) = queue.pop_front() {
+            let (neighbors, count) = maze.get_travellable_neighbors(pos);
+
+            for next in neighbors.into_iter().take(count) {
+                if visited.insert(next) {
+                    pointers.set(next, pos);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Self {
+            origin,
+            pointers,
+            rng,
+        }
+    }
+
+    /// Moves the origin to a random neighbor. Does nothing in a maze of one cell.
+    pub fn mutate(&mut self, maze: &mut Maze) {
+        let neighbors = maze.get_neighbors(self.origin);
+        if neighbors.is_empty() {
+            return;
+        }
+
+        let (x, y, direction) = neighbors[self.rng.random_range(..neighbors.len())];
+
+        let old_pointer = self.pointers.get((x, y));
+
+        maze.carve(self.origin.0, self.origin.1, direction);
+        self.pointers.set(self.origin, (x, y));
+
+        if let Some(old) = old_pointer.filter(|old| *old != self.origin) {
+            maze.uncarve(x, y, Direction::between((x, y), old).unwrap());
+        }
+
+        self.pointers.remove((x, y));
+        self.origin = (x, y);
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for OriginShift {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let radius = get_cell_size() as f32 / 4.;
+
+        let mut circle = CircleShape::new(radius, 12);
+        circle.set_fill_color(Color::BLUE);
+        circle.set_origin((radius, radius));
+        circle.set_position((
+            (self.origin.0 * 2 + 1) as f32 * radius * 2.,
+            (self.origin.1 * 2 + 1) as f32 * radius * 2.,
+        ));
+
+        target.draw_circle_shape(&circle, rs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::DEFAULT_CAVE_FILL;
+    use crate::formats::dat;
+
+    fn generate(mut generator: impl MazeGenerator, maze: &mut Maze) {
+        for _ in 0..10_000 {
+            if generator.step(maze).is_done() {
+                return;
+            }
+        }
+
+        panic!("generator did not finish");
+    }
+
+    /// FNV-1a, which unlike the standard library's hasher is fixed across releases
+    fn hash(data: &[u8]) -> u64 {
+        data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+        })
+    }
+
+    /// Cells reachable from the top left corner, and passages between cells
+    fn reach(maze: &Maze) -> (usize, usize) {
+        let mut seen = vec![false; maze.cell_count()];
+        let mut stack = vec![(0, 0)];
+        let mut reached = 0;
+        seen[0] = true;
+
+        while let Some(cell) = stack.pop() {
+            reached += 1;
+
+            let (neighbors, count) = maze.get_travellable_neighbors(cell);
+            for &(x, y) in &neighbors[..count] {
+                let i = maze.xy_to_i(x, y);
+
+                if !seen[i] {
+                    seen[i] = true;
+                    stack.push((x, y));
+                }
+            }
+        }
+
+        let passages: usize = maze
+            .iter_coords()
+            .map(|cell| maze.get_travellable_neighbors(cell).1)
+            .sum();
+
+        (reached, passages / 2)
+    }
+
+    #[test]
+    fn single_cell_mazes_finish() {
+        let mut maze = Maze::new(1, 1);
+        generate(Wilson::with_seed((1, 1), 7), &mut maze);
+        generate(RandomDFS::with_seed((1, 1), 7), &mut maze);
+        generate(Cave::with_seed((1, 1), DEFAULT_CAVE_FILL, 7), &mut maze);
+        generate(Kruskal::with_seed((1, 1), 7), &mut maze);
+    }
+
+    #[test]
+    fn wilson_connects_every_cell() {
+        let mut maze = Maze::new(5, 4);
+        generate(Wilson::with_seed((5, 4), 7), &mut maze);
+
+        assert!(maze.cells().all(|(_, _, cell)| !cell.is_empty()));
+    }
+
+    #[test]
+    fn kruskal_makes_a_perfect_maze() {
+        let mut maze = Maze::new(6, 5);
+        generate(Kruskal::with_seed((6, 5), 7), &mut maze);
+
+        assert_eq!(reach(&maze), (30, 29));
+    }
+
+    /// Hash of the `.dat` file of the 12x9 maze `generator` makes
+    fn made(generator: impl MazeGenerator) -> u64 {
+        let mut maze = Maze::new(12, 9);
+        generate(generator, &mut maze);
+
+        hash(&dat::encode(&maze).unwrap())
+    }
+
+    /// Known answers: when these change, old seeds and share codes make different mazes
+    #[test]
+    fn seeds_make_the_same_mazes() {
+        let bounds = (12, 9);
+
+        assert_eq!(made(Wilson::with_seed(bounds, 42)), 0x6007_5d81_84ec_b7c1);
+        assert_eq!(
+            made(RandomDFS::with_seed(bounds, 42)),
+            0xe2ff_db62_b2d5_1bad
+        );
+        assert_eq!(
+            made(Cave::with_seed(bounds, DEFAULT_CAVE_FILL, 42)),
+            0x3ad2_ffd9_a137_fcad
+        );
+        assert_eq!(made(Kruskal::with_seed(bounds, 42)), 0xec77_b8ee_825a_f8de);
+    }
+
+    #[test]
+    fn streams_draw_the_same_numbers() {
+        assert_eq!(
+            Stream::Braiding.rng(42).random::<u64>(),
+            0x48ec_7bb4_de4f_ec61
+        );
+        assert_eq!(
+            Stream::TieBreak.rng(42).random::<u64>(),
+            0xc5b7_dde8_4ead_9138
+        );
     }
 }
@@ -0,0 +1,75 @@
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+
+use super::Maze;
+use crate::Direction;
+
+impl Maze {
+    /// Graph with a node per cell, weighted by its position, and an edge per passage. Node `i` is
+    /// the cell at [`Maze::i_to_xy`]`(i)`. Every passage is one step long, so edges weigh `1`.
+    pub fn to_petgraph(&self) -> UnGraph<(usize, usize), u32> {
+        let (width, height) = self.get_bounds();
+        let mut graph = UnGraph::with_capacity(self.cell_count(), self.cell_count());
+
+        for pos in self.iter_coords() {
+            graph.add_node(pos);
+        }
+
+        // Only right and down passages, so every edge is added once
+        for (x, y) in self.iter_coords() {
+            for direction in [Direction::RIGHT, Direction::DOWN] {
+                if let Some((nx, ny)) = self.travel((x, y), direction) {
+                    graph.add_edge(
+                        NodeIndex::new(y * width + x),
+                        NodeIndex::new(ny * width + nx),
+                        1,
+                    );
+                }
+            }
+        }
+
+        debug_assert_eq!(graph.node_count(), width * height);
+        graph
+    }
+
+    /// Builds a maze with a passage for every edge of `graph`, whose nodes are cell positions like
+    /// those of [`Maze::to_petgraph`]. The maze is just large enough to hold every node, and edge
+    /// weights are ignored. Meant for spanning trees, such as a minimum spanning tree of a randomly
+    /// weighted grid, but any edges between neighboring cells are carved.
+    pub fn from_spanning_tree<E>(graph: &UnGraph<(usize, usize), E>) -> Result<Self, String> {
+        let bounds = graph
+            .node_weights()
+            .fold(None, |bounds: Option<(usize, usize)>, &(x, y)| {
+                let (width, height) = bounds.unwrap_or_default();
+                Some((width.max(x + 1), height.max(y + 1)))
+            })
+            .ok_or("graph has no nodes")?;
+
+        let (Ok(width), Ok(height)) = (u16::try_from(bounds.0), u16::try_from(bounds.1)) else {
+            return Err(format!(
+                "maze of {}x{} cells is too large, at most {} a side",
+                bounds.0,
+                bounds.1,
+                u16::MAX
+            ));
+        };
+
+        let mut maze = Maze::new(width, height);
+
+        for edge in graph.edge_references() {
+            let from = graph[edge.source()];
+            let to = graph[edge.target()];
+
+            let direction = Direction::between(from, to).ok_or_else(|| {
+                format!(
+                    "edge between {},{} and {},{} does not join neighboring cells",
+                    from.0, from.1, to.0, to.1
+                )
+            })?;
+
+            maze.carve(from.0, from.1, direction);
+        }
+
+        Ok(maze)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{Direction, DirectionSet};
+
+/// Flat per-cell visited flags, one bit per cell, indexed the same way as [`super::Maze`].
+#[derive(Debug, Clone)]
+pub struct VisitedGrid {
+    width: usize,
+    height: usize,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl VisitedGrid {
+    pub fn new(bounds: (usize, usize)) -> Self {
+        Self {
+            width: bounds.0,
+            height: bounds.1,
+            bits: vec![0; (bounds.0 * bounds.1).div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        let i = y * self.width + x;
+
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// Returns `true` if the cell was not already visited
+    pub fn insert(&mut self, (x, y): (usize, usize)) -> bool {
+        let i = y * self.width + x;
+        let mask = 1 << (i % 64);
+
+        if self.bits[i / 64] & mask != 0 {
+            return false;
+        }
+
+        self.bits[i / 64] |= mask;
+        self.len += 1;
+
+        true
+    }
+
+    /// Returns `true` if the cell was visited
+    pub fn remove(&mut self, (x, y): (usize, usize)) -> bool {
+        let i = y * self.width + x;
+        let mask = 1 << (i % 64);
+
+        if self.bits[i / 64] & mask == 0 {
+            return false;
+        }
+
+        self.bits[i / 64] &= !mask;
+        self.len -= 1;
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.len = 0;
+    }
+
+    /// Heap size of the flags
+    pub fn bytes(&self) -> usize {
+        self.bits.len() * size_of::<u64>()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.width * self.height)
+            .filter(|i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+            .map(|i| (i % self.width, i / self.width))
+    }
+}
+
+/// Flat per-cell step numbers of when each cell was first visited, indexed the same way as
+/// [`super::Maze`], so how long ago a solver was somewhere can be drawn.
+#[derive(Debug, Clone)]
+pub struct VisitTimes {
+    width: usize,
+    /// Step of the first visit plus one, `0` while unvisited
+    times: Vec<u32>,
+    now: u32,
+}
+
+impl VisitTimes {
+    pub fn new(bounds: (usize, usize)) -> Self {
+        Self {
+            width: bounds.0,
+            times: vec![0; bounds.0 * bounds.1],
+            now: 0,
+        }
+    }
+
+    /// Moves on to the next step, which cells inserted from now on are stamped with
+    pub fn tick(&mut self) {
+        self.now += 1;
+    }
+
+    /// Stamps the cell with the current step, unless it was visited before
+    pub fn insert(&mut self, (x, y): (usize, usize)) {
+        let time = &mut self.times[y * self.width + x];
+
+        if *time == 0 {
+            *time = self.now + 1;
+        }
+    }
+
+    /// Steps since the cell was first visited, `0` during the step it was
+    pub fn age(&self, (x, y): (usize, usize)) -> Option<u32> {
+        match self.times[y * self.width + x] {
+            0 => None,
+            time => Some(self.now + 1 - time),
+        }
+    }
+
+    /// Every visited cell with its [`Self::age`]
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), u32)> + '_ {
+        self.times
+            .iter()
+            .enumerate()
+            .filter(|(_, &time)| time != 0)
+            .map(|(i, time)| ((i % self.width, i / self.width), self.now + 1 - time))
+    }
+}
+
+/// Flat per-cell parent pointers. Since a parent is always an adjacent cell, each entry is stored
+/// as the direction towards the parent, packed two cells per byte like the maze file format.
+#[derive(Debug, Clone)]
+pub struct ParentGrid {
+    width: usize,
+    cells: Vec<u8>,
+}
+
+impl ParentGrid {
+    pub fn new(bounds: (usize, usize)) -> Self {
+        Self {
+            width: bounds.0,
+            cells: vec![0; (bounds.0 * bounds.1).div_ceil(2)],
+        }
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        self.direction((x, y))?.travel(x, y)
+    }
+
+    /// The direction from `pos` towards its parent
+    pub fn direction(&self, (x, y): (usize, usize)) -> Option<Direction> {
+        let i = y * self.width + x;
+        let byte = self.cells[i / 2];
+        let nibble = if i.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0f
+        };
+
+        DirectionSet::from_bits(nibble).iter().next()
+    }
+
+    /// Panics if `parent` is not orthogonally adjacent to `pos`
+    pub fn set(&mut self, pos: (usize, usize), parent: (usize, usize)) {
+        let direction = Direction::between(pos, parent)
+            .unwrap_or_else(|| panic!("{parent:?} is not adjacent to {pos:?}"));
+
+        self.write(pos, DirectionSet::from(direction));
+    }
+
+    pub fn remove(&mut self, pos: (usize, usize)) {
+        self.write(pos, DirectionSet::EMPTY);
+    }
+
+    fn write(&mut self, (x, y): (usize, usize), direction: DirectionSet) {
+        let i = y * self.width + x;
+        let byte = &mut self.cells[i / 2];
+
+        let bits = direction.bits();
+
+        *byte = if i.is_multiple_of(2) {
+            (*byte & 0x0f) | bits << 4
+        } else {
+            (*byte & 0xf0) | bits
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(0);
+    }
+
+    /// Heap size of the parent pointers
+    pub fn bytes(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Follows parent pointers back from `end`, returning the path from its root to `end`
+    pub fn path_to(&self, end: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut path = vec![end];
+        let mut pos = end;
+
+        while let Some(parent) = self.get(pos) {
+            path.push(parent);
+            pos = parent;
+        }
+
+        path.reverse();
+
+        path
+    }
+}
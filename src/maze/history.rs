@@ -0,0 +1,54 @@
+use crate::DirectionSet;
+
+/// A single cell write, kept with the value it replaced so it can be played either way
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Change {
+    pub i: usize,
+    pub before: DirectionSet,
+    pub after: DirectionSet,
+}
+
+/// Edits made to a maze, each a carve, close or delete made of the cell writes it took
+#[derive(Debug, Clone, Default)]
+pub(crate) struct History {
+    /// Writes of the edit in progress
+    pending: Vec<Change>,
+    done: Vec<Vec<Change>>,
+    undone: Vec<Vec<Change>>,
+}
+
+impl History {
+    pub fn record(&mut self, change: Change) {
+        self.pending.push(change);
+    }
+
+    /// Closes the edit in progress, which also forgets anything that could be redone
+    pub fn end_edit(&mut self) {
+        if !self.pending.is_empty() {
+            self.done.push(std::mem::take(&mut self.pending));
+            self.undone.clear();
+        }
+    }
+
+    /// Cell values reverting the last edit, in the order to write them
+    pub fn undo(&mut self) -> Option<Vec<(usize, DirectionSet)>> {
+        let edit = self.done.pop()?;
+        let writes = edit
+            .iter()
+            .rev()
+            .map(|change| (change.i, change.before))
+            .collect();
+
+        self.undone.push(edit);
+        Some(writes)
+    }
+
+    /// Cell values making the last undone edit again, in the order to write them
+    pub fn redo(&mut self) -> Option<Vec<(usize, DirectionSet)>> {
+        let edit = self.undone.pop()?;
+        let writes = edit.iter().map(|change| (change.i, change.after)).collect();
+
+        self.done.push(edit);
+        Some(writes)
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use crate::maze::generators::RandomDFS;
+use crate::maze::{Maze, MazeGenerator};
+use crate::{Direction, DirectionSet};
+
+/// Width and height of a chunk, in cells
+pub const CHUNK_SIZE: usize = 32;
+
+/// A maze without bounds, made of [`CHUNK_SIZE`] square chunks generated the first time they are
+/// needed. Every chunk is a perfect maze from a seed derived from the maze seed and the chunk's
+/// position, with one door through each side shared with the neighboring chunk, so a chunk comes
+/// out the same whenever it is made again and neighbors always meet.
+///
+/// Positions are in cells and may be negative.
+pub struct InfiniteMaze {
+    seed: u64,
+    chunks: HashMap<(i64, i64), Maze>,
+}
+
+impl InfiniteMaze {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Chunks currently generated
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// The chunk containing the cell
+    pub fn chunk_of((x, y): (i64, i64)) -> (i64, i64) {
+        (
+            x.div_euclid(CHUNK_SIZE as i64),
+            y.div_euclid(CHUNK_SIZE as i64),
+        )
+    }
+
+    /// Generates the chunk if needed
+    pub fn chunk(&mut self, chunk: (i64, i64)) -> &Maze {
+        let seed = self.seed;
+
+        self.chunks
+            .entry(chunk)
+            .or_insert_with(|| generate_chunk(seed, chunk))
+    }
+
+    pub fn get(&mut self, (x, y): (i64, i64)) -> DirectionSet {
+        let size = CHUNK_SIZE as i64;
+
+        self.chunk(Self::chunk_of((x, y)))
+            .get(x.rem_euclid(size) as usize, y.rem_euclid(size) as usize)
+    }
+
+    /// The cell reached by leaving `pos` in `direction`, if no wall is in the way
+    pub fn travel(&mut self, (x, y): (i64, i64), direction: Direction) -> Option<(i64, i64)> {
+        if !self.get((x, y)).contains(direction) {
+            return None;
+        }
+
+        Some(match direction {
+            Direction::UP => (x, y - 1),
+            Direction::RIGHT => (x + 1, y),
+            Direction::DOWN => (x, y + 1),
+            Direction::LEFT => (x - 1, y),
+        })
+    }
+
+    /// Copies the chunks up to `radius` chunks away from `center` into a single maze, whose top
+    /// left cell is the returned position. Chunks further away than that are dropped, to be
+    /// generated again if they are needed later.
+    pub fn window(&mut self, center: (i64, i64), radius: usize) -> (Maze, (i64, i64)) {
+        let radius = radius as i64;
+        let side = (2 * radius + 1) as usize * CHUNK_SIZE;
+
+        self.chunks.retain(|&(cx, cy), _| {
+            (cx - center.0).abs() <= radius + 1 && (cy - center.1).abs() <= radius + 1
+        });
+
+        let mut maze = Maze::new(side as u16, side as u16);
+
+        for cy in -radius..=radius {
+            for cx in -radius..=radius {
+                let chunk = self.chunk((center.0 + cx, center.1 + cy));
+                let left = (cx + radius) as usize * CHUNK_SIZE;
+                let top = (cy + radius) as usize * CHUNK_SIZE;
+
+                for (x, y, cell) in chunk.cells() {
+                    for direction in cell.iter() {
+                        maze.open(left + x, top + y, direction);
+                    }
+                }
+            }
+        }
+
+        let origin = (
+            (center.0 - radius) * CHUNK_SIZE as i64,
+            (center.1 - radius) * CHUNK_SIZE as i64,
+        );
+
+        (maze, origin)
+    }
+}
+
+fn generate_chunk(seed: u64, (cx, cy): (i64, i64)) -> Maze {
+    let last = CHUNK_SIZE - 1;
+
+    let mut maze = Maze::new(CHUNK_SIZE as u16, CHUNK_SIZE as u16);
+    let mut generator = RandomDFS::with_seed((CHUNK_SIZE, CHUNK_SIZE), mix(seed, cx, cy, 0));
+
+    while !generator.step(&mut maze).is_done() {}
+
+    maze.carve(door(seed, (cx, cy), Direction::UP), 0, Direction::UP);
+    maze.carve(
+        door(seed, (cx, cy + 1), Direction::UP),
+        last,
+        Direction::DOWN,
+    );
+    maze.carve(0, door(seed, (cx, cy), Direction::LEFT), Direction::LEFT);
+    maze.carve(
+        last,
+        door(seed, (cx + 1, cy), Direction::LEFT),
+        Direction::RIGHT,
+    );
+
+    maze
+}
+
+/// Where the top (`UP`) or left (`LEFT`) side of the chunk opens into its neighbor, as a column or
+/// row of the chunk. The neighbor looks up the same door for its bottom or right side.
+fn door(seed: u64, (cx, cy): (i64, i64), side: Direction) -> usize {
+    (mix(seed, cx, cy, side as u64) % CHUNK_SIZE as u64) as usize
+}
+
+/// SplitMix64 over the seed, a chunk position and what the number is for
+fn mix(seed: u64, a: i64, b: i64, salt: u64) -> u64 {
+    let mut z = seed;
+
+    for value in [a as u64, b as u64, salt] {
+        z = (z ^ value).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+    }
+
+    z
+}
@@ -1,56 +1,307 @@
+pub mod agents;
 pub mod generators;
+#[cfg(feature = "petgraph")]
+mod graph;
+pub mod grid;
+mod history;
+pub mod infinite;
+pub mod metadata;
+pub mod picture;
+pub mod render;
+pub mod seed;
 pub mod solvers;
+pub mod symmetry;
+pub mod tags;
 
-use crate::Direction;
+use std::collections::BTreeMap;
+
+use crate::{formats, Direction, DirectionSet};
+use grid::VisitedGrid;
+use history::{Change, History};
+use metadata::Metadata;
+#[cfg(feature = "gui")]
 use sfml::{
-    graphics::{Drawable, RectangleShape, Shape, Transformable},
+    graphics::{Color, Drawable, RectangleShape, Shape, Transformable},
     system::Vector2f,
 };
+use tags::{Tag, TagSet, LOCK_COLORS};
 
-use crate::consts::{get_cell_size, CELL_COLOR, EMPTY_CELL_COLOR, WALL_COLOR, WALL_WIDTH};
+#[cfg(feature = "gui")]
+use crate::consts::{get_cell_size, get_wall_width, wall_insets};
+#[cfg(feature = "gui")]
+use crate::theme::theme;
 
-#[derive(Debug)]
+/// Cells are packed two per byte (the even-indexed cell in the high nibble), matching the file
+/// format.
+#[derive(Debug, Clone)]
 pub struct Maze {
     width: usize,
     height: usize,
     cells: Vec<u8>,
+
+    /// Cells changed since the last [`Maze::take_dirty`], each listed once
+    dirty: Vec<(usize, usize)>,
+    dirty_marks: VisitedGrid,
+
+    /// Only kept once [`Maze::record_history`] is called
+    history: Option<History>,
+    /// Every cell written since the last [`Maze::take_changes`], only kept once
+    /// [`Maze::record_changes`] is called
+    changes: Option<Vec<(usize, usize)>>,
+
+    metadata: Metadata,
+
+    /// Tags of the tagged cells only, by cell index
+    tags: BTreeMap<usize, TagSet>,
+    /// Colors of keys and doors other than `0`, by cell index
+    lock_colors: BTreeMap<usize, u8>,
 }
 
 impl Maze {
     pub fn new(width: u16, height: u16) -> Self {
-        let cells = vec![0; width as usize * height as usize];
+        let cells = vec![0; (width as usize * height as usize).div_ceil(2)];
+
+        Self::from_cells(width as usize, height as usize, cells)
+    }
 
+    /// `cells` must be packed like [`Maze::packed_cells`]
+    pub(crate) fn from_cells(width: usize, height: usize, cells: Vec<u8>) -> Self {
         Self {
-            width: width as usize,
-            height: height as usize,
+            width,
+            height,
             cells,
+
+            dirty: vec![],
+            dirty_marks: VisitedGrid::new((width, height)),
+
+            history: None,
+            changes: None,
+
+            metadata: Metadata::default(),
+
+            tags: BTreeMap::new(),
+            lock_colors: BTreeMap::new(),
         }
     }
 
     pub fn from_data(data: &[u8]) -> Result<Self, String> {
-        decode_maze(data)
+        formats::dat::decode(data)
+    }
+
+    /// Reads a maze pasted as text, see [`formats::share`]
+    pub fn from_share_code(code: &str) -> Result<Self, String> {
+        formats::share::decode(code)
+    }
+
+    pub(crate) fn packed_cells(&self) -> &[u8] {
+        &self.cells
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Panics if `(x, y)` is outside the maze
+    pub fn tags(&self, x: usize, y: usize) -> TagSet {
+        let i = self.xy_to_i(x, y);
+
+        self.tags.get(&i).copied().unwrap_or_default()
+    }
+
+    /// Panics if `(x, y)` is outside the maze
+    pub fn tag(&mut self, x: usize, y: usize, tag: Tag) {
+        let i = self.xy_to_i(x, y);
+
+        self.tags.entry(i).or_default().insert(tag);
+    }
+
+    /// Panics if `(x, y)` is outside the maze
+    pub fn untag(&mut self, x: usize, y: usize, tag: Tag) {
+        let i = self.xy_to_i(x, y);
+
+        if let Some(tags) = self.tags.get_mut(&i) {
+            tags.remove(tag);
+
+            if !tags.contains(Tag::Key) && !tags.contains(Tag::Door) {
+                self.lock_colors.remove(&i);
+            }
+            if tags.is_empty() {
+                self.tags.remove(&i);
+            }
+        }
+    }
+
+    /// Color of the key or door at `(x, y)`, a key opening the doors of its color. `0` unless
+    /// set. Panics if `(x, y)` is outside the maze.
+    pub fn lock_color(&self, x: usize, y: usize) -> u8 {
+        let i = self.xy_to_i(x, y);
+
+        self.lock_colors.get(&i).copied().unwrap_or(0)
     }
 
-    pub fn get(&self, x: usize, y: usize) -> u8 {
-        if x >= self.width {
-            panic!("x {} larger than width {}", x, self.width)
-        } else if y >= self.height {
-            panic!("y {} larger than width {}", y, self.height)
+    /// Fails if `(x, y)` is neither a key nor a door, or `color` is not below [`LOCK_COLORS`].
+    /// Panics if `(x, y)` is outside the maze.
+    pub fn set_lock_color(&mut self, x: usize, y: usize, color: u8) -> Result<(), String> {
+        let tags = self.tags(x, y);
+        if !tags.contains(Tag::Key) && !tags.contains(Tag::Door) {
+            return Err(format!("({x}, {y}) is neither a key nor a door to color"));
+        }
+        if color >= LOCK_COLORS {
+            return Err(format!("lock color {color} is not below {LOCK_COLORS}"));
+        }
+
+        let i = self.xy_to_i(x, y);
+        match color {
+            0 => self.lock_colors.remove(&i),
+            color => self.lock_colors.insert(i, color),
+        };
+
+        Ok(())
+    }
+
+    /// Every cell with at least one tag as `(x, y, tags)`, in row-major order
+    pub fn tagged(&self) -> impl Iterator<Item = (usize, usize, TagSet)> + '_ {
+        self.tags
+            .iter()
+            .map(|(&i, &tags)| (i % self.width, i / self.width, tags))
+    }
+
+    /// Cells tagged with `tag`, in row-major order
+    pub fn find_tag(&self, tag: Tag) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.tagged()
+            .filter(move |(_, _, tags)| tags.contains(tag))
+            .map(|(x, y, _)| (x, y))
+    }
+
+    /// Returns `None` if `(x, y)` is outside the maze
+    pub fn try_get(&self, x: usize, y: usize) -> Option<DirectionSet> {
+        if x >= self.width || y >= self.height {
+            None
         } else {
-            self.cells[y * self.width + x]
+            Some(self.read(y * self.width + x))
         }
     }
 
-    pub fn geti(&self, i: usize) -> u8 {
-        if i >= self.cells.len() {
-            panic!(
-                "i {} larger than cells array length {}",
+    /// Returns `None` if `i` is not a valid cell index
+    pub fn try_geti(&self, i: usize) -> Option<DirectionSet> {
+        if i >= self.cell_count() {
+            None
+        } else {
+            Some(self.read(i))
+        }
+    }
+
+    /// Panics if `(x, y)` is outside the maze, use [`Maze::try_get`] for untrusted coordinates
+    pub fn get(&self, x: usize, y: usize) -> DirectionSet {
+        match self.try_get(x, y) {
+            Some(cell) => cell,
+            None => panic!(
+                "({}, {}) outside of maze bounds ({}, {})",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+
+    /// Panics if `i` is not a valid cell index, use [`Maze::try_geti`] for untrusted indices
+    pub fn geti(&self, i: usize) -> DirectionSet {
+        match self.try_geti(i) {
+            Some(cell) => cell,
+            None => panic!("i {} larger than cell count {}", i, self.cell_count()),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `(x, y)` must be within [`Maze::get_bounds`]
+    pub unsafe fn get_unchecked(&self, x: usize, y: usize) -> DirectionSet {
+        let i = y * self.width + x;
+        let byte = *self.cells.get_unchecked(i / 2);
+
+        DirectionSet::from_bits(if i.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0f
+        })
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Every cell as `(x, y, cell)`, in row-major order
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, DirectionSet)> + '_ {
+        (0..self.cell_count()).map(|i| (i % self.width, i / self.width, self.read(i)))
+    }
+
+    /// The cells of each row in turn, top to bottom
+    pub fn rows(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = (usize, usize, DirectionSet)> + '_> + '_ {
+        (0..self.height)
+            .map(move |y| (0..self.width).map(move |x| (x, y, self.read(y * self.width + x))))
+    }
+
+    /// Coordinates of every cell, in the same order as [`Maze::cells`]
+    pub fn iter_coords(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+
+        (0..self.cell_count()).map(move |i| (i % width, i / width))
+    }
+
+    fn read(&self, i: usize) -> DirectionSet {
+        let byte = self.cells[i / 2];
+
+        DirectionSet::from_bits(if i.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0f
+        })
+    }
+
+    fn write(&mut self, i: usize, value: DirectionSet) {
+        let before = self.read(i);
+
+        if let Some(history) = &mut self.history {
+            history.record(Change {
                 i,
-                self.cells.len()
-            )
+                before,
+                after: value,
+            });
+        }
+
+        self.set(i, value);
+    }
+
+    /// Writes a cell without recording it in the history
+    fn set(&mut self, i: usize, value: DirectionSet) {
+        let byte = &mut self.cells[i / 2];
+
+        *byte = if i.is_multiple_of(2) {
+            (*byte & 0x0f) | value.bits() << 4
         } else {
-            self.cells[i]
+            (*byte & 0xf0) | value.bits()
+        };
+
+        let pos = (i % self.width, i / self.width);
+        if self.dirty_marks.insert(pos) {
+            self.dirty.push(pos);
         }
+        if let Some(changes) = &mut self.changes {
+            changes.push(pos);
+        }
+    }
+
+    /// Returns the cells changed since the last call, so renderers can update incrementally
+    pub fn take_dirty(&mut self) -> Vec<(usize, usize)> {
+        for pos in &self.dirty {
+            self.dirty_marks.remove(*pos);
+        }
+
+        std::mem::take(&mut self.dirty)
     }
 
     pub fn get_bounds(&self) -> (usize, usize) {
@@ -58,7 +309,12 @@ impl Maze {
     }
 
     pub fn as_str(&self) -> Result<String, String> {
-        encode_maze(self)
+        formats::dat::encode(self).map(|data| unsafe { String::from_utf8_unchecked(data) })
+    }
+
+    /// The maze as a line of text to paste into a chat or an issue, see [`formats::share`]
+    pub fn to_share_code(&self) -> Result<String, String> {
+        formats::share::encode(self)
     }
 
     pub fn i_to_xy(&self, i: usize) -> (usize, usize) {
@@ -72,40 +328,217 @@ impl Maze {
     }
 
     pub fn open(&mut self, x: usize, y: usize, direction: Direction) {
-        let cell = self.get(x, y);
-
-        self.cells[y * self.width + x] = cell
-            | match direction {
-                Direction::UP => 0b0001,
-                Direction::RIGHT => 0b0010,
-                Direction::DOWN => 0b0100,
-                Direction::LEFT => 0b1000,
-            }
+        self.open_cell(x, y, direction);
+        self.end_edit();
     }
 
     pub fn close(&mut self, x: usize, y: usize, direction: Direction) {
-        let cell = self.get(x, y);
-
-        self.cells[y * self.width + x] = cell
-            & match direction {
-                Direction::UP => 0b1110,
-                Direction::RIGHT => 0b1101,
-                Direction::DOWN => 0b1011,
-                Direction::LEFT => 0b0111,
-            }
+        self.close_cell(x, y, direction);
+        self.end_edit();
+    }
+
+    fn open_cell(&mut self, x: usize, y: usize, direction: Direction) {
+        let mut cell = self.get(x, y);
+        cell.insert(direction);
+
+        self.write(y * self.width + x, cell)
+    }
+
+    fn close_cell(&mut self, x: usize, y: usize, direction: Direction) {
+        let mut cell = self.get(x, y);
+        cell.remove(direction);
+
+        self.write(y * self.width + x, cell)
     }
 
+    /// Opens the wall between `(x, y)` and its neighbor in `direction`. On the maze border this
+    /// only opens the wall of `(x, y)`.
     pub fn carve(&mut self, x: usize, y: usize, direction: Direction) {
-        self.open(x, y, direction);
+        self.open_cell(x, y, direction);
+
+        if let Some((x, y)) = direction.travel(x, y) {
+            if x < self.width && y < self.height {
+                self.open_cell(x, y, direction.opposite());
+            }
+        }
 
-        let (x, y) = direction.travel(x, y);
-        self.open(x, y, direction.opposite());
+        self.end_edit();
+    }
+
+    /// Closes the wall between `(x, y)` and its neighbor in `direction`, undoing [`Maze::carve`]
+    pub fn uncarve(&mut self, x: usize, y: usize, direction: Direction) {
+        self.close_cell(x, y, direction);
+
+        if let Some((x, y)) = direction.travel(x, y) {
+            if x < self.width && y < self.height {
+                self.close_cell(x, y, direction.opposite());
+            }
+        }
+
+        self.end_edit();
     }
 
     pub fn delete(&mut self, x: usize, y: usize) {
         self.get(x, y);
 
-        self.cells[y * self.width + x] = 0
+        self.write(y * self.width + x, DirectionSet::EMPTY);
+        self.end_edit();
+    }
+
+    /// Retracts every dead end, `iterations` times over, leaving the retracted cells empty. Each
+    /// pass shortens every dead-end corridor by a cell, so a few passes turn a perfect maze into
+    /// sparse caves. Tagged cells and the corners solvers run between are kept. Returns the
+    /// number of cells removed.
+    pub fn sparsify(&mut self, iterations: usize) -> usize {
+        if self.cell_count() == 0 {
+            return 0;
+        }
+
+        let corners = [(0, 0), (self.width - 1, self.height - 1)];
+        let mut removed = 0;
+
+        for _ in 0..iterations {
+            let dead_ends: Vec<((usize, usize), (usize, usize))> = self
+                .iter_coords()
+                .filter(|pos| !corners.contains(pos) && self.tags(pos.0, pos.1).is_empty())
+                .filter_map(|pos| match self.get_travellable_neighbors(pos) {
+                    (neighbors, 1) => Some((pos, neighbors[0])),
+                    _ => None,
+                })
+                .collect();
+
+            if dead_ends.is_empty() {
+                break;
+            }
+            removed += dead_ends.len();
+
+            for ((x, y), (nx, ny)) in dead_ends {
+                let direction = self
+                    .get(x, y)
+                    .iter()
+                    .find(|direction| direction.travel(x, y) == Some((nx, ny)));
+
+                if let Some(direction) = direction {
+                    self.close_cell(nx, ny, direction.opposite());
+                }
+
+                // Also closes openings through the border
+                self.write(y * self.width + x, DirectionSet::EMPTY);
+            }
+        }
+
+        self.end_edit();
+        removed
+    }
+
+    fn end_edit(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.end_edit();
+        }
+    }
+
+    /// Closes every cell and drops the tags, keeping the metadata. A kept history starts over.
+    pub fn clear(&mut self) {
+        for i in 0..self.cell_count() {
+            self.set(i, DirectionSet::EMPTY);
+        }
+
+        self.tags.clear();
+        self.lock_colors.clear();
+
+        if let Some(history) = &mut self.history {
+            *history = History::default();
+        }
+    }
+
+    /// Starts keeping every carve, close and delete from now on, so they can be undone
+    pub fn record_history(&mut self) {
+        self.history.get_or_insert_with(History::default);
+    }
+
+    /// Starts listing every written cell, in order and as often as it is written, unlike the dirty
+    /// cells which are kept for rendering
+    pub fn record_changes(&mut self) {
+        self.changes.get_or_insert_with(Vec::new);
+    }
+
+    /// Returns the cells written since the last call, empty unless [`Maze::record_changes`] was
+    /// called
+    pub fn take_changes(&mut self) -> Vec<(usize, usize)> {
+        self.changes
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Reverts the last recorded edit, returning `false` if there is none
+    pub fn undo(&mut self) -> bool {
+        let Some(writes) = self.history.as_mut().and_then(History::undo) else {
+            return false;
+        };
+
+        for (i, value) in writes {
+            self.set(i, value);
+        }
+
+        true
+    }
+
+    /// Applies the last undone edit again, returning `false` if there is none
+    pub fn redo(&mut self) -> bool {
+        let Some(writes) = self.history.as_mut().and_then(History::redo) else {
+            return false;
+        };
+
+        for (i, value) in writes {
+            self.set(i, value);
+        }
+
+        true
+    }
+
+    /// The cell reached by leaving `(x, y)` in `direction`, if no wall is in the way
+    pub fn travel(&self, (x, y): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        if !self.get(x, y).contains(direction) {
+            return None;
+        }
+
+        direction
+            .travel(x, y)
+            .filter(|&(x, y)| x < self.width && y < self.height)
+    }
+
+    /// Walks `moves` from `start`, one of the letters U, R, D and L per move like a solution
+    /// saved with `--save-solution`, returning the cell they end on and the number of moves.
+    /// Whitespace between moves is ignored. Fails with the number of moves made before the first
+    /// bad one, and why it is bad.
+    pub fn replay_solution(
+        &self,
+        start: (usize, usize),
+        moves: &str,
+    ) -> Result<((usize, usize), usize), (usize, String)> {
+        let mut pos = start;
+        let mut count = 0;
+
+        for c in moves.chars().filter(|c| !c.is_whitespace()) {
+            let Some(direction) = Direction::from_letter(c) else {
+                return Err((count, format!("`{c}` is not one of U, R, D, L")));
+            };
+
+            match self.travel(pos, direction) {
+                Some(next) => pos = next,
+                None => {
+                    return Err((
+                        count,
+                        format!("{c} from ({}, {}) runs into a wall", pos.0, pos.1),
+                    ))
+                }
+            }
+
+            count += 1;
+        }
+
+        Ok((pos, count))
     }
 
     pub fn get_neighbors(&self, (x, y): (usize, usize)) -> Vec<(usize, usize, Direction)> {
@@ -134,27 +567,29 @@ impl Maze {
         &self,
         (x, y): (usize, usize),
     ) -> ([(usize, usize); 4], usize) {
-        let value = self.get(x, y);
-
         let mut neighbors: [_; 4] = [(0, 0); 4];
         let mut neighbor_count = 0;
 
-        if x > 0 && value & Direction::LEFT as u8 != 0 {
+        let Some(value) = self.try_get(x, y) else {
+            return (neighbors, neighbor_count);
+        };
+
+        if x > 0 && value.contains(Direction::LEFT) {
             neighbors[neighbor_count] = (x - 1, y);
             neighbor_count += 1;
         }
 
-        if x + 1 < self.width && value & Direction::RIGHT as u8 != 0 {
+        if x + 1 < self.width && value.contains(Direction::RIGHT) {
             neighbors[neighbor_count] = (x + 1, y);
             neighbor_count += 1;
         }
 
-        if y > 0 && value & Direction::UP as u8 != 0 {
+        if y > 0 && value.contains(Direction::UP) {
             neighbors[neighbor_count] = (x, y - 1);
             neighbor_count += 1;
         }
 
-        if y + 1 < self.height && value & Direction::DOWN as u8 != 0 {
+        if y + 1 < self.height && value.contains(Direction::DOWN) {
             neighbors[neighbor_count] = (x, y + 1);
             neighbor_count += 1;
         }
@@ -163,93 +598,65 @@ impl Maze {
     }
 }
 
+/// Only cells and passages are drawn, so the target should be cleared with the
+/// [`crate::theme::Theme::wall`] color first. See [`render::MazeRenderer`] for a cached alternative.
+#[cfg(feature = "gui")]
 impl Drawable for Maze {
     fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
         &'a self,
         target: &mut dyn sfml::graphics::RenderTarget,
         rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
     ) {
-        target.clear(WALL_COLOR);
         let cell_size = get_cell_size();
-
-        let mut empty_rect = RectangleShape::with_size(
-            (
-                (cell_size - WALL_WIDTH * 2) as f32,
-                (cell_size - WALL_WIDTH * 2) as f32,
-            )
-                .into(),
-        );
-        empty_rect.set_origin((
-            cell_size as f32 / 2. - WALL_WIDTH as f32,
-            cell_size as f32 / 2. - WALL_WIDTH as f32,
-        ));
-        empty_rect.set_fill_color(EMPTY_CELL_COLOR);
-
-        let mut up_rect = RectangleShape::with_size(
-            (
-                (cell_size - WALL_WIDTH * 2) as f32,
-                (cell_size - WALL_WIDTH) as f32,
-            )
-                .into(),
-        );
-        up_rect.set_origin((
-            cell_size as f32 / 2. - WALL_WIDTH as f32,
-            cell_size as f32 / 2.,
-        ));
-        up_rect.set_fill_color(CELL_COLOR);
-
-        let mut down_rect = up_rect.clone();
-        down_rect.set_origin((
-            cell_size as f32 / 2. - WALL_WIDTH as f32,
-            cell_size as f32 / 2. - WALL_WIDTH as f32,
-        ));
-        down_rect.set_fill_color(CELL_COLOR);
-
-        let mut left_rect = RectangleShape::with_size(up_rect.size().perpendicular());
-        left_rect.set_origin((
-            cell_size as f32 / -2. + WALL_WIDTH as f32,
-            cell_size as f32 / 2. - WALL_WIDTH as f32,
-        ));
-        left_rect.set_fill_color(CELL_COLOR);
-
-        let mut right_rect = left_rect.clone();
-        right_rect.set_origin((
-            cell_size as f32 / -2.,
-            cell_size as f32 / 2. - WALL_WIDTH as f32,
-        ));
-        right_rect.set_fill_color(CELL_COLOR);
+        let wall = get_wall_width();
+        let (before, after) = wall_insets(wall);
+        let theme = theme();
+
+        // Every rectangle is placed at the top left corner of its cell, shifted by its origin, so
+        // all edges stay on whole pixels
+        let rect = |size: (usize, usize), offset: (usize, usize), color: Color| {
+            let mut rect = RectangleShape::with_size((size.0 as f32, size.1 as f32).into());
+            rect.set_origin((-(offset.0 as f32), -(offset.1 as f32)));
+            rect.set_fill_color(color);
+            rect
+        };
+
+        let inner = cell_size - wall;
+        let mut empty_rect = rect((inner, inner), (before, before), theme.empty_cell);
+        let mut up_rect = rect((inner, cell_size - after), (before, 0), theme.cell);
+        let mut down_rect = rect((inner, cell_size - before), (before, before), theme.cell);
+        let mut left_rect = rect((cell_size - after, inner), (0, before), theme.cell);
+        let mut right_rect = rect((cell_size - before, inner), (before, before), theme.cell);
 
         for y in 0..self.height {
             for x in 0..self.width {
-                let position = Vector2f::new(
-                    ((x * 2 + 1) * cell_size) as f32 / 2.,
-                    ((y * 2 + 1) * cell_size) as f32 / 2.,
-                );
+                let position = Vector2f::new((x * cell_size) as f32, (y * cell_size) as f32);
 
-                let cell = self.get(x, y);
+                // SAFETY: x and y are bounded by the loop ranges
+                let cell = unsafe { self.get_unchecked(x, y) };
 
-                if cell == 0 {
+                if cell.is_empty() {
                     empty_rect.set_position(position);
                     target.draw_rectangle_shape(&empty_rect, rs);
                     continue;
                 }
 
-                if (cell & Direction::UP as u8) != 0 {
+                if cell.contains(Direction::UP) {
                     up_rect.set_position(position);
                     target.draw_rectangle_shape(&up_rect, rs);
                 }
 
-                if (cell & Direction::DOWN as u8) != 0 {
+                if cell.contains(Direction::DOWN) {
                     down_rect.set_position(position);
                     target.draw_rectangle_shape(&down_rect, rs);
                 }
 
-                if (cell & Direction::LEFT as u8) != 0 {
+                if cell.contains(Direction::LEFT) {
                     left_rect.set_position(position);
                     target.draw_rectangle_shape(&left_rect, rs);
                 }
 
-                if (cell & Direction::RIGHT as u8) != 0 {
+                if cell.contains(Direction::RIGHT) {
                     right_rect.set_position(position);
                     target.draw_rectangle_shape(&right_rect, rs);
                 }
@@ -258,60 +665,120 @@ impl Drawable for Maze {
     }
 }
 
-fn encode_maze(maze: &Maze) -> Result<String, String> {
-    let mut data = vec![];
+/// How far a generator is through the maze, to report progress while generating without a window
+pub trait GenerationProgress {
+    /// Cells not yet joined to the maze
+    fn remaining(&self) -> usize;
 
-    if maze.width > u16::MAX as usize {
-        return Err(String::from("maze width too large"));
+    /// Cells the finished maze has
+    fn total(&self) -> usize;
+
+    /// [`GenStep::InProgress`] with the progress so far
+    fn in_progress(&self) -> GenStep {
+        GenStep::InProgress {
+            remaining: self.remaining(),
+            total: self.total(),
+        }
     }
+}
+
+/// Where a generator stands after a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenStep {
+    /// As in [`GenerationProgress`], after the step
+    InProgress {
+        remaining: usize,
+        total: usize,
+    },
+    Done,
+}
 
-    let width: u16 = maze.width.try_into().unwrap();
-    data.extend(width.to_be_bytes());
+impl GenStep {
+    pub fn is_done(self) -> bool {
+        self == Self::Done
+    }
+}
 
-    let default = 0;
+/// Stands in for SFML's `Drawable` as the bound of [`MazeGenerator`] and [`MazeSolver`] without the
+/// `gui` feature, met by everything
+#[cfg(not(feature = "gui"))]
+pub trait Drawable {}
 
-    for i in 0..maze.cells.len().div_ceil(2) {
-        let cell1 = *maze.cells.get(i * 2).unwrap_or(&default);
-        let cell2 = *maze.cells.get(i * 2 + 1).unwrap_or(&default);
+#[cfg(not(feature = "gui"))]
+impl<T: ?Sized> Drawable for T {}
 
-        data.push(cell1 << 4 | cell2);
-    }
+pub trait MazeGenerator: Drawable + GenerationProgress {
+    /// Once the maze is done, every later step returns [`GenStep::Done`]
+    fn step(&mut self, maze: &mut Maze) -> GenStep;
 
-    Ok(unsafe { String::from_utf8_unchecked(data) })
+    /// Starts over with a new random seed, to generate another maze
+    fn reset(&mut self, bounds: (usize, usize));
 }
 
-fn decode_maze(data: &[u8]) -> Result<Maze, String> {
-    let cell_data = &data[2..];
+/// Where a solver stands after a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveStep<'a> {
+    InProgress,
+    /// The path from the start to the end, both included
+    Found(&'a [(usize, usize)]),
+    /// Every cell the solver could get to was explored without reaching the end, e.g. as it is
+    /// walled off in a partly generated maze
+    Unsolvable,
+}
 
-    let width = (((data[0] as u16) << 8) + data[1] as u16) as usize;
-    let cell_count = if cell_data.last().unwrap() & 0x0f == 0 {
-        cell_data.len() * 2 - 1
-    } else {
-        cell_data.len() * 2
-    };
-    let height = cell_count / width;
+pub trait MazeSolver: Drawable {
+    /// Solves from the top left to the bottom right corner
+    fn new(bounds: (usize, usize)) -> Self
+    where
+        Self: Sized,
+    {
+        Self::between(bounds, (0, 0), (bounds.0 - 1, bounds.1 - 1))
+    }
 
-    let mut cells: Vec<u8> = vec![];
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self
+    where
+        Self: Sized;
 
-    for (i, cell_pair) in cell_data.iter().enumerate() {
-        cells.push((*cell_pair) >> 4);
-        if i * 2 + 1 < cell_count {
-            cells.push((*cell_pair) & 0x0f);
-        }
-    }
+    /// Once the path is found, or found not to exist, every later step returns the same
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_>;
 
-    Ok(Maze {
-        width,
-        height,
-        cells,
-    })
-}
+    /// Starts over between the same start and end, e.g. on a new maze
+    fn reset(&mut self, bounds: (usize, usize));
+
+    /// Number of cells explored so far
+    fn visited_count(&self) -> usize;
+
+    /// Size of the solver's working data, at its largest so far
+    fn memory(&self) -> solvers::MemoryStats;
 
-pub trait MazeGenerator: Drawable {
-    fn step(&mut self, maze: &mut Maze) -> bool;
+    /// The cell the last step expanded or moved to, if it worked on a single one
+    fn current(&self) -> Option<(usize, usize)>;
 }
 
-pub trait MazeSolver: Drawable {
-    fn new(bounds: (usize, usize)) -> Self;
-    fn step(&mut self, maze: &Maze) -> Option<&Vec<(usize, usize)>>;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparsifying_an_empty_maze_removes_nothing() {
+        assert_eq!(Maze::new(0, 0).sparsify(3), 0);
+        assert_eq!(Maze::new(0, 4).sparsify(3), 0);
+    }
+
+    #[test]
+    fn replays_solutions_up_to_the_first_bad_move() {
+        let mut maze = Maze::new(2, 2);
+        maze.carve(0, 0, Direction::RIGHT);
+        maze.carve(1, 0, Direction::DOWN);
+
+        assert_eq!(maze.replay_solution((0, 0), "R\nD\n"), Ok(((1, 1), 2)));
+        assert_eq!(
+            maze.replay_solution((0, 0), "RL D"),
+            Err((2, String::from("D from (0, 0) runs into a wall")))
+        );
+        assert_eq!(
+            maze.replay_solution((0, 0), "x"),
+            Err((0, String::from("`x` is not one of U, R, D, L")))
+        );
+    }
 }
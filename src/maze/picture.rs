@@ -0,0 +1,84 @@
+#[cfg(feature = "gui")]
+use sfml::graphics::Image;
+
+/// Per-cell darkness of an image, resampled to the maze bounds. Cells lighter than the threshold
+/// are masked out with a weight of `0.0`, the rest keep their darkness in `0.0..=1.0`.
+#[derive(Debug, Clone)]
+pub struct WeightMap {
+    width: usize,
+    weights: Vec<f32>,
+}
+
+impl WeightMap {
+    /// Loading images needs the `gui` feature, as SFML decodes them
+    #[cfg(feature = "gui")]
+    pub fn from_image(path: &str, bounds: (usize, usize), threshold: f32) -> Result<Self, String> {
+        let image =
+            Image::from_file(path).map_err(|err| format!("could not load {path}: {err}"))?;
+        let size = image.size();
+
+        if size.x == 0 || size.y == 0 {
+            return Err(format!("{path} is empty"));
+        }
+
+        let mut weights = Vec::with_capacity(bounds.0 * bounds.1);
+
+        for y in 0..bounds.1 {
+            for x in 0..bounds.0 {
+                // Average every pixel covered by this cell, at least one for images smaller than
+                // the maze
+                let x0 = (x * size.x as usize / bounds.0) as u32;
+                let y0 = (y * size.y as usize / bounds.1) as u32;
+                let x1 = (((x + 1) * size.x as usize / bounds.0) as u32).max(x0 + 1);
+                let y1 = (((y + 1) * size.y as usize / bounds.1) as u32).max(y0 + 1);
+
+                let mut total = 0.;
+                let mut count = 0;
+
+                for py in y0..y1.min(size.y) {
+                    for px in x0..x1.min(size.x) {
+                        if let Some(color) = image.pixel_at(px, py) {
+                            let luminance = 0.299 * color.r as f32
+                                + 0.587 * color.g as f32
+                                + 0.114 * color.b as f32;
+
+                            total += 1. - luminance / 255.;
+                            count += 1;
+                        }
+                    }
+                }
+
+                let darkness = if count == 0 { 0. } else { total / count as f32 };
+
+                weights.push(if darkness >= threshold { darkness } else { 0. });
+            }
+        }
+
+        Ok(Self {
+            width: bounds.0,
+            weights,
+        })
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> f32 {
+        self.weights[y * self.width + x]
+    }
+
+    /// Number of cells, the same as in the maze it was made for
+    pub fn cell_count(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// The darkest cell, used as a starting point so generation begins inside the picture
+    pub fn darkest(&self) -> (usize, usize) {
+        let i = self
+            .weights
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+
+        (i % self.width, i / self.width)
+    }
+}
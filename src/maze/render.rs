@@ -0,0 +1,503 @@
+#[cfg(feature = "gui")]
+use sfml::cpp::FBox;
+#[cfg(feature = "gui")]
+use sfml::graphics::{
+    Color, Drawable, PrimitiveType, RenderStates, RenderTarget, RenderTexture, Sprite, Vertex,
+    VertexBuffer, VertexBufferUsage,
+};
+
+#[cfg(feature = "gui")]
+use crate::consts::{get_cell_size, get_wall_width, wall_insets};
+use crate::maze::grid::VisitTimes;
+#[cfg(feature = "gui")]
+use crate::maze::Maze;
+#[cfg(feature = "gui")]
+use crate::theme::{theme, Theme};
+#[cfg(feature = "gui")]
+use crate::Direction;
+
+/// Center, right passage and down passage quads, two triangles each
+#[cfg(feature = "gui")]
+const VERTICES_PER_CELL: usize = 18;
+
+/// Draws a maze from a vertex buffer that is only rewritten for cells changed since the last
+/// [`MazeRenderer::update`], instead of rebuilding every cell each frame. Once the maze stops
+/// changing it is rendered into a texture, and drawing becomes a single sprite blit until the
+/// next change.
+///
+/// Walls are not drawn while the maze is changing, so the target should be cleared with the
+/// [`Theme::wall`] color first.
+#[cfg(feature = "gui")]
+pub struct MazeRenderer {
+    bounds: (usize, usize),
+    cell_size: usize,
+    wall_width: usize,
+    /// The theme when the renderer was made
+    theme: Theme,
+
+    vertices: Vec<Vertex>,
+    buffer: FBox<VertexBuffer>,
+
+    /// `None` if the texture could not be created, e.g. when the maze is larger than the maximum
+    /// texture size
+    texture: Option<FBox<RenderTexture>>,
+    texture_is_current: bool,
+    smooth: bool,
+
+    /// Per-cell colors replacing [`Theme::cell`], indexed like the maze
+    colors: Option<Vec<Option<Color>>>,
+}
+
+#[cfg(feature = "gui")]
+impl MazeRenderer {
+    pub fn new(maze: &mut Maze) -> Self {
+        let bounds = maze.get_bounds();
+        let vertex_count = bounds.0 * bounds.1 * VERTICES_PER_CELL;
+
+        let mut renderer = Self {
+            bounds,
+            cell_size: get_cell_size(),
+            wall_width: get_wall_width(),
+            theme: theme(),
+
+            vertices: vec![Vertex::default(); vertex_count],
+            buffer: VertexBuffer::new(
+                PrimitiveType::TRIANGLES,
+                vertex_count,
+                VertexBufferUsage::DYNAMIC,
+            )
+            .unwrap(),
+
+            texture: RenderTexture::new(
+                (bounds.0 * get_cell_size()) as u32,
+                (bounds.1 * get_cell_size()) as u32,
+            )
+            .ok(),
+            texture_is_current: false,
+            smooth: false,
+
+            colors: None,
+        };
+
+        maze.take_dirty();
+        renderer.rebuild(maze);
+
+        renderer
+    }
+
+    /// Re-uploads the cells changed since the last update. Returns `true` if anything changed.
+    pub fn update(&mut self, maze: &mut Maze) -> bool {
+        let dirty = maze.take_dirty();
+
+        if dirty.is_empty() {
+            if !self.texture_is_current {
+                self.render_texture();
+            }
+
+            return false;
+        }
+
+        self.texture_is_current = false;
+
+        // Past this point a single upload of the whole buffer is cheaper
+        if dirty.len() * 4 > self.bounds.0 * self.bounds.1 {
+            self.rebuild(maze);
+            return true;
+        }
+
+        for (x, y) in dirty {
+            // The passage quads between this cell and its left and upper neighbors belong to
+            // those neighbors
+            let mut cells = vec![(x, y)];
+            if x > 0 {
+                cells.push((x - 1, y));
+            }
+            if y > 0 {
+                cells.push((x, y - 1));
+            }
+
+            for pos in cells {
+                let start = self.write_cell(maze, pos);
+
+                self.buffer
+                    .update(
+                        &self.vertices[start..start + VERTICES_PER_CELL],
+                        start as u32,
+                    )
+                    .unwrap();
+            }
+        }
+
+        true
+    }
+
+    /// Tints every cell with its own color, e.g. from [`crate::analysis::distance_colors`], or
+    /// goes back to plain cells with `None`. Cells without a color stay plain.
+    pub fn set_colors(&mut self, maze: &Maze, colors: Option<Vec<Option<Color>>>) {
+        self.colors = colors;
+        self.texture_is_current = false;
+
+        self.rebuild(maze);
+    }
+
+    /// Blends neighboring pixels of the rendered maze when it is drawn smaller than its size, so
+    /// walls a pixel wide fade instead of flickering in and out as the view moves. Magnified
+    /// mazes are sharper without it.
+    pub fn set_smooth(&mut self, smooth: bool) {
+        if smooth == self.smooth {
+            return;
+        }
+        self.smooth = smooth;
+
+        if let Some(texture) = &mut self.texture {
+            texture.set_smooth(smooth);
+        }
+    }
+
+    fn render_texture(&mut self) {
+        let Some(texture) = &mut self.texture else {
+            return;
+        };
+
+        texture.clear(self.theme.wall);
+        texture.draw_vertex_buffer(&self.buffer, &RenderStates::DEFAULT);
+        texture.display();
+
+        self.texture_is_current = true;
+    }
+
+    fn rebuild(&mut self, maze: &Maze) {
+        for y in 0..self.bounds.1 {
+            for x in 0..self.bounds.0 {
+                self.write_cell(maze, (x, y));
+            }
+        }
+
+        self.buffer.update(&self.vertices, 0).unwrap();
+    }
+
+    /// Returns the index of the cell's first vertex
+    fn write_cell(&mut self, maze: &Maze, (x, y): (usize, usize)) -> usize {
+        let cell_size = self.cell_size as f32;
+        let wall = self.wall_width as f32;
+        let (before, after) = wall_insets(self.wall_width);
+        let (before, after) = (before as f32, after as f32);
+        let left = x as f32 * cell_size;
+        let top = y as f32 * cell_size;
+
+        let cell = maze.get(x, y);
+
+        let open_right = cell.contains(Direction::RIGHT)
+            || maze
+                .try_get(x + 1, y)
+                .is_some_and(|v| v.contains(Direction::LEFT));
+        let open_down = cell.contains(Direction::DOWN)
+            || maze
+                .try_get(x, y + 1)
+                .is_some_and(|v| v.contains(Direction::UP));
+
+        let center = (
+            left + before,
+            top + before,
+            cell_size - wall,
+            cell_size - wall,
+        );
+        let right = (
+            left + cell_size - after,
+            top + before,
+            wall,
+            cell_size - wall,
+        );
+        let down = (
+            left + before,
+            top + cell_size - after,
+            cell_size - wall,
+            wall,
+        );
+
+        let i = y * self.bounds.0 + x;
+        let color = self
+            .colors
+            .as_ref()
+            .and_then(|colors| colors[i])
+            .unwrap_or(self.theme.cell);
+
+        let start = i * VERTICES_PER_CELL;
+        let quads = &mut self.vertices[start..start + VERTICES_PER_CELL];
+
+        write_quad(
+            &mut quads[0..6],
+            center,
+            if cell.is_empty() {
+                self.theme.empty_cell
+            } else {
+                color
+            },
+        );
+        write_quad(
+            &mut quads[6..12],
+            if open_right { right } else { (0., 0., 0., 0.) },
+            color,
+        );
+        write_quad(
+            &mut quads[12..18],
+            if open_down { down } else { (0., 0., 0., 0.) },
+            color,
+        );
+
+        start
+    }
+}
+
+#[cfg(feature = "gui")]
+fn write_quad(
+    vertices: &mut [Vertex],
+    (left, top, width, height): (f32, f32, f32, f32),
+    color: Color,
+) {
+    let corners = [
+        (left, top),
+        (left + width, top),
+        (left + width, top + height),
+        (left, top),
+        (left + width, top + height),
+        (left, top + height),
+    ];
+
+    for (vertex, corner) in vertices.iter_mut().zip(corners) {
+        *vertex = Vertex::with_pos_color(corner.into(), color);
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for MazeRenderer {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        match &self.texture {
+            Some(texture) if self.texture_is_current => {
+                target.draw_sprite(&Sprite::with_texture(texture.texture()), rs)
+            }
+            _ => target.draw_vertex_buffer(&self.buffer, rs),
+        }
+    }
+}
+
+/// What a solver marks cells as, drawn in the matching [`Theme`] color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Visited,
+    Frontier,
+}
+
+#[cfg(feature = "gui")]
+impl Mark {
+    fn color(self) -> Color {
+        let theme = theme();
+
+        match self {
+            Mark::Visited => theme.visited,
+            Mark::Frontier => theme.frontier,
+        }
+    }
+}
+
+/// Visited marks that fade out over `length` solver steps after their cell is first visited,
+/// drawn instead of a solver's visited cells so the search leaves a glowing trail behind it
+/// rather than a filled region
+#[derive(Debug, Clone)]
+pub struct Trail {
+    length: u32,
+    times: VisitTimes,
+}
+
+impl Trail {
+    pub fn new(bounds: (usize, usize), length: u32) -> Self {
+        Self {
+            length: length.max(1),
+            times: VisitTimes::new(bounds),
+        }
+    }
+
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Ages every mark by a step, to be called at the start of every solver step
+    pub fn tick(&mut self) {
+        self.times.tick();
+    }
+
+    pub fn insert(&mut self, pos: (usize, usize)) {
+        self.times.insert(pos);
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for Trail {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn RenderTarget,
+        rs: &RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let color = Mark::Visited.color();
+
+        let cells = self
+            .times
+            .iter()
+            .filter(|&(_, age)| age < self.length)
+            .map(|(pos, age)| {
+                let left = 1. - age as f32 / self.length as f32;
+                let alpha = (color.a as f32 * left).round() as u8;
+
+                (pos, Color { a: alpha, ..color })
+            });
+
+        draw_colored_marks(target, rs, cells, Mark::Visited);
+    }
+}
+
+/// Marks each cell with a square in its middle. With [`Theme::patterns`], visited cells are
+/// hatched and frontier cells get a dot instead. Faded marks are see-through, e.g. for cells left
+/// off the final path.
+#[cfg(feature = "gui")]
+pub fn draw_marks<'texture, 'shader, 'shader_texture>(
+    target: &mut dyn RenderTarget,
+    rs: &RenderStates<'texture, 'shader, 'shader_texture>,
+    cells: impl IntoIterator<Item = (usize, usize)>,
+    mark: Mark,
+    faded: bool,
+) {
+    let mut color = mark.color();
+    if faded {
+        color.a = 64;
+    }
+
+    draw_colored_marks(target, rs, cells.into_iter().map(|pos| (pos, color)), mark);
+}
+
+/// Like [`draw_marks`], with a color for every cell
+#[cfg(feature = "gui")]
+fn draw_colored_marks<'texture, 'shader, 'shader_texture>(
+    target: &mut dyn RenderTarget,
+    rs: &RenderStates<'texture, 'shader, 'shader_texture>,
+    cells: impl IntoIterator<Item = ((usize, usize), Color)>,
+    mark: Mark,
+) {
+    let cell_size = get_cell_size() as f32;
+    let half = cell_size / 2.;
+
+    let mut vertices = vec![];
+
+    let primitive = match (theme().patterns, mark) {
+        (false, _) => {
+            for ((x, y), color) in cells {
+                let square = (
+                    x as f32 * cell_size + half / 2.,
+                    y as f32 * cell_size + half / 2.,
+                    half,
+                    half,
+                );
+
+                let start = vertices.len();
+                vertices.resize(start + 6, Vertex::default());
+                write_quad(&mut vertices[start..], square, color);
+            }
+
+            PrimitiveType::TRIANGLES
+        }
+        // Three parallel diagonals across the middle square
+        (true, Mark::Visited) => {
+            for ((x, y), color) in cells {
+                let (left, top) = (
+                    x as f32 * cell_size + half / 2.,
+                    y as f32 * cell_size + half / 2.,
+                );
+
+                for (from, to) in [
+                    ((left, top + half / 2.), (left + half / 2., top)),
+                    ((left, top + half), (left + half, top)),
+                    (
+                        (left + half / 2., top + half),
+                        (left + half, top + half / 2.),
+                    ),
+                ] {
+                    vertices.push(Vertex::with_pos_color(from.into(), color));
+                    vertices.push(Vertex::with_pos_color(to.into(), color));
+                }
+            }
+
+            PrimitiveType::LINES
+        }
+        // A small diamond in the center
+        (true, Mark::Frontier) => {
+            let radius = cell_size / 6.;
+
+            for ((x, y), color) in cells {
+                let (cx, cy) = ((x as f32 + 0.5) * cell_size, (y as f32 + 0.5) * cell_size);
+                let corners = [
+                    (cx, cy - radius),
+                    (cx + radius, cy),
+                    (cx, cy + radius),
+                    (cx - radius, cy),
+                ];
+
+                for i in [0, 1, 2, 0, 2, 3] {
+                    vertices.push(Vertex::with_pos_color(corners[i].into(), color));
+                }
+            }
+
+            PrimitiveType::TRIANGLES
+        }
+    };
+
+    draw_vertices(target, rs, primitive, &vertices);
+}
+
+/// A line through the centers of the cells, by default in the [`Theme::path`] color
+#[cfg(feature = "gui")]
+pub fn draw_path<'texture, 'shader, 'shader_texture>(
+    target: &mut dyn RenderTarget,
+    rs: &RenderStates<'texture, 'shader, 'shader_texture>,
+    path: &[(usize, usize)],
+    color: Option<Color>,
+) {
+    let cell_size = get_cell_size();
+    let color = color.unwrap_or_else(|| theme().path);
+
+    let points: Vec<Vertex> = path
+        .iter()
+        .map(|(x, y)| {
+            Vertex::with_pos_color(
+                (
+                    ((x * 2 + 1) * cell_size / 2) as f32,
+                    ((y * 2 + 1) * cell_size / 2) as f32,
+                )
+                    .into(),
+                color,
+            )
+        })
+        .collect();
+
+    draw_vertices(target, rs, PrimitiveType::LINE_STRIP, &points);
+}
+
+#[cfg(feature = "gui")]
+fn draw_vertices<'texture, 'shader, 'shader_texture>(
+    target: &mut dyn RenderTarget,
+    rs: &RenderStates<'texture, 'shader, 'shader_texture>,
+    primitive: PrimitiveType,
+    vertices: &[Vertex],
+) {
+    if vertices.is_empty() {
+        return;
+    }
+
+    let mut buffer =
+        VertexBuffer::new(primitive, vertices.len(), VertexBufferUsage::DYNAMIC).unwrap();
+    buffer.update(vertices, 0).unwrap();
+
+    target.draw_vertex_buffer(&buffer, rs);
+}
@@ -0,0 +1,39 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+/// Random numbers drawn from a seed. This is pinned rather than left to [`rand::rngs::StdRng`],
+/// which may change algorithm in any release of `rand`.
+pub type SeedRng = ChaCha12Rng;
+
+/// What random numbers drawn from a seed are for. Each purpose gets its own ChaCha12 stream of the
+/// same key, so drawing more or fewer numbers for one never shifts the others, and a maze is made
+/// again cell for cell from its seed on any platform.
+///
+/// The key is the seed expanded by [`SeedableRng::seed_from_u64`], and the stream is the number of
+/// the purpose below. Numbers are picked from the streams with `rand` 0.9's portable
+/// distributions, which only change in a new minor version. Changing any of this changes the
+/// maze every seed makes, so the numbers are never reused or reordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u64)]
+pub enum Stream {
+    /// Carving the maze, including picking where generators start. Stream 0 is what
+    /// [`rand::rngs::StdRng`] drew from before streams were split, so seeds from then make the
+    /// same mazes, but for the joins of symmetric ones.
+    Generation = 0,
+    /// Passages carved on top of what the generator made, such as those joining the halves of a
+    /// [`super::symmetry::Symmetric`] maze
+    Braiding = 1,
+    /// Choices solvers make between equally good cells, such as A*'s random tie break
+    TieBreak = 2,
+    /// Changes to a maze while it is solved, such as where [`super::generators::OriginShift`]
+    /// moves its origin
+    Mutation = 3,
+}
+
+impl Stream {
+    pub fn rng(self, seed: u64) -> SeedRng {
+        let mut rng = SeedRng::seed_from_u64(seed);
+        rng.set_stream(self as u64);
+        rng
+    }
+}
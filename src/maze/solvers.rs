@@ -1,29 +1,226 @@
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
-
-use sfml::graphics::{
-    Color, Drawable, PrimitiveType, RectangleShape, Shape, Transformable, Vertex, VertexBuffer,
-    VertexBufferUsage,
-};
-
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use rand::{seq::IteratorRandom, Rng};
+use rayon::prelude::*;
+#[cfg(feature = "gui")]
+use sfml::graphics::{Drawable, PrimitiveType, Vertex, VertexBuffer, VertexBufferUsage};
+
+#[cfg(feature = "gui")]
 use crate::consts::*;
-use crate::maze::{Maze, MazeSolver};
+use crate::maze::agents::Agent;
+use crate::maze::generators::random_seed;
+use crate::maze::grid::{ParentGrid, VisitedGrid};
+use crate::maze::render::Trail;
+#[cfg(feature = "gui")]
+use crate::maze::render::{draw_marks, draw_path, Mark};
+use crate::maze::seed::{SeedRng, Stream};
+use crate::maze::tags::{Tag, LOCK_COLORS};
+use crate::maze::{Maze, MazeSolver, SolveStep};
+#[cfg(feature = "gui")]
+use crate::theme::theme;
+use crate::{Direction, DirectionSet};
+
+/// Peak size of a solver's working data, so runs can be compared by memory as well as time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Cells marked visited
+    pub visited: usize,
+    /// Entries waiting in the queue, stack, open set or frontier after the last step
+    pub open: usize,
+    /// Most entries waiting at once in the queue, stack, open set or frontier
+    pub open_peak: usize,
+    /// Entries in the closed set, which are cells and key sets for [`ConstraintSolver`]
+    pub closed: usize,
+    /// Estimated heap bytes of the solver's grids and collections at their peak sizes
+    pub bytes: usize,
+}
+
+impl MemoryStats {
+    /// Records the current size of the open collection
+    fn track_open(&mut self, len: usize) {
+        self.open = len;
+        self.open_peak = self.open_peak.max(len);
+    }
+}
 
 pub enum Algorithm {
     DepthFirstSearch(DFSSolver),
     BreadthFirstSearch(BFSSolver),
+    ParallelBFS(ParallelBFSSolver),
     AStar(AStarSolver),
+    MultiGoal(MultiGoalSolver),
+    Replanning(ReplanningSolver),
+    FlowField(FlowFieldSolver),
+    Agent(AgentSolver),
+    Constraint(ConstraintSolver),
 }
 
 impl Algorithm {
-    pub fn step(&mut self, maze: &Maze) -> Option<&Vec<(usize, usize)>> {
+    pub fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
         match self {
             Self::BreadthFirstSearch(v) => v.step(maze),
+            Self::ParallelBFS(v) => v.step(maze),
             Self::DepthFirstSearch(v) => v.step(maze),
             Self::AStar(v) => v.step(maze),
+            Self::MultiGoal(v) => v.step(maze),
+            Self::Replanning(v) => v.step(maze),
+            Self::FlowField(v) => v.step(maze),
+            Self::Agent(v) => v.step(maze),
+            Self::Constraint(v) => v.step(maze),
+        }
+    }
+}
+
+impl Algorithm {
+    /// Sets how A* breaks ties, including in every leg through waypoints. Other solvers have no
+    /// ties to break and stay as they are.
+    pub fn with_tie_break(self, tie_break: TieBreak) -> Self {
+        match self {
+            Self::AStar(v) => Self::AStar(v.with_tie_break(tie_break)),
+            Self::MultiGoal(mut v) => {
+                v.legs = v
+                    .legs
+                    .into_iter()
+                    .map(|leg| leg.with_tie_break(tie_break))
+                    .collect();
+                Self::MultiGoal(v)
+            }
+            other => other,
+        }
+    }
+
+    pub fn with_heuristic_weight(mut self, weight: f32) -> Self {
+        self.set_heuristic_weight(weight);
+        self
+    }
+
+    /// Sets the heuristic weight of A*, see [`AStarSolver::set_heuristic_weight`], including in
+    /// every leg through waypoints. Other solvers have no heuristic and stay as they are.
+    pub fn set_heuristic_weight(&mut self, weight: f32) {
+        match self {
+            Self::AStar(v) => v.set_heuristic_weight(weight),
+            Self::MultiGoal(v) => {
+                for leg in &mut v.legs {
+                    leg.set_heuristic_weight(weight);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Draws the visited cells of breadth-first search, A* and constraint search as a [`Trail`]
+    /// fading over `length` steps while they solve, including in every leg through waypoints.
+    /// `None` draws them all again. Other solvers draw no visited cells.
+    pub fn set_trail(&mut self, bounds: (usize, usize), length: Option<u32>) {
+        match self {
+            Self::BreadthFirstSearch(v) => v.set_trail(bounds, length),
+            Self::ParallelBFS(v) => v.set_trail(bounds, length),
+            Self::AStar(v) => v.set_trail(bounds, length),
+            Self::MultiGoal(v) => {
+                for leg in &mut v.legs {
+                    leg.set_trail(bounds, length);
+                }
+            }
+            Self::Constraint(v) => v.set_trail(bounds, length),
+            _ => {}
+        }
+    }
+
+    /// Seeds the random choices of randomized DFS and A*'s random tie break from the
+    /// [`Stream::TieBreak`] stream of `seed`, including in every leg through waypoints, so the
+    /// same maze is solved the same way every time. Other solvers make no random choices.
+    pub fn set_seed(&mut self, seed: u64) {
+        match self {
+            Self::DepthFirstSearch(v) => v.set_seed(seed),
+            Self::AStar(v) => v.set_seed(seed),
+            Self::MultiGoal(v) => {
+                for leg in &mut v.legs {
+                    leg.set_seed(seed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Starts over between the same start and end, e.g. on a new maze
+    pub fn reset(&mut self, bounds: (usize, usize)) {
+        match self {
+            Self::BreadthFirstSearch(v) => v.reset(bounds),
+            Self::ParallelBFS(v) => v.reset(bounds),
+            Self::DepthFirstSearch(v) => v.reset(bounds),
+            Self::AStar(v) => v.reset(bounds),
+            Self::MultiGoal(v) => v.reset(bounds),
+            Self::Replanning(v) => v.reset(bounds),
+            Self::FlowField(v) => v.reset(bounds),
+            Self::Agent(v) => v.reset(bounds),
+            Self::Constraint(v) => v.reset(bounds),
+        }
+    }
+
+    /// Cells explored so far. Solvers that only walk, like the replanning one, give the cells
+    /// walked through.
+    pub fn visited(&self) -> Vec<(usize, usize)> {
+        match self {
+            Self::BreadthFirstSearch(v) => v.visited.iter().collect(),
+            Self::ParallelBFS(v) => v.visited().collect(),
+            Self::DepthFirstSearch(v) => v.visited.iter().collect(),
+            Self::AStar(v) => v.closed.iter().collect(),
+            Self::MultiGoal(v) => v.legs.iter().flat_map(Algorithm::visited).collect(),
+            Self::Replanning(v) => v.walked.clone(),
+            Self::FlowField(v) => v.visited.iter().collect(),
+            Self::Agent(v) => v.visited.iter().collect(),
+            Self::Constraint(v) => v.visited.iter().collect(),
+        }
+    }
+
+    pub fn memory(&self) -> MemoryStats {
+        match self {
+            Self::BreadthFirstSearch(v) => v.memory(),
+            Self::ParallelBFS(v) => v.memory(),
+            Self::DepthFirstSearch(v) => v.memory(),
+            Self::AStar(v) => v.memory(),
+            Self::MultiGoal(v) => v.memory(),
+            Self::Replanning(v) => v.memory(),
+            Self::FlowField(v) => v.memory(),
+            Self::Agent(v) => v.memory(),
+            Self::Constraint(v) => v.memory(),
+        }
+    }
+
+    pub fn current(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::BreadthFirstSearch(v) => v.current(),
+            Self::ParallelBFS(v) => v.current(),
+            Self::DepthFirstSearch(v) => v.current(),
+            Self::AStar(v) => v.current(),
+            Self::MultiGoal(v) => v.current(),
+            Self::Replanning(v) => v.current(),
+            Self::FlowField(v) => v.current(),
+            Self::Agent(v) => v.current(),
+            Self::Constraint(v) => v.current(),
+        }
+    }
+
+    pub fn visited_count(&self) -> usize {
+        match self {
+            Self::BreadthFirstSearch(v) => v.visited_count(),
+            Self::ParallelBFS(v) => v.visited_count(),
+            Self::DepthFirstSearch(v) => v.visited_count(),
+            Self::AStar(v) => v.visited_count(),
+            Self::MultiGoal(v) => v.visited_count(),
+            Self::Replanning(v) => v.visited_count(),
+            Self::FlowField(v) => v.visited_count(),
+            Self::Agent(v) => v.visited_count(),
+            Self::Constraint(v) => v.visited_count(),
         }
     }
 }
 
+#[cfg(feature = "gui")]
 impl Drawable for Algorithm {
     fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
         &'a self,
@@ -32,46 +229,212 @@ impl Drawable for Algorithm {
     ) {
         match self {
             Self::BreadthFirstSearch(v) => v.draw(target, rs),
+            Self::ParallelBFS(v) => v.draw(target, rs),
             Self::DepthFirstSearch(v) => v.draw(target, rs),
             Self::AStar(v) => v.draw(target, rs),
+            Self::MultiGoal(v) => v.draw(target, rs),
+            Self::Replanning(v) => v.draw(target, rs),
+            Self::FlowField(v) => v.draw(target, rs),
+            Self::Agent(v) => v.draw(target, rs),
+            Self::Constraint(v) => v.draw(target, rs),
+        };
+    }
+}
+
+/// Chains one solver per leg so the final path visits every waypoint in order
+pub struct MultiGoalSolver {
+    legs: Vec<Algorithm>,
+    current_leg: usize,
+
+    path: Vec<(usize, usize)>,
+    unsolvable: bool,
+}
+
+impl MultiGoalSolver {
+    /// Each leg should start where the previous one ended
+    pub fn new(legs: Vec<Algorithm>) -> Self {
+        Self {
+            legs,
+            current_leg: 0,
+
+            path: Vec::new(),
+            unsolvable: false,
+        }
+    }
+
+    pub fn visited_count(&self) -> usize {
+        self.legs.iter().map(Algorithm::visited_count).sum()
+    }
+
+    /// Every leg is kept, so their sizes add up, except for the open peak of one leg at a time
+    pub fn memory(&self) -> MemoryStats {
+        let total =
+            self.legs
+                .iter()
+                .map(Algorithm::memory)
+                .fold(MemoryStats::default(), |total, leg| MemoryStats {
+                    visited: total.visited + leg.visited,
+                    open: 0,
+                    open_peak: total.open_peak.max(leg.open_peak),
+                    closed: total.closed + leg.closed,
+                    bytes: total.bytes + leg.bytes,
+                });
+
+        MemoryStats {
+            open: self.leg().map_or(0, |leg| leg.memory().open),
+            ..total
+        }
+    }
+
+    /// The leg being solved, or the last one once all are
+    fn leg(&self) -> Option<&Algorithm> {
+        self.legs.get(self.current_leg).or_else(|| self.legs.last())
+    }
+
+    pub fn current(&self) -> Option<(usize, usize)> {
+        self.leg()?.current()
+    }
+
+    pub fn reset(&mut self, bounds: (usize, usize)) {
+        for leg in &mut self.legs {
+            leg.reset(bounds);
+        }
+
+        self.current_leg = 0;
+        self.path.clear();
+        self.unsolvable = false;
+    }
+
+    pub fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
+        let Some(leg) = self.legs.get_mut(self.current_leg) else {
+            return self.result();
         };
+
+        match leg.step(maze) {
+            SolveStep::InProgress => {}
+            SolveStep::Found(leg_path) => {
+                // The first cell of each leg is the last cell of the previous one
+                let skip = if self.path.is_empty() { 0 } else { 1 };
+                self.path.extend(leg_path.iter().skip(skip));
+
+                self.current_leg += 1;
+            }
+            // One unreachable waypoint leaves no path through all of them
+            SolveStep::Unsolvable => {
+                self.unsolvable = true;
+                self.current_leg = self.legs.len();
+            }
+        }
+
+        if self.current_leg == self.legs.len() {
+            self.result()
+        } else {
+            SolveStep::InProgress
+        }
+    }
+
+    /// What every step returns once all legs are done
+    fn result(&self) -> SolveStep<'_> {
+        match self.unsolvable {
+            true => SolveStep::Unsolvable,
+            false => SolveStep::Found(&self.path),
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for MultiGoalSolver {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        if let Some(leg) = self.legs.get(self.current_leg) {
+            leg.draw(target, rs);
+        }
+
+        draw_path(target, rs, &self.path, None);
     }
 }
 
 pub struct DFSSolver {
-    visited: HashSet<(usize, usize)>,
+    visited: VisitedGrid,
     path: Vec<(usize, usize)>,
+    memory: MemoryStats,
 
+    start: (usize, usize),
     end: (usize, usize),
+    /// Take unvisited passages in random order instead of always the first
+    randomized: bool,
+    seed: u64,
+    rng: SeedRng,
 }
 
-impl MazeSolver for DFSSolver {
-    fn new(bounds: (usize, usize)) -> Self {
+impl DFSSolver {
+    pub fn randomized(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
         Self {
-            visited: HashSet::new(),
-            path: vec![(0, 0)],
+            randomized: true,
+            ..Self::between(bounds, start, end)
+        }
+    }
 
-            end: (bounds.0 - 1, bounds.1 - 1),
+    /// Restarts the random order passages are taken in from `seed`, which is kept on reset
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Stream::TieBreak.rng(seed);
+    }
+}
+
+impl MazeSolver for DFSSolver {
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
+        let seed = random_seed();
+
+        Self {
+            visited: VisitedGrid::new(bounds),
+            path: vec![start],
+            memory: MemoryStats::default(),
+
+            start,
+            end,
+            randomized: false,
+            seed,
+            rng: Stream::TieBreak.rng(seed),
         }
     }
 
-    fn step(&mut self, maze: &Maze) -> Option<&Vec<(usize, usize)>> {
-        let pos = *self.path.last().unwrap();
+    fn reset(&mut self, bounds: (usize, usize)) {
+        let seed = self.seed;
+
+        *self = Self {
+            randomized: self.randomized,
+            ..Self::between(bounds, self.start, self.end)
+        };
+        self.set_seed(seed);
+    }
+
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
+        // Backtracking past the start empties the path
+        let Some(&pos) = self.path.last() else {
+            return SolveStep::Unsolvable;
+        };
 
         if pos == self.end {
-            return Some(&self.path);
+            return SolveStep::Found(&self.path);
         }
 
         let neighbors = maze.get_travellable_neighbors(pos);
-        let next = (0..neighbors.1)
-            .filter_map(|i| {
-                if self.visited.contains(&neighbors.0[i]) {
-                    None
-                } else {
-                    Some(neighbors.0[i])
-                }
-            })
-            .nth(0);
+        let mut unvisited = (0..neighbors.1).filter_map(|i| {
+            if self.visited.contains(neighbors.0[i]) {
+                None
+            } else {
+                Some(neighbors.0[i])
+            }
+        });
+        let next = if self.randomized {
+            unvisited.choose(&mut self.rng)
+        } else {
+            unvisited.nth(0)
+        };
 
         self.visited.insert(pos);
         match next {
@@ -80,72 +443,110 @@ impl MazeSolver for DFSSolver {
                 self.path.pop();
             }
         }
+        self.memory.track_open(self.path.len());
 
-        None
+        SolveStep::InProgress
+    }
+
+    fn visited_count(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// The top of the stack, which the last step pushed or backtracked to
+    fn current(&self) -> Option<(usize, usize)> {
+        self.path.last().copied()
+    }
+
+    /// The path is the stack
+    fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.visited.len(),
+            closed: self.visited.len(),
+            bytes: self.visited.bytes() + self.memory.open_peak * size_of::<(usize, usize)>(),
+            ..self.memory
+        }
     }
 }
 
 pub struct BFSSolver {
     queue: VecDeque<(usize, usize)>,
-    visited: HashMap<(usize, usize), Option<(usize, usize)>>,
+    visited: VisitedGrid,
+    parents: ParentGrid,
+    memory: MemoryStats,
+    current: Option<(usize, usize)>,
 
     path: Vec<(usize, usize)>,
     finished: bool,
+    trail: Option<Trail>,
 
+    start: (usize, usize),
     end: (usize, usize),
 }
 
+impl BFSSolver {
+    pub fn set_trail(&mut self, bounds: (usize, usize), length: Option<u32>) {
+        self.trail = length.map(|length| Trail::new(bounds, length));
+    }
+}
+
 impl MazeSolver for BFSSolver {
-    fn new(bounds: (usize, usize)) -> Self {
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
         let mut queue = VecDeque::new();
-        let mut visited: HashMap<(usize, usize), Option<(usize, usize)>> = HashMap::new();
+        let mut visited = VisitedGrid::new(bounds);
 
-        queue.push_back((0, 0));
-        visited.insert((0, 0), None);
+        queue.push_back(start);
+        visited.insert(start);
 
         Self {
             visited,
+            parents: ParentGrid::new(bounds),
             queue,
+            memory: MemoryStats::default(),
+            current: None,
 
             path: vec![],
             finished: false,
+            trail: None,
 
-            end: (bounds.0 - 1, bounds.1 - 1),
+            start,
+            end,
         }
     }
 
-    fn step(&mut self, maze: &Maze) -> Option<&Vec<(usize, usize)>> {
+    fn reset(&mut self, bounds: (usize, usize)) {
+        let trail = self.trail.as_ref().map(Trail::length);
+
+        *self = Self::between(bounds, self.start, self.end);
+        self.set_trail(bounds, trail);
+    }
+
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
         if self.finished {
-            return Some(&self.path);
+            return SolveStep::Found(&self.path);
         }
 
-        let pos = self.queue.pop_front().unwrap();
+        let Some(pos) = self.queue.pop_front() else {
+            return SolveStep::Unsolvable;
+        };
+        self.current = Some(pos);
+
+        if let Some(trail) = &mut self.trail {
+            trail.tick();
+            // Cells are stamped as they are queued, which the start never was
+            trail.insert(pos);
+        }
 
         if pos == self.end {
             self.finished = true;
+            self.path = self.parents.path_to(pos);
 
-            let mut pos = pos;
-
-            loop {
-                self.path.push(pos);
-
-                let prev = self.visited.get(&pos).unwrap();
-
-                match prev {
-                    None => break,
-                    Some(v) => pos = *v,
-                }
-            }
-
-            self.path.reverse();
-
-            return Some(&self.path);
+            return SolveStep::Found(&self.path);
         }
 
         let neighbors = maze.get_travellable_neighbors(pos);
         let next: Vec<_> = (0..neighbors.1)
             .filter_map(|i| {
-                if self.visited.contains_key(&neighbors.0[i]) {
+                if self.visited.contains(neighbors.0[i]) {
                     None
                 } else {
                     Some(neighbors.0[i])
@@ -154,10 +555,192 @@ impl MazeSolver for BFSSolver {
             .collect();
 
         for next_pos in next {
-            self.visited.insert(next_pos, Some(pos));
+            self.visited.insert(next_pos);
+            self.parents.set(next_pos, pos);
             self.queue.push_back(next_pos);
+
+            if let Some(trail) = &mut self.trail {
+                trail.insert(next_pos);
+            }
+        }
+        self.memory.track_open(self.queue.len());
+
+        SolveStep::InProgress
+    }
+
+    fn visited_count(&self) -> usize {
+        self.visited.len()
+    }
+
+    fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.visited.len(),
+            closed: self.visited.len(),
+            bytes: self.visited.bytes()
+                + self.parents.bytes()
+                + self.memory.open_peak * size_of::<(usize, usize)>(),
+            ..self.memory
+        }
+    }
+
+    fn current(&self) -> Option<(usize, usize)> {
+        self.current
+    }
+}
+
+/// Marks the start in [`ParallelBFSSolver::parents`], since it has no parent
+const ROOT: u8 = 0b1_0000;
+
+/// Breadth-first search expanding the whole frontier on every step, spread over all cores. Cells
+/// are claimed in a flat grid of atomic parent directions, so each is entered from exactly one
+/// parent without locking. Finds the same shortest path as [`BFSSolver`] in one step per path
+/// cell rather than one per visited cell, which pays off on mazes of millions of cells.
+pub struct ParallelBFSSolver {
+    width: usize,
+    /// Direction from every cell towards its parent, `0` while unvisited
+    parents: Vec<AtomicU8>,
+    frontier: Vec<(usize, usize)>,
+    visited_count: usize,
+    memory: MemoryStats,
+
+    path: Vec<(usize, usize)>,
+    finished: bool,
+    trail: Option<Trail>,
+
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+impl ParallelBFSSolver {
+    pub fn set_trail(&mut self, bounds: (usize, usize), length: Option<u32>) {
+        self.trail = length.map(|length| Trail::new(bounds, length));
+    }
+
+    fn parent_bits(&self, (x, y): (usize, usize)) -> u8 {
+        self.parents[y * self.width + x].load(Ordering::Relaxed)
+    }
+
+    /// Cells reached so far, in row-major order
+    pub fn visited(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.parents
+            .iter()
+            .enumerate()
+            .filter(|(_, bits)| bits.load(Ordering::Relaxed) != 0)
+            .map(|(i, _)| (i % self.width, i / self.width))
+    }
+
+    fn path_to(&self, end: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut path = vec![end];
+        let mut pos = end;
+
+        while let Some(parent) = DirectionSet::from_bits(self.parent_bits(pos))
+            .iter()
+            .next()
+            .and_then(|direction| direction.travel(pos.0, pos.1))
+        {
+            path.push(parent);
+            pos = parent;
+        }
+
+        path.reverse();
+
+        path
+    }
+}
+
+impl MazeSolver for ParallelBFSSolver {
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
+        let parents: Vec<AtomicU8> = (0..bounds.0 * bounds.1).map(|_| AtomicU8::new(0)).collect();
+        parents[start.1 * bounds.0 + start.0].store(ROOT, Ordering::Relaxed);
+
+        Self {
+            width: bounds.0,
+            parents,
+            frontier: vec![start],
+            visited_count: 1,
+            memory: MemoryStats::default(),
+
+            path: vec![],
+            finished: false,
+            trail: None,
+
+            start,
+            end,
+        }
+    }
+
+    fn reset(&mut self, bounds: (usize, usize)) {
+        let trail = self.trail.as_ref().map(Trail::length);
+
+        *self = Self::between(bounds, self.start, self.end);
+        self.set_trail(bounds, trail);
+    }
+
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
+        if self.finished {
+            return SolveStep::Found(&self.path);
+        }
+
+        if self.parent_bits(self.end) != 0 {
+            self.finished = true;
+            self.path = self.path_to(self.end);
+
+            return SolveStep::Found(&self.path);
+        }
+
+        if self.frontier.is_empty() {
+            return SolveStep::Unsolvable;
+        }
+
+        let (width, parents) = (self.width, &self.parents);
+
+        let next: Vec<(usize, usize)> = self
+            .frontier
+            .par_iter()
+            .flat_map_iter(|&pos| {
+                let (neighbors, count) = maze.get_travellable_neighbors(pos);
+
+                neighbors.into_iter().take(count).filter(move |&next| {
+                    let towards_parent = Direction::between(next, pos).unwrap() as u8;
+
+                    parents[next.1 * width + next.0]
+                        .compare_exchange(0, towards_parent, Ordering::Relaxed, Ordering::Relaxed)
+                        .is_ok()
+                })
+            })
+            .collect();
+
+        if let Some(trail) = &mut self.trail {
+            trail.tick();
+            // Cells are stamped as they are reached, which the start never was
+            for &pos in self.frontier.iter().chain(&next) {
+                trail.insert(pos);
+            }
+        }
+
+        self.visited_count += next.len();
+        self.memory.track_open(next.len());
+        self.frontier = next;
+
+        SolveStep::InProgress
+    }
+
+    fn visited_count(&self) -> usize {
+        self.visited_count
+    }
+
+    /// Each step holds the frontier being expanded and the next one
+    fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.visited_count,
+            closed: self.visited_count,
+            bytes: self.parents.len() + 2 * self.memory.open_peak * size_of::<(usize, usize)>(),
+            ..self.memory
         }
+    }
 
+    /// Every step expands a whole frontier
+    fn current(&self) -> Option<(usize, usize)> {
         None
     }
 }
@@ -165,302 +748,1286 @@ impl MazeSolver for BFSSolver {
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct CellInformation {
     f_cost: usize,
+    g_cost: usize,
     h_cost: usize,
     from: Option<(usize, usize)>,
 }
 
+/// How [`AStarSolver`] picks between open cells of the same estimated cost. Each explores the
+/// maze in its own pattern, and in braided or open mazes may end up on another path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// The cell nearest the end
+    #[default]
+    Nearest,
+    /// A cell carrying on in the direction its parent was entered from
+    Straight,
+    /// A cell turning off the direction its parent was entered from
+    Turns,
+    /// Any of them, at random
+    Random,
+}
+
+/// Hundredths [`AStarSolver`] counts its heuristic weight in, keeping costs whole numbers
+const WEIGHT_SCALE: usize = 100;
+
 pub struct AStarSolver {
     open: BTreeMap<(usize, usize), CellInformation>,
-    closed: HashMap<(usize, usize), CellInformation>,
+    closed: VisitedGrid,
+    parents: ParentGrid,
+    memory: MemoryStats,
+    current: Option<(usize, usize)>,
 
+    start: (usize, usize),
     end: (usize, usize),
+    tie_break: TieBreak,
+    /// Heuristic weight in [`WEIGHT_SCALE`]ths
+    weight: usize,
+    seed: u64,
+    /// Only drawn from by [`TieBreak::Random`], while the open cells are borrowed
+    rng: RefCell<SeedRng>,
+    trail: Option<Trail>,
 
     path: Vec<(usize, usize)>,
 }
 
+impl AStarSolver {
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    pub fn with_heuristic_weight(mut self, weight: f32) -> Self {
+        self.set_heuristic_weight(weight);
+        self
+    }
+
+    /// Restarts the random tie break from `seed`, which is kept on reset
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = RefCell::new(Stream::TieBreak.rng(seed));
+    }
+
+    pub fn set_trail(&mut self, bounds: (usize, usize), length: Option<u32>) {
+        self.trail = length.map(|length| Trail::new(bounds, length));
+    }
+
+    /// Scales the distance to the end in the estimated cost, re-estimating the open cells so it
+    /// takes effect mid-solve. Above 1 heads for the end more greedily, finding a path sooner that
+    /// may not be the shortest. 0 ignores the end and spreads out like breadth-first search.
+    pub fn set_heuristic_weight(&mut self, weight: f32) {
+        self.weight = (weight.max(0.) * WEIGHT_SCALE as f32).round() as usize;
+
+        for info in self.open.values_mut() {
+            info.f_cost = info.g_cost * WEIGHT_SCALE + self.weight * info.h_cost;
+        }
+    }
+
+    /// Whether entering `pos` from `from` goes straight on, `None` when `from` is the start and
+    /// was not entered from anywhere
+    fn goes_straight(&self, pos: (usize, usize), from: Option<(usize, usize)>) -> Option<bool> {
+        let from = from?;
+        let before = self.parents.get(from)?;
+
+        Some(Direction::between(before, from) == Direction::between(from, pos))
+    }
+
+    /// Whether the open cell `pos` should be taken over `best`, whose estimated cost is the same.
+    /// `ties` counts the cells of that cost so far, for picking one at random.
+    fn prefers(
+        &self,
+        (pos, info): ((usize, usize), &CellInformation),
+        (best_pos, best): ((usize, usize), &CellInformation),
+        ties: usize,
+    ) -> bool {
+        // Cells the tie break prefers rank lowest, with cells next to the start in between
+        let rank = |pos, info: &CellInformation, straight| match self.goes_straight(pos, info.from)
+        {
+            Some(goes_straight) if goes_straight == straight => 0,
+            None => 1,
+            Some(_) => 2,
+        };
+
+        match self.tie_break {
+            TieBreak::Nearest => info.h_cost <= best.h_cost,
+            TieBreak::Straight => {
+                (rank(pos, info, true), info.h_cost) <= (rank(best_pos, best, true), best.h_cost)
+            }
+            TieBreak::Turns => {
+                (rank(pos, info, false), info.h_cost) <= (rank(best_pos, best, false), best.h_cost)
+            }
+            TieBreak::Random => self.rng.borrow_mut().random_range(..ties) == 0,
+        }
+    }
+}
+
 impl MazeSolver for AStarSolver {
-    fn new(bounds: (usize, usize)) -> Self {
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
+        let seed = random_seed();
         let mut open = BTreeMap::new();
 
         open.insert(
-            (0, 0),
+            start,
             CellInformation {
-                h_cost: 0,
-                f_cost: bounds.0 + bounds.1,
+                g_cost: 0,
+                h_cost: start.0.abs_diff(end.0) + start.1.abs_diff(end.1),
+                f_cost: WEIGHT_SCALE * (start.0.abs_diff(end.0) + start.1.abs_diff(end.1)),
                 from: None,
             },
         );
 
         Self {
+            memory: MemoryStats {
+                open: open.len(),
+                open_peak: open.len(),
+                ..MemoryStats::default()
+            },
             open,
-            closed: HashMap::new(),
-
-            end: (bounds.0 - 1, bounds.1 - 1),
+            closed: VisitedGrid::new(bounds),
+            parents: ParentGrid::new(bounds),
+            current: None,
+
+            start,
+            end,
+            tie_break: TieBreak::default(),
+            weight: WEIGHT_SCALE,
+            seed,
+            rng: RefCell::new(Stream::TieBreak.rng(seed)),
+            trail: None,
 
             path: Vec::new(),
         }
     }
 
-    fn step(&mut self, maze: &Maze) -> Option<&Vec<(usize, usize)>> {
+    fn reset(&mut self, bounds: (usize, usize)) {
+        let weight = self.weight as f32 / WEIGHT_SCALE as f32;
+        let seed = self.seed;
+        let trail = self.trail.as_ref().map(Trail::length);
+
+        *self = Self::between(bounds, self.start, self.end)
+            .with_tie_break(self.tie_break)
+            .with_heuristic_weight(weight);
+        self.set_seed(seed);
+        self.set_trail(bounds, trail);
+    }
+
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
         if !self.path.is_empty() {
-            return Some(&self.path);
+            return SolveStep::Found(&self.path);
+        }
+
+        if self.open.is_empty() {
+            return SolveStep::Unsolvable;
         }
 
         let mut current_data: (&(usize, usize), &CellInformation) = (
             &(1, 1),
             &CellInformation {
                 from: None,
+                g_cost: usize::MAX,
                 f_cost: usize::MAX,
                 h_cost: usize::MAX,
             },
         );
 
+        let mut ties = 0;
+
         for (pos, info) in self.open.iter() {
-            if info.f_cost < current_data.1.f_cost
-                || (info.f_cost == current_data.1.f_cost && info.h_cost <= current_data.1.h_cost)
-            {
-                current_data.0 = pos;
-                current_data.1 = info;
+            if info.f_cost < current_data.1.f_cost {
+                ties = 1;
+            } else if info.f_cost == current_data.1.f_cost {
+                ties += 1;
+
+                if !self.prefers((*pos, info), (*current_data.0, current_data.1), ties) {
+                    continue;
+                }
+            } else {
+                continue;
             }
+
+            current_data.0 = pos;
+            current_data.1 = info;
         }
 
         let current_pos = *current_data.0;
         let current = *current_data.1;
 
-        self.closed.insert(current_pos, current);
+        self.closed.insert(current_pos);
+        self.current = Some(current_pos);
+        if let Some(trail) = &mut self.trail {
+            trail.tick();
+            trail.insert(current_pos);
+        }
+        if let Some(from) = current.from {
+            self.parents.set(current_pos, from);
+        }
         self.open.remove(&current_pos);
 
         if current_pos == self.end {
-            let mut pos = current_pos;
-
-            loop {
-                self.path.push(pos);
-
-                let prev = self.closed.get(&pos).unwrap();
-
-                match prev.from {
-                    None => break,
-                    Some(v) => pos = v,
-                }
-            }
+            self.path = self.parents.path_to(current_pos);
 
-            self.path.reverse();
-
-            return Some(&self.path);
+            return SolveStep::Found(&self.path);
         }
 
         let neighbors = maze.get_travellable_neighbors(current_pos);
 
         for neighbor in (0..neighbors.1).map(|i| neighbors.0[i]) {
-            if self.closed.contains_key(&neighbor) {
+            if self.closed.contains(neighbor) {
                 continue;
             }
 
-            let g_cost = current.h_cost + 1;
-            let h_cost = current_pos.0.abs_diff(self.end.0) + current_pos.1.abs_diff(self.end.1);
-            let f_cost = g_cost + h_cost;
+            let g_cost = current.g_cost + 1;
+            let h_cost = neighbor.0.abs_diff(self.end.0) + neighbor.1.abs_diff(self.end.1);
+            let info = CellInformation {
+                f_cost: g_cost * WEIGHT_SCALE + self.weight * h_cost,
+                g_cost,
+                h_cost,
+                from: Some(current_pos),
+            };
 
-            if if let Some(v) = self.closed.get(&neighbor) {
-                h_cost < v.h_cost
-            } else {
-                false
-            } {
-                self.closed.insert(
-                    neighbor,
-                    CellInformation {
-                        f_cost,
-                        h_cost,
-                        from: Some(current_pos),
-                    },
-                );
-                continue;
+            // A cheaper way to a cell already open replaces the one it was opened by
+            match self.open.entry(neighbor) {
+                Entry::Vacant(entry) => {
+                    entry.insert(info);
+                }
+                Entry::Occupied(mut entry) if g_cost < entry.get().g_cost => {
+                    entry.insert(info);
+                }
+                Entry::Occupied(_) => {}
             }
+        }
+        self.memory.track_open(self.open.len());
 
-            self.open.entry(neighbor).or_insert(CellInformation {
-                f_cost,
-                h_cost,
-                from: Some(current_pos),
-            });
+        SolveStep::InProgress
+    }
+
+    fn visited_count(&self) -> usize {
+        self.closed.len()
+    }
+
+    fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.closed.len(),
+            closed: self.closed.len(),
+            bytes: self.closed.bytes()
+                + self.parents.bytes()
+                + self.memory.open_peak * size_of::<((usize, usize), CellInformation)>(),
+            ..self.memory
         }
+    }
 
-        None
+    fn current(&self) -> Option<(usize, usize)> {
+        self.current
     }
 }
 
+#[cfg(feature = "gui")]
 impl Drawable for DFSSolver {
     fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
         &'a self,
         target: &mut dyn sfml::graphics::RenderTarget,
         rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
     ) {
-        let cell_size = get_cell_size();
+        draw_path(target, rs, &self.path, None);
+    }
+}
 
-        let mut polyline = VertexBuffer::new(
-            PrimitiveType::LINE_STRIP,
-            self.path.len(),
-            VertexBufferUsage::DYNAMIC,
-        )
-        .unwrap();
+#[cfg(feature = "gui")]
+impl Drawable for BFSSolver {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        if self.finished {
+            let (on_path, off_path): (Vec<_>, Vec<_>) =
+                self.visited.iter().partition(|pos| self.path.contains(pos));
+
+            draw_marks(target, rs, off_path, Mark::Visited, true);
+            draw_marks(target, rs, on_path, Mark::Visited, false);
+        } else if let Some(trail) = &self.trail {
+            trail.draw(target, rs);
+        } else {
+            draw_marks(target, rs, self.visited.iter(), Mark::Visited, false);
+        }
 
-        let points: Vec<Vertex> = self
-            .path
-            .iter()
-            .map(|(x, y)| {
-                Vertex::with_pos_color(
-                    (
-                        ((*x * 2 + 1) * cell_size / 2) as f32,
-                        ((*y * 2 + 1) * cell_size / 2) as f32,
-                    )
-                        .into(),
-                    Color::RED,
-                )
+        draw_path(target, rs, &self.path, None);
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for ParallelBFSSolver {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        // The frontier stands out from the cells already behind it
+        match self.trail.as_ref().filter(|_| !self.finished) {
+            Some(trail) => trail.draw(target, rs),
+            None => draw_marks(target, rs, self.visited(), Mark::Visited, true),
+        }
+        draw_marks(
+            target,
+            rs,
+            self.frontier.iter().copied(),
+            Mark::Frontier,
+            false,
+        );
+
+        draw_path(target, rs, &self.path, None);
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for AStarSolver {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let finished = !self.path.is_empty();
+
+        if finished {
+            let (on_path, off_path): (Vec<_>, Vec<_>) =
+                self.closed.iter().partition(|pos| self.path.contains(pos));
+
+            draw_marks(target, rs, off_path, Mark::Visited, true);
+            draw_marks(target, rs, on_path, Mark::Visited, false);
+        } else if let Some(trail) = &self.trail {
+            trail.draw(target, rs);
+        } else {
+            draw_marks(target, rs, self.closed.iter(), Mark::Visited, false);
+        }
+
+        draw_marks(
+            target,
+            rs,
+            self.open.keys().copied(),
+            Mark::Frontier,
+            finished,
+        );
+
+        draw_path(target, rs, &self.path, None);
+    }
+}
+
+/// Walks towards the end one cell per step while the maze may change underneath it, re-running A*
+/// from its current cell whenever a wall appears on the remaining plan.
+pub struct ReplanningSolver {
+    bounds: (usize, usize),
+    pos: (usize, usize),
+    end: (usize, usize),
+
+    /// Remaining cells to walk, with the next cell last
+    plan: Vec<(usize, usize)>,
+    walked: Vec<(usize, usize)>,
+
+    replans: usize,
+    /// Cells explored across every replan
+    explored: usize,
+    /// Largest of the replans
+    planner_memory: MemoryStats,
+}
+
+impl ReplanningSolver {
+    pub fn replans(&self) -> usize {
+        self.replans
+    }
+
+    fn plan_is_valid(&self, maze: &Maze) -> bool {
+        if self.plan.is_empty() {
+            return false;
+        }
+
+        std::iter::once(&self.pos)
+            .chain(self.plan.iter().rev())
+            .zip(self.plan.iter().rev())
+            .all(|(from, to)| match Direction::between(*from, *to) {
+                Some(direction) => maze.get(from.0, from.1).contains(direction),
+                None => false,
             })
+    }
+
+    fn replan(&mut self, maze: &Maze) {
+        let mut solver = AStarSolver::between(self.bounds, self.pos, self.end);
+
+        let path = loop {
+            match solver.step(maze) {
+                SolveStep::InProgress => {}
+                SolveStep::Found(path) => break path.to_vec(),
+                SolveStep::Unsolvable => break vec![],
+            }
+        };
+
+        self.plan = path.iter().skip(1).rev().copied().collect();
+        self.replans += 1;
+        self.explored += solver.visited_count();
+
+        let memory = solver.memory();
+        self.planner_memory = MemoryStats {
+            visited: self.planner_memory.visited.max(memory.visited),
+            open: memory.open,
+            open_peak: self.planner_memory.open_peak.max(memory.open_peak),
+            closed: self.planner_memory.closed.max(memory.closed),
+            bytes: self.planner_memory.bytes.max(memory.bytes),
+        };
+    }
+}
+
+impl MazeSolver for ReplanningSolver {
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
+        Self {
+            bounds,
+            pos: start,
+            end,
+
+            plan: Vec::new(),
+            walked: vec![start],
+
+            replans: 0,
+            explored: 0,
+            planner_memory: MemoryStats::default(),
+        }
+    }
+
+    fn reset(&mut self, bounds: (usize, usize)) {
+        *self = Self::between(bounds, self.walked[0], self.end);
+    }
+
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
+        if self.pos == self.end {
+            return SolveStep::Found(&self.walked);
+        }
+
+        if !self.plan_is_valid(maze) {
+            self.replan(maze);
+
+            if self.plan.is_empty() {
+                return SolveStep::Unsolvable;
+            }
+        }
+
+        if let Some(next) = self.plan.pop() {
+            self.pos = next;
+            self.walked.push(next);
+        }
+
+        if self.pos == self.end {
+            SolveStep::Found(&self.walked)
+        } else {
+            SolveStep::InProgress
+        }
+    }
+
+    fn visited_count(&self) -> usize {
+        self.explored
+    }
+
+    /// Only one plan is made at a time, so this is the largest of them, plus the walked cells
+    fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.explored,
+            bytes: self.planner_memory.bytes
+                + (self.walked.len() + self.plan.len()) * size_of::<(usize, usize)>(),
+            ..self.planner_memory
+        }
+    }
+
+    fn current(&self) -> Option<(usize, usize)> {
+        Some(self.pos)
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for ReplanningSolver {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let plan: Vec<_> = std::iter::once(self.pos)
+            .chain(self.plan.iter().rev().copied())
             .collect();
+        let mut plan_color = theme().frontier;
+        plan_color.a = 128;
+
+        draw_path(target, rs, &plan, Some(plan_color));
+        draw_path(target, rs, &self.walked, None);
+    }
+}
+
+/// Lets an [`Agent`] walk to the end, taking every cell it walked through as the path, dead ends
+/// and all
+pub struct AgentSolver {
+    agent: Box<dyn Agent>,
+    end: (usize, usize),
+
+    visited: VisitedGrid,
+    walked: Vec<(usize, usize)>,
+}
+
+impl AgentSolver {
+    pub fn new(bounds: (usize, usize), agent: Box<dyn Agent>, end: (usize, usize)) -> Self {
+        let mut visited = VisitedGrid::new(bounds);
+        visited.insert(agent.position());
+
+        Self {
+            walked: vec![agent.position()],
+            visited,
+            agent,
+            end,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.agent.name()
+    }
+
+    pub fn visited_count(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// Agents keep nothing waiting to be explored. What they remember themselves is not counted.
+    pub fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.visited.len(),
+            open: 0,
+            open_peak: 0,
+            closed: 0,
+            bytes: self.visited.bytes() + self.walked.len() * size_of::<(usize, usize)>(),
+        }
+    }
+
+    pub fn current(&self) -> Option<(usize, usize)> {
+        Some(self.agent.position())
+    }
+
+    pub fn reset(&mut self, bounds: (usize, usize)) {
+        let start = self.walked[0];
+        self.agent.reset(bounds, start);
+
+        self.visited = VisitedGrid::new(bounds);
+        self.visited.insert(start);
+        self.walked = vec![start];
+    }
+
+    pub fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
+        // Agents would wander forever looking for an end they can not reach, or that they gave up
+        // on, so that is checked before setting off
+        let unreachable = self.walked.len() == 1 && bfs(maze, self.walked[0], self.end).is_none();
+        if unreachable || self.agent.failure().is_some() {
+            return SolveStep::Unsolvable;
+        }
 
-        polyline.update(&points, 0).unwrap();
+        if self.agent.position() != self.end {
+            self.agent.step(maze);
 
-        target.draw_vertex_buffer(&polyline, rs);
+            let pos = self.agent.position();
+            self.visited.insert(pos);
+            self.walked.push(pos);
+        }
+
+        if self.agent.position() == self.end {
+            SolveStep::Found(&self.walked)
+        } else {
+            SolveStep::InProgress
+        }
     }
 }
 
-impl Drawable for BFSSolver {
+#[cfg(feature = "gui")]
+impl Drawable for AgentSolver {
     fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
         &'a self,
         target: &mut dyn sfml::graphics::RenderTarget,
         rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
     ) {
-        let cell_size = get_cell_size();
-
-        let mut rect =
-            RectangleShape::with_size((cell_size as f32 / 2., cell_size as f32 / 2.).into());
-        rect.set_origin((cell_size as f32 / 4., cell_size as f32 / 4.));
-
-        for pos in self.visited.keys() {
-            rect.set_fill_color(Color::rgba(
-                0,
-                255,
-                0,
-                if self.finished {
-                    if self.path.contains(pos) {
-                        255
-                    } else {
-                        64
-                    }
-                } else {
-                    255
-                },
-            ));
+        draw_path(target, rs, &self.walked, None);
+    }
+}
+
+/// Reverse breadth-first search from the end, pointing every reachable cell towards it. Once the
+/// whole maze is covered the path from the start is read off the arrows; see
+/// [`crate::analysis::flow_field`] for the non-animated version.
+pub struct FlowFieldSolver {
+    queue: VecDeque<(usize, usize)>,
+    visited: VisitedGrid,
+    toward_end: ParentGrid,
+    memory: MemoryStats,
+    current: Option<(usize, usize)>,
+
+    start: (usize, usize),
+    end: (usize, usize),
+    path: Vec<(usize, usize)>,
+    finished: bool,
+}
+
+impl FlowFieldSolver {
+    /// Once the whole field is covered, the start either points the way to the end or was never
+    /// reached
+    fn result(&self) -> SolveStep<'_> {
+        match self.path.is_empty() {
+            true => SolveStep::Unsolvable,
+            false => SolveStep::Found(&self.path),
+        }
+    }
+}
+
+impl MazeSolver for FlowFieldSolver {
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
+        let mut visited = VisitedGrid::new(bounds);
+        visited.insert(end);
+
+        Self {
+            queue: VecDeque::from([end]),
+            visited,
+            toward_end: ParentGrid::new(bounds),
+            memory: MemoryStats::default(),
+            current: None,
+
+            start,
+            end,
+            path: vec![],
+            finished: false,
+        }
+    }
+
+    fn reset(&mut self, bounds: (usize, usize)) {
+        *self = Self::between(bounds, self.start, self.end);
+    }
+
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
+        if self.finished {
+            return self.result();
+        }
+
+        let Some(pos) = self.queue.pop_front() else {
+            self.finished = true;
+
+            if self.visited.contains(self.start) {
+                self.path = self.toward_end.path_to(self.start);
+                self.path.reverse();
+            }
+
+            return self.result();
+        };
+        self.current = Some(pos);
+
+        let (neighbors, count) = maze.get_travellable_neighbors(pos);
+
+        for next in neighbors.into_iter().take(count) {
+            if self.visited.insert(next) {
+                self.toward_end.set(next, pos);
+                self.queue.push_back(next);
+            }
+        }
+        self.memory.track_open(self.queue.len());
 
-            rect.set_position((
-                ((pos.0 * 2 + 1) * cell_size / 2) as f32,
-                ((pos.1 * 2 + 1) * cell_size / 2) as f32,
-            ));
+        SolveStep::InProgress
+    }
+
+    fn visited_count(&self) -> usize {
+        self.visited.len()
+    }
+
+    fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.visited.len(),
+            closed: self.visited.len(),
+            bytes: self.visited.bytes()
+                + self.toward_end.bytes()
+                + self.memory.open_peak * size_of::<(usize, usize)>(),
+            ..self.memory
+        }
+    }
+
+    fn current(&self) -> Option<(usize, usize)> {
+        self.current
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for FlowFieldSolver {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let cell_size = get_cell_size() as f32;
+        let theme = theme();
+
+        let mut lines = vec![];
+
+        for pos in self.visited.iter() {
+            let Some(direction) = self.toward_end.direction(pos) else {
+                continue;
+            };
 
-            target.draw(&rect);
+            let (dx, dy) = match direction {
+                Direction::UP => (0., -1.),
+                Direction::RIGHT => (1., 0.),
+                Direction::DOWN => (0., 1.),
+                Direction::LEFT => (-1., 0.),
+            };
+
+            let color = if self.finished && self.path.contains(&pos) {
+                theme.path
+            } else {
+                theme.visited
+            };
+
+            let center = (
+                (pos.0 as f32 + 0.5) * cell_size,
+                (pos.1 as f32 + 0.5) * cell_size,
+            );
+            let tail = (
+                center.0 - dx * cell_size * 0.3,
+                center.1 - dy * cell_size * 0.3,
+            );
+            let tip = (
+                center.0 + dx * cell_size * 0.3,
+                center.1 + dy * cell_size * 0.3,
+            );
+            let back = (tip.0 - dx * cell_size * 0.15, tip.1 - dy * cell_size * 0.15);
+            let head_left = (
+                back.0 - dy * cell_size * 0.15,
+                back.1 + dx * cell_size * 0.15,
+            );
+            let head_right = (
+                back.0 + dy * cell_size * 0.15,
+                back.1 - dx * cell_size * 0.15,
+            );
+
+            for (from, to) in [(tail, tip), (tip, head_left), (tip, head_right)] {
+                lines.push(Vertex::with_pos_color(from.into(), color));
+                lines.push(Vertex::with_pos_color(to.into(), color));
+            }
         }
 
-        let mut polyline = VertexBuffer::new(
-            PrimitiveType::LINE_STRIP,
-            self.path.len(),
+        if lines.is_empty() {
+            return;
+        }
+
+        let mut buffer = VertexBuffer::new(
+            PrimitiveType::LINES,
+            lines.len(),
             VertexBufferUsage::DYNAMIC,
         )
         .unwrap();
 
-        let points: Vec<Vertex> = self
-            .path
-            .iter()
-            .map(|(x, y)| {
-                Vertex::with_pos_color(
-                    (
-                        ((*x * 2 + 1) * cell_size / 2) as f32,
-                        ((*y * 2 + 1) * cell_size / 2) as f32,
-                    )
-                        .into(),
-                    Color::RED,
-                )
-            })
-            .collect();
+        buffer.update(&lines, 0).unwrap();
+
+        target.draw_vertex_buffer(&buffer, rs);
+    }
+}
+
+/// Doors and the keys opening them, matched by color. Keys are kept once picked up, so one key
+/// opens every door of its color.
+#[derive(Debug, Clone, Default)]
+pub struct Locks {
+    /// Cells that can only be entered holding the key of their color
+    pub doors: BTreeMap<(usize, usize), u8>,
+    /// Cells handing out the key of their color to whoever walks in
+    pub keys: BTreeMap<(usize, usize), u8>,
+}
+
+impl Locks {
+    /// Colors a keyring can hold
+    pub const MAX_COLORS: usize = LOCK_COLORS as usize;
+
+    /// Reads [`Tag::Door`]s and [`Tag::Key`]s from the maze, each with its
+    /// [lock color](Maze::lock_color)
+    pub fn from_tags(maze: &Maze) -> Self {
+        let colored = |tag| {
+            maze.find_tag(tag)
+                .map(|(x, y)| ((x, y), maze.lock_color(x, y)))
+                .collect()
+        };
+
+        Self {
+            doors: colored(Tag::Door),
+            keys: colored(Tag::Key),
+        }
+    }
+
+    /// Fails on a color past [`Locks::MAX_COLORS`], which no keyring could hold
+    fn check(&self) -> Result<(), String> {
+        let mut locks = self.doors.iter().chain(&self.keys);
+
+        match locks.find(|(_, &color)| color as usize >= Self::MAX_COLORS) {
+            Some(((x, y), color)) => Err(format!(
+                "lock color {color} at ({x}, {y}) is not below {}",
+                Self::MAX_COLORS
+            )),
+            None => Ok(()),
+        }
+    }
 
-        polyline.update(&points, 0).unwrap();
+    /// The keyring after entering `pos`
+    fn pick_up(&self, keyring: u32, pos: (usize, usize)) -> u32 {
+        match self.keys.get(&pos) {
+            Some(color) => keyring | 1 << color,
+            None => keyring,
+        }
+    }
 
-        target.draw_vertex_buffer(&polyline, rs);
+    fn can_enter(&self, keyring: u32, pos: (usize, usize)) -> bool {
+        self.doors
+            .get(&pos)
+            .is_none_or(|color| keyring & 1 << color != 0)
     }
 }
 
-impl Drawable for AStarSolver {
+/// A cell together with the keys held on reaching it
+type KeyState = ((usize, usize), u32);
+
+/// A* over every cell and set of held keys, so the path may walk past a locked door, fetch its key
+/// and come back. Without explicit [`Locks`] they are read from the maze tags on the first step.
+pub struct ConstraintSolver {
+    start: (usize, usize),
+    end: (usize, usize),
+    locks: Option<Locks>,
+    /// Locks given to [`ConstraintSolver::with_locks`], kept over resets
+    given_locks: Option<Box<Locks>>,
+
+    /// Lowest first, by estimated total cost and then cost so far
+    open: BinaryHeap<Reverse<(usize, usize, KeyState)>>,
+    costs: HashMap<KeyState, usize>,
+    parents: HashMap<KeyState, KeyState>,
+    closed: HashSet<KeyState>,
+    visited: VisitedGrid,
+    memory: MemoryStats,
+    current: Option<(usize, usize)>,
+    trail: Option<Trail>,
+
+    path: Vec<(usize, usize)>,
+}
+
+impl ConstraintSolver {
+    pub fn set_trail(&mut self, bounds: (usize, usize), length: Option<u32>) {
+        self.trail = length.map(|length| Trail::new(bounds, length));
+    }
+
+    /// Fails if a lock has a color past [`Locks::MAX_COLORS`]
+    pub fn with_locks(
+        bounds: (usize, usize),
+        start: (usize, usize),
+        end: (usize, usize),
+        locks: Locks,
+    ) -> Result<Self, String> {
+        locks.check()?;
+
+        Ok(Self::locked(bounds, start, end, locks))
+    }
+
+    fn locked(
+        bounds: (usize, usize),
+        start: (usize, usize),
+        end: (usize, usize),
+        locks: Locks,
+    ) -> Self {
+        let mut solver = Self::between(bounds, start, end);
+        solver.given_locks = Some(Box::new(locks.clone()));
+        solver.unlock(locks);
+
+        solver
+    }
+
+    fn unlock(&mut self, locks: Locks) {
+        let state = (self.start, locks.pick_up(0, self.start));
+
+        self.costs.insert(state, 0);
+        self.open
+            .push(Reverse((self.distance_to_end(self.start), 0, state)));
+        self.locks = Some(locks);
+    }
+
+    fn distance_to_end(&self, pos: (usize, usize)) -> usize {
+        pos.0.abs_diff(self.end.0) + pos.1.abs_diff(self.end.1)
+    }
+}
+
+impl MazeSolver for ConstraintSolver {
+    fn between(bounds: (usize, usize), start: (usize, usize), end: (usize, usize)) -> Self {
+        Self {
+            start,
+            end,
+            locks: None,
+            given_locks: None,
+
+            open: BinaryHeap::new(),
+            costs: HashMap::new(),
+            parents: HashMap::new(),
+            closed: HashSet::new(),
+            visited: VisitedGrid::new(bounds),
+            memory: MemoryStats::default(),
+            current: None,
+            trail: None,
+
+            path: Vec::new(),
+        }
+    }
+
+    fn reset(&mut self, bounds: (usize, usize)) {
+        let trail = self.trail.as_ref().map(Trail::length);
+
+        *self = match self.given_locks.take() {
+            Some(locks) => Self::locked(bounds, self.start, self.end, *locks),
+            None => Self::between(bounds, self.start, self.end),
+        };
+        self.set_trail(bounds, trail);
+    }
+
+    fn step(&mut self, maze: &Maze) -> SolveStep<'_> {
+        if !self.path.is_empty() {
+            return SolveStep::Found(&self.path);
+        }
+
+        if self.locks.is_none() {
+            self.unlock(Locks::from_tags(maze));
+        }
+
+        let Some(Reverse((_, cost, state))) = self.open.pop() else {
+            return SolveStep::Unsolvable;
+        };
+        if !self.closed.insert(state) {
+            return SolveStep::InProgress;
+        }
+
+        let (pos, keyring) = state;
+        self.visited.insert(pos);
+        self.current = Some(pos);
+        if let Some(trail) = &mut self.trail {
+            trail.tick();
+            trail.insert(pos);
+        }
+
+        if pos == self.end {
+            let mut path = vec![pos];
+            let mut current = state;
+
+            while let Some(&parent) = self.parents.get(&current) {
+                path.push(parent.0);
+                current = parent;
+            }
+
+            path.reverse();
+            self.path = path;
+
+            return SolveStep::Found(&self.path);
+        }
+
+        let locks = self.locks.as_ref().unwrap();
+        let neighbors = maze.get_travellable_neighbors(pos);
+
+        for neighbor in (0..neighbors.1).map(|i| neighbors.0[i]) {
+            if !locks.can_enter(keyring, neighbor) {
+                continue;
+            }
+
+            let next = (neighbor, locks.pick_up(keyring, neighbor));
+            if self.closed.contains(&next) || self.costs.get(&next).is_some_and(|c| *c <= cost + 1)
+            {
+                continue;
+            }
+
+            self.costs.insert(next, cost + 1);
+            self.parents.insert(next, state);
+            self.open.push(Reverse((
+                cost + 1 + self.distance_to_end(neighbor),
+                cost + 1,
+                next,
+            )));
+        }
+        self.memory.track_open(self.open.len());
+
+        SolveStep::InProgress
+    }
+
+    fn visited_count(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// Map entries are counted at their key and value sizes, leaving out the tables' own overhead
+    fn memory(&self) -> MemoryStats {
+        MemoryStats {
+            visited: self.visited.len(),
+            closed: self.closed.len(),
+            bytes: self.visited.bytes()
+                + self.memory.open_peak * size_of::<(usize, usize, KeyState)>()
+                + self.costs.len() * size_of::<(KeyState, usize)>()
+                + self.parents.len() * size_of::<(KeyState, KeyState)>()
+                + self.closed.len() * size_of::<KeyState>(),
+            ..self.memory
+        }
+    }
+
+    fn current(&self) -> Option<(usize, usize)> {
+        self.current
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for ConstraintSolver {
     fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
         &'a self,
         target: &mut dyn sfml::graphics::RenderTarget,
         rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
     ) {
-        let cell_size = get_cell_size();
+        match self.trail.as_ref().filter(|_| self.path.is_empty()) {
+            Some(trail) => trail.draw(target, rs),
+            None => draw_marks(target, rs, self.visited.iter(), Mark::Visited, true),
+        }
 
-        let finished = !self.path.is_empty();
+        // The path can double back after fetching a key, so it is drawn as a line
+        draw_path(target, rs, &self.path, None);
+    }
+}
 
-        let mut rect =
-            RectangleShape::with_size((cell_size as f32 / 2., cell_size as f32 / 2.).into());
-        rect.set_origin((cell_size as f32 / 4., cell_size as f32 / 4.));
-
-        for pos in self.closed.keys() {
-            rect.set_fill_color(Color::rgba(
-                255,
-                0,
-                0,
-                if finished {
-                    if self.path.contains(pos) {
-                        255
-                    } else {
-                        64
-                    }
-                } else {
-                    255
-                },
-            ));
-            rect.set_position((
-                ((pos.0 * 2 + 1) * cell_size / 2) as f32,
-                ((pos.1 * 2 + 1) * cell_size / 2) as f32,
-            ));
+// Plain solvers for library use, running to completion without any of the stepping and drawing
+// state above. Each returns the path from `start` to `end` inclusive, or `None` if either is
+// outside the maze or `end` cannot be reached.
+
+fn contains(maze: &Maze, (x, y): (usize, usize)) -> bool {
+    maze.try_get(x, y).is_some()
+}
 
-            target.draw(&rect);
+/// Straightens a path through `maze` without making it longer. Detours are cut out wherever a
+/// passage joins two cells of the path directly, then corners are swapped around the other side
+/// of their square wherever that turns less, so staircases across open areas become one turn.
+/// Perfect mazes leave only one way between cells, and their paths come back unchanged.
+pub fn smooth(maze: &Maze, path: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // The last index of every cell, to jump straight to the furthest cell a passage reaches
+    let mut last = HashMap::new();
+    for (i, &pos) in path.iter().enumerate() {
+        last.insert(pos, i);
+    }
+
+    // Once a cell is left it is never reached again, so the path stays free of loops
+    let mut smoothed = vec![];
+    let mut i = 0;
+    while i < path.len() {
+        let pos = path[i];
+        smoothed.push(pos);
+
+        let (neighbors, count) = maze.get_travellable_neighbors(pos);
+        i = neighbors[..count]
+            .iter()
+            .filter_map(|next| last.get(next).copied())
+            .fold(last[&pos] + 1, usize::max);
+    }
+
+    let mut on_path: HashSet<_> = smoothed.iter().copied().collect();
+    let direction = |from, to| Direction::between(from, to).unwrap();
+
+    // Every swap takes away a turn, so this ends
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for i in 1..smoothed.len().saturating_sub(1) {
+            let (before, corner, after) = (smoothed[i - 1], smoothed[i], smoothed[i + 1]);
+            let (first, second) = (direction(before, corner), direction(corner, after));
+            if first == second {
+                continue;
+            }
+
+            let Some(other) = maze.travel(before, second) else {
+                continue;
+            };
+            if on_path.contains(&other) || maze.travel(other, first) != Some(after) {
+                continue;
+            }
+
+            let previous = (i > 1).then(|| direction(smoothed[i - 2], before));
+            let next = smoothed.get(i + 2).map(|&pos| direction(after, pos));
+            let turns = |moves: [Direction; 2]| {
+                let moves: Vec<_> = previous.into_iter().chain(moves).chain(next).collect();
+                moves.windows(2).filter(|pair| pair[0] != pair[1]).count()
+            };
+
+            if turns([second, first]) < turns([first, second]) {
+                on_path.remove(&corner);
+                on_path.insert(other);
+                smoothed[i] = other;
+                changed = true;
+            }
         }
+    }
+
+    smoothed
+}
 
-        rect.set_fill_color(Color::rgba(0, 255, 0, if finished { 64 } else { 255 }));
+/// Shortest path, by breadth-first search
+pub fn bfs(maze: &Maze, start: (usize, usize), end: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    if !contains(maze, start) || !contains(maze, end) {
+        return None;
+    }
 
-        for pos in self.open.keys() {
-            rect.set_position((
-                ((pos.0 * 2 + 1) * cell_size / 2) as f32,
-                ((pos.1 * 2 + 1) * cell_size / 2) as f32,
-            ));
+    let bounds = maze.get_bounds();
+    let mut visited = VisitedGrid::new(bounds);
+    let mut parents = ParentGrid::new(bounds);
+    let mut queue = VecDeque::from([start]);
+    visited.insert(start);
 
-            target.draw(&rect);
+    while let Some(pos) = queue.pop_front() {
+        if pos == end {
+            return Some(parents.path_to(end));
         }
 
-        if !self.path.is_empty() {
-            let mut polyline = VertexBuffer::new(
-                PrimitiveType::LINE_STRIP,
-                self.path.len(),
-                VertexBufferUsage::DYNAMIC,
-            )
-            .unwrap();
-
-            let points: Vec<Vertex> = self
-                .path
-                .iter()
-                .map(|(x, y)| {
-                    Vertex::with_pos_color(
-                        (
-                            ((*x * 2 + 1) * cell_size / 2) as f32,
-                            ((*y * 2 + 1) * cell_size / 2) as f32,
-                        )
-                            .into(),
-                        Color::RED,
-                    )
-                })
-                .collect();
+        let (neighbors, count) = maze.get_travellable_neighbors(pos);
+        for &next in &neighbors[..count] {
+            if visited.insert(next) {
+                parents.set(next, pos);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// The path [`DFSSolver`] finds, always taking the first unvisited passage. Not necessarily the
+/// shortest.
+pub fn dfs(maze: &Maze, start: (usize, usize), end: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    if !contains(maze, start) || !contains(maze, end) {
+        return None;
+    }
+
+    let mut visited = VisitedGrid::new(maze.get_bounds());
+    let mut path = vec![start];
+
+    while let Some(&pos) = path.last() {
+        if pos == end {
+            return Some(path);
+        }
+
+        visited.insert(pos);
+
+        let (neighbors, count) = maze.get_travellable_neighbors(pos);
+        match neighbors[..count]
+            .iter()
+            .find(|&&next| !visited.contains(next))
+        {
+            Some(&next) => path.push(next),
+            None => {
+                path.pop();
+            }
+        }
+    }
+
+    None
+}
+
+/// Shortest path, by Dijkstra's algorithm. Every passage costs the same, so this finds paths as
+/// short as [`bfs`], only exploring in order of distance through a priority queue.
+pub fn dijkstra(
+    maze: &Maze,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    search(maze, start, end, |_| 0)
+}
+
+/// Shortest path, by A* with the Manhattan distance to `end` as heuristic
+pub fn a_star(
+    maze: &Maze,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    search(maze, start, end, |(x, y)| {
+        x.abs_diff(end.0) + y.abs_diff(end.1)
+    })
+}
 
-            polyline.update(&points, 0).unwrap();
+/// Best-first search shared by [`dijkstra`] and [`a_star`], with distances in a flat array
+fn search(
+    maze: &Maze,
+    start: (usize, usize),
+    end: (usize, usize),
+    heuristic: impl Fn((usize, usize)) -> usize,
+) -> Option<Vec<(usize, usize)>> {
+    if !contains(maze, start) || !contains(maze, end) {
+        return None;
+    }
+
+    let bounds = maze.get_bounds();
+    let mut distances = vec![usize::MAX; maze.cell_count()];
+    let mut closed = VisitedGrid::new(bounds);
+    let mut parents = ParentGrid::new(bounds);
+    let mut open = BinaryHeap::from([Reverse((heuristic(start), 0, start))]);
+    distances[start.1 * bounds.0 + start.0] = 0;
+
+    while let Some(Reverse((_, distance, pos))) = open.pop() {
+        if pos == end {
+            return Some(parents.path_to(end));
+        }
+        if !closed.insert(pos) {
+            continue;
+        }
+
+        let (neighbors, count) = maze.get_travellable_neighbors(pos);
+        for &next in &neighbors[..count] {
+            let i = next.1 * bounds.0 + next.0;
+
+            if distance + 1 < distances[i] {
+                distances[i] = distance + 1;
+                parents.set(next, pos);
+                open.push(Reverse((
+                    distance + 1 + heuristic(next),
+                    distance + 1,
+                    next,
+                )));
+            }
+        }
+    }
+
+    None
+}
 
-            target.draw_vertex_buffer(&polyline, rs);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(maze: &Maze) -> Option<Vec<(usize, usize)>> {
+        let mut solver = ConstraintSolver::between(maze.get_bounds(), (0, 0), (2, 0));
+
+        loop {
+            match solver.step(maze) {
+                SolveStep::InProgress => {}
+                SolveStep::Found(path) => return Some(path.to_vec()),
+                SolveStep::Unsolvable => return None,
+            }
         }
     }
+
+    /// A door between the start at the top left and the end at the top right, with a key in the
+    /// dead end below the start
+    fn locked_corridor(key_color: u8, door_color: u8) -> Maze {
+        let mut maze = Maze::new(3, 2);
+        maze.carve(0, 0, Direction::RIGHT);
+        maze.carve(1, 0, Direction::RIGHT);
+        maze.carve(0, 0, Direction::DOWN);
+
+        maze.tag(0, 1, Tag::Key);
+        maze.tag(1, 0, Tag::Door);
+        maze.set_lock_color(0, 1, key_color).unwrap();
+        maze.set_lock_color(1, 0, door_color).unwrap();
+
+        maze
+    }
+
+    #[test]
+    fn fetches_the_key_before_the_door() {
+        assert_eq!(
+            solve(&locked_corridor(2, 2)),
+            Some(vec![(0, 0), (0, 1), (0, 0), (1, 0), (2, 0)])
+        );
+    }
+
+    #[test]
+    fn keys_only_open_doors_of_their_color() {
+        assert_eq!(solve(&locked_corridor(1, 2)), None);
+    }
 }
@@ -0,0 +1,277 @@
+use rand::Rng;
+#[cfg(feature = "gui")]
+use sfml::graphics::Drawable;
+
+use crate::maze::generators::Generator;
+use crate::maze::seed::{SeedRng, Stream};
+use crate::maze::{GenStep, GenerationProgress, Maze, MazeGenerator};
+use crate::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    /// The left half mirrored onto the right
+    Horizontal,
+    /// The top half mirrored onto the bottom
+    Vertical,
+    /// The top half turned half a turn onto the bottom
+    Rotational,
+}
+
+impl Symmetry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Horizontal => "horizontal",
+            Self::Vertical => "vertical",
+            Self::Rotational => "rotational",
+        }
+    }
+
+    /// Bounds of the part a base generator fills in for a maze of `bounds`. It leaves out the
+    /// middle column or row of odd sizes, which would be mirrored onto itself.
+    pub fn region(&self, (width, height): (usize, usize)) -> (usize, usize) {
+        match self {
+            Self::Horizontal => (width / 2, height),
+            Self::Vertical | Self::Rotational => (width, height / 2),
+        }
+    }
+
+    fn image(&self, (x, y): (usize, usize), (width, height): (usize, usize)) -> (usize, usize) {
+        match self {
+            Self::Horizontal => (width - 1 - x, y),
+            Self::Vertical => (x, height - 1 - y),
+            Self::Rotational => (width - 1 - x, height - 1 - y),
+        }
+    }
+
+    fn mirror(&self, direction: Direction) -> Direction {
+        match (self, direction) {
+            (Self::Horizontal, Direction::LEFT | Direction::RIGHT)
+            | (Self::Vertical, Direction::UP | Direction::DOWN)
+            | (Self::Rotational, _) => direction.opposite(),
+            _ => direction,
+        }
+    }
+}
+
+/// Runs a base generator on one half of the maze, carving the mirror image of every passage it
+/// carves into the other half, then joins the halves.
+///
+/// A perfect maze stays perfect only if the passage joining the halves is its own mirror image,
+/// and on odd sizes only if the middle column or row mirrored onto itself is one straight
+/// corridor, so both are carved that way. The exception is rotational symmetry of even widths and
+/// heights, where no passage is its own image and joining the halves leaves a single loop.
+pub struct Symmetric {
+    base: Generator,
+    symmetry: Symmetry,
+    bounds: (usize, usize),
+    /// What the base generator has carved, in the coordinates of the maze
+    region: Maze,
+    joined: bool,
+
+    rng: SeedRng,
+}
+
+impl Symmetric {
+    /// `base` has to be made for [`Symmetry::region`] of `bounds`
+    pub fn new(base: Generator, symmetry: Symmetry, bounds: (usize, usize)) -> Self {
+        Self {
+            region: region_maze(symmetry.region(bounds)),
+            rng: Stream::Braiding.rng(base.seed()),
+
+            base,
+            symmetry,
+            bounds,
+            joined: false,
+        }
+    }
+
+    pub fn base(&self) -> &Generator {
+        &self.base
+    }
+
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    fn carve(&self, maze: &mut Maze, pos: (usize, usize), direction: Direction) {
+        maze.carve(pos.0, pos.1, direction);
+
+        let image = self.symmetry.image(pos, self.bounds);
+        maze.carve(image.0, image.1, self.symmetry.mirror(direction));
+    }
+
+    /// Copies a cell the base generator changed, along with its mirror image
+    fn copy(&self, maze: &mut Maze, pos: (usize, usize)) {
+        let image = self.symmetry.image(pos, self.bounds);
+
+        for direction in self.region.get(pos.0, pos.1).iter() {
+            maze.open(pos.0, pos.1, direction);
+            maze.open(image.0, image.1, self.symmetry.mirror(direction));
+        }
+    }
+
+    fn carved(&self, (x, y): (usize, usize)) -> bool {
+        !self.region.get(x, y).is_empty()
+    }
+
+    /// A random one of the `crossings` that are `usable`, else of those leading out of cells the
+    /// base generator made part of the maze, as caves leave some out, else of all of them
+    fn pick(
+        &mut self,
+        crossings: Vec<(usize, usize)>,
+        usable: impl Fn(&Self, (usize, usize)) -> bool,
+    ) -> (usize, usize) {
+        let choices = [
+            crossings
+                .iter()
+                .copied()
+                .filter(|&pos| usable(self, pos))
+                .collect(),
+            crossings
+                .iter()
+                .copied()
+                .filter(|&pos| self.carved(pos))
+                .collect(),
+            crossings,
+        ]
+        .into_iter()
+        .find(|choices: &Vec<_>| !choices.is_empty())
+        .unwrap_or_default();
+
+        choices[self.rng.random_range(..choices.len())]
+    }
+
+    /// Carves along the row from `from` towards `to`, until it reaches a cell of the base maze
+    fn dig(&self, maze: &mut Maze, from: (usize, usize), to: (usize, usize)) {
+        let direction = if to.0 > from.0 {
+            Direction::RIGHT
+        } else {
+            Direction::LEFT
+        };
+
+        let mut pos = from;
+        while pos != to && !self.carved(pos) {
+            self.carve(maze, pos, direction);
+            pos = direction.travel(pos.0, pos.1).unwrap();
+        }
+    }
+
+    fn join(&mut self, maze: &mut Maze) {
+        let (width, height) = self.bounds;
+        let (region_width, region_height) = self.region.get_bounds();
+
+        match self.symmetry {
+            Symmetry::Horizontal => {
+                let edge = region_width - 1;
+                let pos = self.pick((0..height).map(|y| (edge, y)).collect(), Self::carved);
+
+                if width % 2 == 1 {
+                    for y in 0..height - 1 {
+                        self.carve(maze, (region_width, y), Direction::DOWN);
+                    }
+                }
+                self.carve(maze, pos, Direction::RIGHT);
+            }
+            Symmetry::Vertical | Symmetry::Rotational => {
+                let edge = region_height - 1;
+                let crossings = (0..width).map(|x| (x, edge)).collect();
+
+                // Rotating even heights joins the halves with a second passage, from the cell
+                // across the middle, which only the center column of odd widths shares
+                let pos = if self.symmetry == Symmetry::Rotational && height % 2 == 0 {
+                    let center = (width / 2, edge);
+
+                    if width % 2 == 1 && self.carved(center) {
+                        center
+                    } else {
+                        let pos = self.pick(crossings, |this, (x, y)| {
+                            this.carved((x, y)) && this.carved((width - 1 - x, y))
+                        });
+
+                        // Caves may leave out the cell the second passage leads from, so it is
+                        // joined to the cave along the row
+                        self.dig(maze, (width - 1 - pos.0, edge), pos);
+                        pos
+                    }
+                } else {
+                    self.pick(crossings, Self::carved)
+                };
+
+                if height % 2 == 1 {
+                    for x in 0..width - 1 {
+                        self.carve(maze, (x, region_height), Direction::RIGHT);
+                    }
+                }
+                self.carve(maze, pos, Direction::DOWN);
+            }
+        }
+
+        self.joined = true;
+    }
+}
+
+#[cfg(feature = "gui")]
+impl Drawable for Symmetric {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        // The region starts at the top left corner, so the base draws in place
+        self.base.draw(target, rs);
+    }
+}
+
+impl MazeGenerator for Symmetric {
+    fn reset(&mut self, bounds: (usize, usize)) {
+        let region = self.symmetry.region(bounds);
+        self.base.reset(region);
+
+        self.bounds = bounds;
+        self.region = region_maze(region);
+        self.joined = false;
+        self.rng = Stream::Braiding.rng(self.base.seed());
+    }
+
+    fn step(&mut self, maze: &mut Maze) -> GenStep {
+        if self.joined {
+            return GenStep::Done;
+        }
+
+        let step = self.base.step(&mut self.region);
+
+        for pos in self.region.take_changes() {
+            self.copy(maze, pos);
+        }
+
+        match step {
+            GenStep::Done => {
+                self.join(maze);
+                GenStep::Done
+            }
+            // The base only knows about its region
+            GenStep::InProgress { .. } => self.in_progress(),
+        }
+    }
+}
+
+impl GenerationProgress for Symmetric {
+    fn remaining(&self) -> usize {
+        if self.joined {
+            return 0;
+        }
+
+        // Plus the middle column or row, if any, carved when joining
+        self.base.remaining() * 2 + (self.total() - self.base.total() * 2)
+    }
+
+    fn total(&self) -> usize {
+        self.bounds.0 * self.bounds.1
+    }
+}
+
+fn region_maze(bounds: (usize, usize)) -> Maze {
+    let mut maze = Maze::new(bounds.0 as u16, bounds.1 as u16);
+    maze.record_changes();
+    maze
+}
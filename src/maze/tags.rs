@@ -0,0 +1,184 @@
+#[cfg(feature = "gui")]
+use sfml::graphics::{CircleShape, Color, Drawable, Shape, Transformable};
+
+#[cfg(feature = "gui")]
+use crate::consts::get_cell_size;
+#[cfg(feature = "gui")]
+use crate::maze::Maze;
+
+/// Colors a [`Tag::Key`] or [`Tag::Door`] can have, as many as a solver's keyring holds
+pub const LOCK_COLORS: u8 = 32;
+
+/// Semantic marker on a cell, for games using the maze as a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tag {
+    Start = 0b00001,
+    End = 0b00010,
+    Key = 0b00100,
+    Door = 0b01000,
+    Trap = 0b10000,
+}
+
+impl Tag {
+    pub const ALL: [Tag; 5] = [Tag::Start, Tag::End, Tag::Key, Tag::Door, Tag::Trap];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tag::Start => "start",
+            Tag::End => "end",
+            Tag::Key => "key",
+            Tag::Door => "door",
+            Tag::Trap => "trap",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|tag| tag.name() == name)
+    }
+
+    /// Number of points of the marker shape, its rotation in degrees and its color
+    #[cfg(feature = "gui")]
+    fn marker(&self) -> (usize, f32, Color) {
+        match self {
+            Tag::Start => (16, 0., Color::GREEN),
+            Tag::End => (16, 0., Color::RED),
+            Tag::Key => (4, 0., Color::rgb(230, 190, 0)),
+            Tag::Door => (4, 45., Color::rgb(140, 80, 30)),
+            Tag::Trap => (3, 0., Color::MAGENTA),
+        }
+    }
+}
+
+/// A set of [`Tag`]s, stored as a bitmask like [`crate::DirectionSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TagSet(u8);
+
+impl TagSet {
+    pub const EMPTY: Self = Self(0);
+    pub const ALL: Self = Self(0b11111);
+
+    /// Bits of unknown tags are ignored
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(&self, tag: Tag) -> bool {
+        self.0 & tag as u8 != 0
+    }
+
+    pub fn insert(&mut self, tag: Tag) {
+        self.0 |= tag as u8;
+    }
+
+    pub fn remove(&mut self, tag: Tag) {
+        self.0 &= !(tag as u8);
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterates in the order of [`Tag::ALL`]
+    pub fn iter(&self) -> impl Iterator<Item = Tag> {
+        let set = *self;
+
+        Tag::ALL.into_iter().filter(move |tag| set.contains(*tag))
+    }
+}
+
+impl From<Tag> for TagSet {
+    fn from(tag: Tag) -> Self {
+        Self(tag as u8)
+    }
+}
+
+/// Draws a small marker for every tag of the maze, each tag in its own spot of the cell so a cell
+/// with several tags shows all of them
+#[cfg(feature = "gui")]
+pub struct TagMarkers<'a>(pub &'a Maze);
+
+#[cfg(feature = "gui")]
+impl Drawable for TagMarkers<'_> {
+    fn draw<'a: 'shader, 'texture, 'shader, 'shader_texture>(
+        &'a self,
+        target: &mut dyn sfml::graphics::RenderTarget,
+        rs: &sfml::graphics::RenderStates<'texture, 'shader, 'shader_texture>,
+    ) {
+        let cell_size = get_cell_size() as f32;
+        let radius = cell_size / 6.;
+
+        // Corners for the first four tags, the center for the last
+        let spots = [(1., 1.), (5., 1.), (1., 5.), (5., 5.), (3., 3.)];
+
+        for (x, y, tags) in self.0.tagged() {
+            for tag in tags.iter() {
+                let (points, rotation, color) = tag.marker();
+                let spot = spots[Tag::ALL.iter().position(|t| *t == tag).unwrap()];
+
+                let mut marker = CircleShape::new(radius, points);
+                marker.set_fill_color(color);
+                marker.set_outline_color(Color::BLACK);
+                marker.set_outline_thickness(1.);
+                marker.set_origin((radius, radius));
+                marker.set_rotation(rotation);
+                marker.set_position((
+                    x as f32 * cell_size + spot.0 * radius,
+                    y as f32 * cell_size + spot.1 * radius,
+                ));
+
+                target.draw_circle_shape(&marker, rs);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::Maze;
+
+    #[test]
+    fn rejects_lock_colors_past_the_keyring() {
+        let mut maze = Maze::new(2, 2);
+        maze.tag(0, 0, Tag::Key);
+        maze.tag(1, 1, Tag::Door);
+
+        assert!(maze.set_lock_color(0, 0, LOCK_COLORS - 1).is_ok());
+        assert!(maze.set_lock_color(0, 0, LOCK_COLORS).is_err());
+        assert!(maze.set_lock_color(1, 1, u8::MAX).is_err());
+
+        assert_eq!(maze.lock_color(0, 0), LOCK_COLORS - 1);
+        assert_eq!(maze.lock_color(1, 1), 0);
+    }
+
+    #[test]
+    fn colors_only_keys_and_doors() {
+        let mut maze = Maze::new(2, 2);
+        maze.tag(0, 0, Tag::Trap);
+
+        assert!(maze.set_lock_color(0, 0, 1).is_err());
+        assert!(maze.set_lock_color(1, 0, 1).is_err());
+    }
+
+    #[test]
+    fn untagging_a_lock_drops_its_color() {
+        let mut maze = Maze::new(2, 2);
+        maze.tag(0, 0, Tag::Key);
+        maze.set_lock_color(0, 0, 5).unwrap();
+        maze.untag(0, 0, Tag::Key);
+        maze.tag(0, 0, Tag::Key);
+
+        assert_eq!(maze.lock_color(0, 0), 0);
+    }
+
+    #[test]
+    fn ignores_unknown_tag_bits() {
+        let tags = TagSet::from_bits(0b1110_0101);
+
+        assert!(tags.iter().eq([Tag::Start, Tag::Key]));
+    }
+}
@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use sfml::cpp::FBox;
+use sfml::graphics::{
+    Color, Drawable, FloatRect, RectangleShape, RenderStates, RenderTarget, RenderTexture, Shape,
+    Sprite, Transformable, View,
+};
+use sfml::system::Vector2f;
+
+use crate::camera::Camera;
+use crate::theme::theme;
+
+/// Longest side of the minimap, in pixels
+const MINIMAP_SIZE: f32 = 160.;
+/// Distance from the window corner, as a fraction of the window size
+const MINIMAP_MARGIN: f32 = 0.02;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A small overview in the top right corner showing the whole maze, the solver's progress and
+/// the camera's viewport. The maze itself is kept in a downscaled texture that is only refreshed
+/// a few times per second while it is changing.
+pub struct Minimap {
+    world_size: Vector2f,
+    window_size: Vector2f,
+
+    texture: Option<FBox<RenderTexture>>,
+    stale: bool,
+    last_refresh: Instant,
+}
+
+impl Minimap {
+    pub fn new(world_size: (f32, f32), window_size: (f32, f32)) -> Self {
+        let world_size: Vector2f = world_size.into();
+        let scale = MINIMAP_SIZE / world_size.x.max(world_size.y);
+
+        Self {
+            world_size,
+            window_size: window_size.into(),
+
+            texture: RenderTexture::new(
+                (world_size.x * scale).ceil() as u32,
+                (world_size.y * scale).ceil() as u32,
+            )
+            .ok(),
+            stale: true,
+            last_refresh: Instant::now(),
+        }
+    }
+
+    /// Marks the cached maze texture as outdated
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Re-renders the cached maze texture if it is outdated
+    pub fn refresh(&mut self, maze: &dyn Drawable) {
+        if !self.stale || self.last_refresh.elapsed() < REFRESH_INTERVAL {
+            return;
+        }
+
+        let Some(texture) = &mut self.texture else {
+            return;
+        };
+
+        let view =
+            View::from_rect(FloatRect::new(0., 0., self.world_size.x, self.world_size.y)).unwrap();
+
+        texture.set_view(&view);
+        texture.clear(theme().wall);
+        texture.draw(maze);
+        texture.display();
+
+        self.stale = false;
+        self.last_refresh = Instant::now();
+    }
+
+    /// Draws the minimap with `overlay` (e.g. the solver) on top, then restores the camera view
+    pub fn draw(
+        &self,
+        target: &mut dyn RenderTarget,
+        overlay: Option<&dyn Drawable>,
+        camera: &Camera,
+    ) {
+        let Some(texture) = &self.texture else {
+            return;
+        };
+
+        let scale = MINIMAP_SIZE / self.world_size.x.max(self.world_size.y);
+        let width = self.world_size.x * scale / self.window_size.x;
+        let height = self.world_size.y * scale / self.window_size.y;
+
+        // A view over the whole maze, squeezed into the corner of the window
+        let mut view =
+            View::from_rect(FloatRect::new(0., 0., self.world_size.x, self.world_size.y)).unwrap();
+        view.set_viewport(FloatRect::new(
+            1. - MINIMAP_MARGIN - width,
+            MINIMAP_MARGIN,
+            width,
+            height,
+        ));
+        target.set_view(&view);
+
+        let mut sprite = Sprite::with_texture(texture.texture());
+        sprite.set_scale((1. / scale, 1. / scale));
+        target.draw_sprite(&sprite, &RenderStates::DEFAULT);
+
+        if let Some(overlay) = overlay {
+            target.draw(overlay);
+        }
+
+        let visible = camera.visible_rect();
+        let mut viewport = RectangleShape::with_size((visible.width, visible.height).into());
+        viewport.set_position((visible.left, visible.top));
+        viewport.set_fill_color(Color::TRANSPARENT);
+        viewport.set_outline_color(Color::YELLOW);
+        viewport.set_outline_thickness(1. / scale);
+        target.draw(&viewport);
+
+        target.set_view(camera.view());
+    }
+}
@@ -0,0 +1,101 @@
+use clap::ValueEnum;
+use maze::ui::{Ui, UiInput};
+use sfml::graphics::RenderTarget;
+use sfml::window::Event;
+
+use crate::cli::ThemeArg;
+
+/// Fastest speed the panel goes up to, as a power of 10 steps per second
+const MAX_SPEED_EXPONENT: f32 = 4.;
+const MAX_STEPS_PER_FRAME: f32 = 500.;
+const MAX_HEURISTIC_WEIGHT: f32 = 5.;
+
+/// Window options that can be changed while it is open
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tweaks {
+    /// Steps per second, like `--speed`
+    pub speed: u32,
+    /// Steps taken every frame instead of at `speed`, unless 0
+    pub steps_per_frame: u32,
+    pub theme: ThemeArg,
+    /// Like `--accessible`
+    pub accessible: bool,
+    pub heuristic_weight: f32,
+}
+
+/// Overlay of sliders and switches changing the [`Tweaks`] live, shown and hidden with Tab
+#[derive(Debug, Default)]
+pub struct Panel {
+    pub shown: bool,
+    input: UiInput,
+}
+
+impl Panel {
+    /// Returns `true` if the event was used by the panel, and so should not move the camera
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        self.shown && self.input.handle_event(event)
+    }
+
+    /// Draws the panel over the window if it is shown, applying what the user changed
+    pub fn show(&mut self, target: &mut dyn RenderTarget, tweaks: &mut Tweaks) {
+        if !self.shown {
+            return;
+        }
+
+        let mut ui = Ui::new(&mut self.input);
+
+        let mut speed = (tweaks.speed as f32).log10();
+        if ui.slider("Speed", &mut speed, 0.0..=MAX_SPEED_EXPONENT, |v| {
+            format!("{}/s", speed_of(v))
+        }) {
+            tweaks.speed = speed_of(speed);
+        }
+
+        let mut steps = tweaks.steps_per_frame as f32;
+        if ui.slider(
+            "Steps/frame",
+            &mut steps,
+            0.0..=MAX_STEPS_PER_FRAME,
+            |v| match v.round() as u32 {
+                0 => String::from("by speed"),
+                steps => steps.to_string(),
+            },
+        ) {
+            tweaks.steps_per_frame = steps.round() as u32;
+        }
+
+        let themes = ThemeArg::value_variants();
+        let names: Vec<_> = themes
+            .iter()
+            .filter_map(|theme| theme.to_possible_value())
+            .collect();
+        let names: Vec<_> = names.iter().map(|name| name.get_name()).collect();
+
+        let mut selected = themes
+            .iter()
+            .position(|&theme| theme == tweaks.theme)
+            .unwrap_or(0);
+        if ui.choice("Theme", &mut selected, &names) {
+            tweaks.theme = themes[selected];
+        }
+
+        ui.toggle("Patterns", &mut tweaks.accessible);
+
+        let mut weight = tweaks.heuristic_weight;
+        if ui.slider("A* weight", &mut weight, 0.0..=MAX_HEURISTIC_WEIGHT, |v| {
+            format!("{:.1}", tenths(v))
+        }) {
+            tweaks.heuristic_weight = tenths(weight);
+        }
+
+        ui.draw(target);
+    }
+}
+
+fn speed_of(exponent: f32) -> u32 {
+    10f32.powf(exponent).round().max(1.) as u32
+}
+
+fn tenths(value: f32) -> f32 {
+    (value * 10.).round() / 10.
+}
@@ -0,0 +1,102 @@
+use std::io::{stderr, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+const WIDTH: usize = 30;
+/// How often the bar is redrawn at most
+const REDRAW_EVERY: Duration = Duration::from_millis(100);
+/// Updates between looking at the clock by default, as generation steps take far less time than
+/// reading it
+const CHECK_EVERY: usize = 1024;
+
+/// Bar of the cells carved so far, or whatever else is counted, with an estimate of the time left,
+/// redrawn in place on stderr while working without a window.
+pub struct ProgressBar {
+    start: Instant,
+    last_draw: Option<Instant>,
+    updates: usize,
+    /// What is counted, e.g. `cells`
+    unit: &'static str,
+    check_every: usize,
+}
+
+impl ProgressBar {
+    /// `None` when stderr is not a terminal, where redrawing in place would only fill up logs
+    pub fn stderr() -> Option<Self> {
+        stderr().is_terminal().then(|| Self {
+            start: Instant::now(),
+            last_draw: None,
+            updates: 0,
+            unit: "cells",
+            check_every: CHECK_EVERY,
+        })
+    }
+
+    pub fn with_unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Looks at the clock every `updates` updates instead of every 1024, e.g. every update when
+    /// each takes long
+    pub fn with_check_every(mut self, updates: usize) -> Self {
+        self.check_every = updates.max(1);
+        self
+    }
+
+    pub fn update(&mut self, remaining: usize, total: usize) {
+        self.updates += 1;
+        if !self.updates.is_multiple_of(self.check_every) {
+            return;
+        }
+
+        let now = Instant::now();
+        // Quick generations finish before the first draw and never show a bar
+        let due = match self.last_draw {
+            Some(last) => now - last >= REDRAW_EVERY,
+            None => now - self.start >= REDRAW_EVERY,
+        };
+        if !due {
+            return;
+        }
+        self.last_draw = Some(now);
+
+        let done = total - remaining.min(total);
+        let fraction = done as f64 / total.max(1) as f64;
+        let filled = (fraction * WIDTH as f64) as usize;
+
+        let eta = time_left(now - self.start, done, remaining).unwrap_or_else(|| "?".into());
+
+        eprint!(
+            "\r[{}{}] {:3.0}% {done}/{total} {}, ETA {eta} ",
+            "#".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            fraction * 100.,
+            self.unit,
+        );
+        stderr().flush().ok();
+    }
+
+    /// Clears the bar, if it was ever drawn, for the lines printed after it
+    pub fn finish(self) {
+        if self.last_draw.is_some() {
+            eprint!("\r\x1b[2K");
+            stderr().flush().ok();
+        }
+    }
+}
+
+/// Time `remaining` more units will take at the rate `done` took `elapsed`, e.g. `1m05s`. `None`
+/// before anything is done to go by.
+pub fn time_left(elapsed: Duration, done: usize, remaining: usize) -> Option<String> {
+    if done == 0 {
+        return None;
+    }
+
+    let seconds = (elapsed.as_secs_f64() * remaining as f64 / done as f64).round() as u64;
+
+    Some(match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds / 60 % 60),
+    })
+}
@@ -0,0 +1,202 @@
+//! Python bindings, built with `maturin build`, which leaves out the `gui` feature so the extension
+//! does not need CSFML. Cells come out as a copy in raw bytes, which numpy can read without
+//! copying again, e.g. `numpy.frombuffer(maze.cells(), numpy.uint8).reshape(maze.shape)`.
+
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::analysis;
+use crate::consts::DEFAULT_CAVE_FILL;
+use crate::maze::generators::{Cave, RandomDFS, Wilson};
+use crate::maze::solvers;
+use crate::maze::{Maze, MazeGenerator};
+use crate::Direction;
+
+fn parse_direction(name: &str) -> PyResult<Direction> {
+    match name {
+        "up" => Ok(Direction::UP),
+        "right" => Ok(Direction::RIGHT),
+        "down" => Ok(Direction::DOWN),
+        "left" => Ok(Direction::LEFT),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown direction `{name}`, expected up, right, down or left"
+        ))),
+    }
+}
+
+#[pyclass(name = "Maze", module = "maze")]
+#[derive(Clone)]
+pub struct PyMaze {
+    maze: Maze,
+}
+
+impl PyMaze {
+    fn check_cell(&self, (x, y): (usize, usize)) -> PyResult<()> {
+        let (width, height) = self.maze.get_bounds();
+
+        if x >= width || y >= height {
+            return Err(PyIndexError::new_err(format!(
+                "({x}, {y}) is outside of the maze"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl PyMaze {
+    /// An empty maze, with every wall closed
+    #[new]
+    fn new(width: u16, height: u16) -> PyResult<Self> {
+        if width == 0 || height == 0 {
+            return Err(PyValueError::new_err("maze size must not be 0"));
+        }
+
+        Ok(Self {
+            maze: Maze::new(width, height),
+        })
+    }
+
+    /// Reads the native `.dat` format
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Maze::from_data(data)
+            .map(|maze| Self { maze })
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Writes the native `.dat` format
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = crate::formats::dat::encode(&self.maze).map_err(PyValueError::new_err)?;
+
+        Ok(PyBytes::new(py, &data))
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.maze.get_bounds().0
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.maze.get_bounds().1
+    }
+
+    /// `(height, width)`, the numpy shape of [`PyMaze::cells`]
+    #[getter]
+    fn shape(&self) -> (usize, usize) {
+        let (width, height) = self.maze.get_bounds();
+
+        (height, width)
+    }
+
+    /// A copy of the cells, one byte each in row-major order, each a bitmask of open directions:
+    /// up 1, right 2, down 4, left 8
+    fn cells<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        let cells: Vec<u8> = self.maze.cells().map(|(_, _, cell)| cell.bits()).collect();
+
+        PyBytes::new(py, &cells)
+    }
+
+    fn get(&self, x: usize, y: usize) -> PyResult<u8> {
+        self.check_cell((x, y))?;
+
+        Ok(self.maze.get(x, y).bits())
+    }
+
+    /// Opens the wall between a cell and its neighbor
+    fn carve(&mut self, x: usize, y: usize, direction: &str) -> PyResult<()> {
+        self.check_cell((x, y))?;
+        self.maze.carve(x, y, parse_direction(direction)?);
+
+        Ok(())
+    }
+
+    /// Closes the wall between a cell and its neighbor
+    fn uncarve(&mut self, x: usize, y: usize, direction: &str) -> PyResult<()> {
+        self.check_cell((x, y))?;
+        self.maze.uncarve(x, y, parse_direction(direction)?);
+
+        Ok(())
+    }
+
+    /// Replaces the maze with a freshly generated one of the same size
+    #[pyo3(signature = (generator = "wilson"))]
+    fn generate(&mut self, generator: &str) -> PyResult<()> {
+        let bounds = self.maze.get_bounds();
+        self.maze = Maze::new(bounds.0 as u16, bounds.1 as u16);
+
+        match generator {
+            "wilson" => {
+                let mut generator = Wilson::new(bounds);
+                while !generator.step(&mut self.maze).is_done() {}
+            }
+            "random-dfs" => {
+                let mut generator = RandomDFS::new(bounds);
+                while !generator.step(&mut self.maze).is_done() {}
+            }
+            "cave" => {
+                let mut generator = Cave::new(bounds, DEFAULT_CAVE_FILL);
+                while !generator.step(&mut self.maze).is_done() {}
+            }
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown generator `{generator}`, expected wilson, random-dfs or cave"
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Path of cells from `start` to `end`, by default the top left and bottom right corners.
+    /// Empty if `end` cannot be reached.
+    #[pyo3(signature = (algorithm = "bfs", start = None, end = None))]
+    fn solve(
+        &self,
+        algorithm: &str,
+        start: Option<(usize, usize)>,
+        end: Option<(usize, usize)>,
+    ) -> PyResult<Vec<(usize, usize)>> {
+        let bounds = self.maze.get_bounds();
+        let start = start.unwrap_or((0, 0));
+        let end = end.unwrap_or((bounds.0 - 1, bounds.1 - 1));
+
+        self.check_cell(start)?;
+        self.check_cell(end)?;
+
+        let path = match algorithm {
+            "bfs" => solvers::bfs(&self.maze, start, end),
+            "dfs" => solvers::dfs(&self.maze, start, end),
+            "a-star" => solvers::a_star(&self.maze, start, end),
+            "dijkstra" => solvers::dijkstra(&self.maze, start, end),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown algorithm `{algorithm}`, expected bfs, dfs, a-star or dijkstra"
+                )))
+            }
+        };
+
+        Ok(path.unwrap_or_default())
+    }
+
+    /// Steps from `start` to every cell in row-major order, `None` where unreachable
+    fn distance_map(&self, start: (usize, usize)) -> PyResult<Vec<Option<usize>>> {
+        self.check_cell(start)?;
+
+        Ok(analysis::distance_map(&self.maze, start))
+    }
+
+    fn __repr__(&self) -> String {
+        let (width, height) = self.maze.get_bounds();
+
+        format!("Maze({width}, {height})")
+    }
+}
+
+#[pymodule]
+fn maze(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMaze>()
+}
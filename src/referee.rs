@@ -0,0 +1,169 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use maze::formats::json;
+use maze::json::Json;
+use maze::maze::agents::Agent;
+use maze::maze::Maze;
+
+/// An external solver program, racing like any other [`Agent`].
+///
+/// The program gets the maze as a single line of JSON on stdin, in the json format with `start`
+/// and `end` cells added, after which stdin is closed. It answers with one move per line on
+/// stdout, `U`, `R`, `D` or `L`. An illegal or late move, or exiting before the end is reached,
+/// disqualifies it. Moves are polled for, so waiting on them never holds up the window.
+pub struct ExternalAgent {
+    command: String,
+    child: Child,
+    moves: Receiver<String>,
+    move_timeout: Duration,
+    /// Set once the program is waited on for its next move
+    deadline: Option<Instant>,
+
+    position: (usize, usize),
+    failure: Option<String>,
+}
+
+impl ExternalAgent {
+    /// `command` is split on whitespace into the program and its arguments
+    pub fn spawn(
+        command: &str,
+        maze: &Maze,
+        start: (usize, usize),
+        end: (usize, usize),
+        move_timeout: Duration,
+    ) -> Result<Self, String> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| String::from("empty solver command"))?;
+
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Could not start {command}: {err}"))?;
+
+        let mut request = json::encode(maze, None);
+        if let Json::Object(entries) = &mut request {
+            let point = |(x, y): (usize, usize)| Json::Array(vec![x.into(), y.into()]);
+
+            entries.push(("start".into(), point(start)));
+            entries.push(("end".into(), point(end)));
+        }
+
+        // Dropping stdin closes it, so programs reading to the end see the whole maze. Programs
+        // that exit without reading it are judged on their moves like any other.
+        let mut stdin = child.stdin.take().unwrap();
+        let _ = writeln!(stdin, "{request}");
+        drop(stdin);
+
+        let stdout = child.stdout.take().unwrap();
+        let (sender, moves) = channel();
+
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            command: command.to_string(),
+            child,
+            moves,
+            move_timeout,
+            deadline: None,
+
+            position: start,
+            failure: None,
+        })
+    }
+
+    fn disqualify(&mut self, reason: String) {
+        self.failure = Some(reason);
+
+        let _ = self.child.kill();
+    }
+}
+
+impl Agent for ExternalAgent {
+    fn name(&self) -> &str {
+        &self.command
+    }
+
+    fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// The program has already seen the maze, so it cannot take part again
+    fn reset(&mut self, _bounds: (usize, usize), _start: (usize, usize)) {
+        if self.failure.is_none() {
+            self.disqualify(String::from("cannot restart on a new maze"));
+        }
+    }
+
+    fn step(&mut self, maze: &Maze) {
+        if self.failure.is_some() {
+            return;
+        }
+
+        let line = loop {
+            match self.moves.try_recv() {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => break line,
+                Err(TryRecvError::Empty) => {
+                    let deadline = *self
+                        .deadline
+                        .get_or_insert_with(|| Instant::now() + self.move_timeout);
+
+                    if Instant::now() >= deadline {
+                        self.deadline = None;
+                        self.disqualify(format!(
+                            "took longer than {:?} to move",
+                            self.move_timeout
+                        ));
+                    }
+                    return;
+                }
+                Err(TryRecvError::Disconnected) => {
+                    self.deadline = None;
+                    return self.disqualify(String::from("stopped before reaching the exit"));
+                }
+            }
+        };
+        self.deadline = None;
+
+        let line = line.trim();
+        if line.chars().count() != 1 {
+            return self.disqualify(format!("sent `{line}`, which is not one of U, R, D, L"));
+        }
+
+        match maze.replay_solution(self.position, line) {
+            Ok((next, _)) => self.position = next,
+            Err((_, reason)) => self.disqualify(reason),
+        }
+    }
+
+    fn is_waiting(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+}
+
+impl Drop for ExternalAgent {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,497 @@
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use maze::json::Json;
+use maze::maze::agents::Race;
+use maze::maze::generators::{Generator, OriginShift};
+use maze::maze::solvers::{smooth, Algorithm, TieBreak};
+use maze::maze::{GenStep, GenerationProgress, Maze, SolveStep};
+use sfml::graphics::Drawable;
+
+use crate::cli::AlgorithmArg;
+use crate::progress::ProgressBar;
+use crate::trace::Trace;
+
+pub struct PhaseStats {
+    pub steps: usize,
+    pub duration: Duration,
+}
+
+impl PhaseStats {
+    pub fn to_json(&self) -> Vec<(&'static str, Json)> {
+        vec![
+            ("steps", self.steps.into()),
+            ("time_ms", (self.duration.as_secs_f64() * 1000.).into()),
+        ]
+    }
+}
+
+/// Events kept between [`Run::take_events`] calls at most
+pub const EVENT_LIMIT: usize = 4096;
+
+/// What a step did, e.g. to play a sound for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    /// The generator wrote this cell
+    Carve((usize, usize)),
+    /// The solver moved to this cell
+    Visit((usize, usize)),
+}
+
+fn push_events(events: &mut Option<Vec<StepEvent>>, new: impl IntoIterator<Item = StepEvent>) {
+    if let Some(events) = events {
+        let room = EVENT_LIMIT.saturating_sub(events.len());
+        events.extend(new.into_iter().take(room));
+    }
+}
+
+/// Times one phase, which may be advanced a step at a time across frames
+#[derive(Default)]
+struct PhaseTimer {
+    steps: usize,
+    start: Option<Instant>,
+}
+
+impl PhaseTimer {
+    fn step(&mut self) {
+        self.start.get_or_insert_with(Instant::now);
+        self.steps += 1;
+    }
+
+    fn stats(&self) -> PhaseStats {
+        PhaseStats {
+            steps: self.steps,
+            duration: self.start.map_or(Duration::ZERO, |start| start.elapsed()),
+        }
+    }
+}
+
+/// A maze being generated and then solved, shared by the windowed and headless commands
+pub struct Run {
+    pub maze: Maze,
+
+    generator: Option<Generator>,
+    generated: bool,
+    generation: PhaseTimer,
+    pub generation_stats: Option<PhaseStats>,
+    /// Dead-end retraction passes made once generation is done
+    sparsify: usize,
+    /// Share of the cells after which generation stops, leaving the rest solid
+    fill: Option<f32>,
+
+    solver: Option<Algorithm>,
+    /// What the solver was made from, if known, so [`Run::next_algorithm`] can move on from it
+    algorithm: Option<AlgorithmArg>,
+    /// How A* breaks ties, for solvers made by [`Run::next_algorithm`] too
+    tie_break: TieBreak,
+    /// Weight of the A* heuristic, likewise
+    heuristic_weight: f32,
+    /// Steps the solver's visited cells take to fade out, if they do
+    trail: Option<u32>,
+    /// Straighten solutions once found, see [`smooth`]
+    smooth: bool,
+    /// Shift the maze origin every N solver steps
+    dynamic: Option<NonZeroUsize>,
+    /// Created once generation is done, since it needs the finished maze
+    shifter: Option<OriginShift>,
+    solve: PhaseTimer,
+    pub solve_stats: Option<PhaseStats>,
+    pub solution: Option<Vec<(usize, usize)>>,
+    /// The solver found no way to the end
+    unsolvable: bool,
+
+    /// Agents racing to the exit once the maze is generated
+    race: Option<Race>,
+
+    /// Where every generator and solver step is written, if anywhere
+    trace: Option<Trace>,
+    /// Shown while [`Run::finish_generation`] runs, then dropped
+    progress: Option<ProgressBar>,
+    /// Kept from [`Run::record_events`] on, see [`Run::take_events`]
+    events: Option<Vec<StepEvent>>,
+}
+
+impl Run {
+    /// Without a generator the maze is taken as already generated
+    pub fn new(maze: Maze, generator: Option<Generator>, solver: Option<Algorithm>) -> Self {
+        let mut run = Self {
+            maze,
+
+            generated: generator.is_none(),
+            generator,
+            generation: PhaseTimer::default(),
+            generation_stats: None,
+            sparsify: 0,
+            fill: None,
+
+            solver,
+            algorithm: None,
+            tie_break: TieBreak::default(),
+            heuristic_weight: 1.,
+            trail: None,
+            smooth: false,
+            dynamic: None,
+            shifter: None,
+            solve: PhaseTimer::default(),
+            solve_stats: None,
+            solution: None,
+            unsolvable: false,
+
+            race: None,
+
+            trace: None,
+            progress: None,
+            events: None,
+        };
+
+        run.seed_solver();
+        run
+    }
+
+    pub fn with_dynamic(mut self, every: Option<NonZeroUsize>) -> Self {
+        self.dynamic = every;
+        self
+    }
+
+    pub fn with_sparsify(mut self, iterations: usize) -> Self {
+        self.sparsify = iterations;
+        self
+    }
+
+    pub fn with_fill(mut self, fill: Option<f32>) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn with_algorithm(mut self, algorithm: Option<AlgorithmArg>) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Applies to the solver the run was made with as well
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.solver = self.solver.map(|solver| solver.with_tie_break(tie_break));
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Applies to the solver the run was made with as well
+    pub fn with_heuristic_weight(mut self, weight: f32) -> Self {
+        self.set_heuristic_weight(weight);
+        self
+    }
+
+    /// Takes effect on the solver mid-solve, without starting it over
+    pub fn set_heuristic_weight(&mut self, weight: f32) {
+        if let Some(solver) = &mut self.solver {
+            solver.set_heuristic_weight(weight);
+        }
+        self.heuristic_weight = weight;
+    }
+
+    pub fn heuristic_weight(&self) -> f32 {
+        self.heuristic_weight
+    }
+
+    /// Applies to the solver the run was made with as well, see [`Algorithm::set_trail`]
+    pub fn with_trail(mut self, length: Option<u32>) -> Self {
+        let bounds = self.maze.get_bounds();
+        if let Some(solver) = &mut self.solver {
+            solver.set_trail(bounds, length);
+        }
+        self.trail = length;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    pub fn with_race(mut self, race: Option<Race>) -> Self {
+        self.race = race;
+        self
+    }
+
+    pub fn with_trace(mut self, trace: Option<Trace>) -> Self {
+        if trace.is_some() {
+            self.maze.record_changes();
+        }
+
+        self.trace = trace;
+        self
+    }
+
+    pub fn with_progress(mut self, progress: Option<ProgressBar>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Starts keeping what every step does until taken by [`Run::take_events`]
+    pub fn record_events(&mut self) {
+        if self.events.is_none() {
+            self.maze.record_changes();
+            self.events = Some(vec![]);
+        }
+    }
+
+    /// Returns what the steps since the last call did, oldest first. Past [`EVENT_LIMIT`] events
+    /// between calls, later ones are dropped.
+    pub fn take_events(&mut self) -> Vec<StepEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Stops tracing, handing back the trace to be finished
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    pub fn generator(&self) -> Option<&Generator> {
+        self.generator.as_ref()
+    }
+
+    pub fn solver(&self) -> Option<&Algorithm> {
+        self.solver.as_ref()
+    }
+
+    /// The solution, if any, when it was straightened after solving and so differs from the path
+    /// the solver draws
+    pub fn smoothed_solution(&self) -> Option<&[(usize, usize)]> {
+        self.solution.as_deref().filter(|_| self.smooth)
+    }
+
+    pub fn race(&self) -> Option<&Race> {
+        self.race.as_ref()
+    }
+
+    pub fn is_generated(&self) -> bool {
+        self.generated
+    }
+
+    pub fn is_unsolvable(&self) -> bool {
+        self.unsolvable
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.generated
+            && (self.solver.is_none() || self.solution.is_some() || self.unsolvable)
+            && self.race.as_ref().is_none_or(Race::is_done)
+    }
+
+    /// Reverts the last edit to the maze, once generating it is done
+    pub fn undo(&mut self) -> bool {
+        self.generated && self.maze.undo()
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.generated && self.maze.redo()
+    }
+
+    /// Clears the maze and starts generating a new one from a new seed, solving it again after.
+    /// Returns `false` without a generator.
+    pub fn regenerate(&mut self) -> bool {
+        let Some(generator) = &mut self.generator else {
+            return false;
+        };
+
+        generator.reset(self.maze.get_bounds());
+        self.maze.clear();
+        // Clearing is not a step of the new maze
+        self.maze.take_changes();
+
+        self.generated = false;
+        self.generation = PhaseTimer::default();
+        self.generation_stats = None;
+
+        self.restart_solve();
+        true
+    }
+
+    /// Solves the maze again from scratch with the algorithm after the current one, in the order
+    /// of `--alg`, and returns it
+    pub fn next_algorithm(&mut self) -> AlgorithmArg {
+        let algorithms = AlgorithmArg::value_variants();
+        let next = match self.algorithm {
+            Some(current) => {
+                let i = algorithms.iter().position(|v| *v == current).unwrap();
+                algorithms[(i + 1) % algorithms.len()]
+            }
+            None => algorithms[0],
+        };
+
+        let bounds = self.maze.get_bounds();
+        let mut solver = next
+            .solver(bounds, (0, 0), (bounds.0 - 1, bounds.1 - 1))
+            .with_tie_break(self.tie_break)
+            .with_heuristic_weight(self.heuristic_weight);
+        solver.set_trail(bounds, self.trail);
+        self.solver = Some(solver);
+        self.algorithm = Some(next);
+
+        self.restart_solve();
+        next
+    }
+
+    /// The seed of the maze, from its generator or else what it was saved with
+    fn seed(&self) -> Option<u64> {
+        match &self.generator {
+            Some(generator) => Some(generator.seed()),
+            None => self.maze.metadata().seed(),
+        }
+    }
+
+    /// Seeds the solver's random choices from the maze's seed, so a maze is solved the same way
+    /// whenever it is made again
+    fn seed_solver(&mut self) {
+        if let (Some(seed), Some(solver)) = (self.seed(), &mut self.solver) {
+            solver.set_seed(seed);
+        }
+    }
+
+    fn restart_solve(&mut self) {
+        let bounds = self.maze.get_bounds();
+
+        self.seed_solver();
+        if let Some(solver) = &mut self.solver {
+            solver.reset(bounds);
+        }
+        if let Some(race) = &mut self.race {
+            race.reset(bounds);
+        }
+
+        self.shifter = None;
+        self.solve = PhaseTimer::default();
+        self.solve_stats = None;
+        self.solution = None;
+        self.unsolvable = false;
+    }
+
+    /// Advances generation, or solving once the maze is generated, by one step
+    pub fn step(&mut self) {
+        if !self.generated {
+            let Some(generator) = &mut self.generator else {
+                return;
+            };
+
+            self.generation.step();
+
+            let mut step = generator.step(&mut self.maze);
+            if let GenStep::InProgress { remaining, total } = step {
+                let done = total - remaining.min(total);
+                if self
+                    .fill
+                    .is_some_and(|fill| done as f32 >= fill * total as f32)
+                {
+                    step = GenStep::Done;
+                }
+            }
+            self.generated = step.is_done();
+
+            if self.generated {
+                self.maze.sparsify(self.sparsify);
+                self.generation_stats = Some(self.generation.stats());
+            }
+
+            let cells = self.maze.take_changes();
+            push_events(
+                &mut self.events,
+                cells.iter().map(|&cell| StepEvent::Carve(cell)),
+            );
+
+            if let Some(trace) = &mut self.trace {
+                trace.generation_step(self.generation.steps, &cells, step);
+            }
+        } else if let Some(race) = self.race.as_mut().filter(|race| !race.is_done()) {
+            race.step(&self.maze);
+        } else if self.solution.is_none() && !self.unsolvable {
+            let Some(solver) = &mut self.solver else {
+                return;
+            };
+
+            self.solve.step();
+
+            let step = solver.step(&self.maze);
+            let found = match step {
+                SolveStep::Found(path) => Some(path.to_vec()),
+                _ => None,
+            };
+            self.unsolvable = step == SolveStep::Unsolvable;
+
+            push_events(&mut self.events, solver.current().map(StepEvent::Visit));
+
+            if let Some(trace) = &mut self.trace {
+                let step = match &found {
+                    Some(path) => SolveStep::Found(path),
+                    None if self.unsolvable => SolveStep::Unsolvable,
+                    None => SolveStep::InProgress,
+                };
+
+                trace.solve_step(self.solve.steps, solver.current(), solver.memory(), step);
+            }
+
+            if found.is_some() || self.unsolvable {
+                self.solution = match self.smooth {
+                    true => found.map(|path| smooth(&self.maze, &path)),
+                    false => found,
+                };
+                self.solve_stats = Some(self.solve.stats());
+                return;
+            }
+
+            if let Some(every) = self.dynamic {
+                if self.solve.steps.is_multiple_of(every.get()) {
+                    let seed = self.seed();
+
+                    self.shifter
+                        .get_or_insert_with(|| match seed {
+                            Some(seed) => OriginShift::with_seed(&self.maze, seed),
+                            None => OriginShift::new(&self.maze),
+                        })
+                        .mutate(&mut self.maze);
+                }
+            }
+        }
+    }
+
+    pub fn finish_generation(&mut self) -> Option<&PhaseStats> {
+        while !self.generated {
+            self.step();
+
+            if let (Some(progress), Some(generator)) = (&mut self.progress, &self.generator) {
+                progress.update(generator.remaining(), generator.total());
+            }
+        }
+
+        if let Some(progress) = self.progress.take() {
+            progress.finish();
+        }
+
+        self.generation_stats.as_ref()
+    }
+
+    pub fn finish_solve(&mut self) -> Option<&PhaseStats> {
+        self.finish_generation();
+
+        while !self.is_done() {
+            self.step();
+        }
+
+        self.solve_stats.as_ref()
+    }
+
+    /// What is currently being worked on, to draw over the maze
+    pub fn overlay(&self) -> Option<&dyn Drawable> {
+        if !self.generated {
+            self.generator.as_ref().map(|v| v as &dyn Drawable)
+        } else if let Some(race) = &self.race {
+            Some(race as &dyn Drawable)
+        } else {
+            self.solver.as_ref().map(|v| v as &dyn Drawable)
+        }
+    }
+
+    pub fn shifter(&self) -> Option<&OriginShift> {
+        self.shifter.as_ref().filter(|_| self.generated)
+    }
+}
@@ -0,0 +1,406 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use clap::ValueEnum;
+use maze::consts::MIN_MAZE_SIZE;
+use maze::formats;
+use maze::json::Json;
+use maze::maze::generators::random_seed;
+use maze::maze::tags::{Tag, LOCK_COLORS};
+use maze::maze::Maze;
+use maze::Direction;
+
+use crate::cli::{AlgorithmArg, GeneratorArg};
+use crate::run::Run;
+use crate::viewer::Control;
+
+/// Largest width and height a client can reset the maze to, so one request can not take up all
+/// the memory
+const MAX_SIZE: u16 = 1024;
+
+/// A line of the protocol, e.g. `{"command": "carve", "x": 0, "y": 0, "direction": "right"}`
+enum Request {
+    Carve((usize, usize), Direction),
+    Uncarve((usize, usize), Direction),
+    /// With the [lock color](maze::maze::Maze::lock_color) of a key or door, if given
+    Tag((usize, usize), Tag, Option<u8>),
+    Untag((usize, usize), Tag),
+    Undo,
+    Redo,
+    /// Restarts solving from the top left to the bottom right corner
+    Solve(AlgorithmArg),
+    /// Clears the maze and generates a new one
+    Generate(GeneratorArg),
+    /// Clears the maze, optionally resizing it
+    Reset(Option<(u16, u16)>),
+    Export {
+        format: Option<String>,
+        path: String,
+        solution: bool,
+    },
+}
+
+impl Request {
+    fn parse(line: &str) -> Result<Self, String> {
+        let json = Json::parse(line)?;
+
+        let field = |key: &str| json.get(key).filter(|v| **v != Json::Null);
+        let string = |key: &str| {
+            field(key)
+                .map(|v| {
+                    v.as_str()
+                        .ok_or_else(|| format!("`{key}` must be a string"))
+                })
+                .transpose()
+        };
+        let position = || {
+            let coordinate = |key: &str| {
+                field(key)
+                    .and_then(Json::as_usize)
+                    .ok_or_else(|| format!("`{key}` must be a cell coordinate"))
+            };
+
+            Ok::<_, String>((coordinate("x")?, coordinate("y")?))
+        };
+        let direction = || match string("direction")? {
+            Some("up") => Ok(Direction::UP),
+            Some("right") => Ok(Direction::RIGHT),
+            Some("down") => Ok(Direction::DOWN),
+            Some("left") => Ok(Direction::LEFT),
+            _ => Err(String::from(
+                "`direction` must be one of up, right, down, left",
+            )),
+        };
+
+        let tag = || {
+            let name = string("tag")?.ok_or_else(|| String::from("`tag` is required"))?;
+
+            Tag::from_name(name).ok_or_else(|| {
+                let names: Vec<_> = Tag::ALL.iter().map(Tag::name).collect();
+                format!("`tag` must be one of {}", names.join(", "))
+            })
+        };
+
+        match string("command")? {
+            Some("carve") => Ok(Request::Carve(position()?, direction()?)),
+            Some("uncarve") => Ok(Request::Uncarve(position()?, direction()?)),
+            Some("tag") => {
+                let tag = tag()?;
+                let color = field("color")
+                    .map(|v| {
+                        v.as_usize()
+                            .filter(|&v| v < LOCK_COLORS as usize)
+                            .map(|v| v as u8)
+                            .ok_or_else(|| format!("`color` must be below {LOCK_COLORS}"))
+                    })
+                    .transpose()?;
+
+                if color.is_some() && !matches!(tag, Tag::Key | Tag::Door) {
+                    return Err(String::from("`color` is only for keys and doors"));
+                }
+
+                Ok(Request::Tag(position()?, tag, color))
+            }
+            Some("untag") => Ok(Request::Untag(position()?, tag()?)),
+            Some("undo") => Ok(Request::Undo),
+            Some("redo") => Ok(Request::Redo),
+            Some("solve") => {
+                let algorithm = match string("algorithm")? {
+                    Some(name) => AlgorithmArg::from_str(name, true)
+                        .map_err(|_| format!("unknown algorithm `{name}`"))?,
+                    None => AlgorithmArg::DFS,
+                };
+
+                Ok(Request::Solve(algorithm))
+            }
+            Some("generate") => {
+                let generator = match string("generator")? {
+                    Some(name) => GeneratorArg::from_str(name, true)
+                        .map_err(|_| format!("unknown generator `{name}`"))?,
+                    None => GeneratorArg::Wilson,
+                };
+
+                Ok(Request::Generate(generator))
+            }
+            Some("reset") => {
+                let sizes = MIN_MAZE_SIZE as usize..=MAX_SIZE as usize;
+                let dimension = |key: &str| {
+                    field(key)
+                        .map(|v| {
+                            v.as_usize()
+                                .filter(|v| sizes.contains(v))
+                                .map(|v| v as u16)
+                                .ok_or_else(|| {
+                                    format!(
+                                        "`{key}` must be between {MIN_MAZE_SIZE} and {MAX_SIZE}"
+                                    )
+                                })
+                        })
+                        .transpose()
+                };
+
+                match (dimension("width")?, dimension("height")?) {
+                    (Some(width), Some(height)) => Ok(Request::Reset(Some((width, height)))),
+                    (None, None) => Ok(Request::Reset(None)),
+                    _ => Err(String::from("`width` and `height` must be given together")),
+                }
+            }
+            Some("export") => Ok(Request::Export {
+                format: string("format")?.map(String::from),
+                path: string("path")?
+                    .ok_or_else(|| String::from("`path` is required"))?
+                    .to_string(),
+                solution: field("solution").and_then(Json::as_bool).unwrap_or(false),
+            }),
+            Some(command) => Err(format!("unknown command `{command}`")),
+            None => Err(String::from("`command` is required")),
+        }
+    }
+
+    /// Points an export at where the client may write, see [`Exports`]
+    fn confine(self, exports: &Exports) -> Result<Self, String> {
+        match self {
+            Request::Export {
+                format,
+                path,
+                solution,
+            } => Ok(Request::Export {
+                format,
+                path: exports.resolve(&path)?,
+                solution,
+            }),
+            request => Ok(request),
+        }
+    }
+
+    /// Returns `true` if the run was replaced
+    fn apply(self, run: &mut Run) -> Result<bool, String> {
+        let bounds = run.maze.get_bounds();
+
+        if let Request::Carve((x, y), _)
+        | Request::Uncarve((x, y), _)
+        | Request::Tag((x, y), _, _)
+        | Request::Untag((x, y), _) = self
+        {
+            if x >= bounds.0 || y >= bounds.1 {
+                return Err(format!("({x}, {y}) is outside of the maze"));
+            }
+        }
+
+        match self {
+            Request::Carve((x, y), direction) => {
+                run.maze.carve(x, y, direction);
+                Ok(false)
+            }
+            Request::Uncarve((x, y), direction) => {
+                run.maze.uncarve(x, y, direction);
+                Ok(false)
+            }
+            Request::Tag((x, y), tag, color) => {
+                run.maze.tag(x, y, tag);
+                if let Some(color) = color {
+                    run.maze.set_lock_color(x, y, color)?;
+                }
+                Ok(false)
+            }
+            Request::Untag((x, y), tag) => {
+                run.maze.untag(x, y, tag);
+                Ok(false)
+            }
+            Request::Undo => match run.undo() {
+                true => Ok(false),
+                false => Err(String::from("nothing to undo")),
+            },
+            Request::Redo => match run.redo() {
+                true => Ok(false),
+                false => Err(String::from("nothing to redo")),
+            },
+            Request::Solve(algorithm) => {
+                let solver = algorithm.solver(bounds, (0, 0), (bounds.0 - 1, bounds.1 - 1));
+
+                *run =
+                    Run::new(run.maze.clone(), None, Some(solver)).with_algorithm(Some(algorithm));
+                Ok(true)
+            }
+            Request::Generate(generator) => {
+                let generator = generator.generator(bounds, random_seed());
+
+                let mut maze = Maze::new(bounds.0 as u16, bounds.1 as u16);
+                maze.record_history();
+
+                *run = Run::new(maze, Some(generator), None);
+                Ok(true)
+            }
+            Request::Reset(size) => {
+                let (width, height) = size.unwrap_or((bounds.0 as u16, bounds.1 as u16));
+
+                let mut maze = Maze::new(width, height);
+                maze.record_history();
+
+                *run = Run::new(maze, None, None);
+                Ok(true)
+            }
+            Request::Export {
+                format,
+                path,
+                solution,
+            } => {
+                let format = match &format {
+                    Some(name) => {
+                        formats::by_name(name).ok_or_else(|| format!("unknown format `{name}`"))?
+                    }
+                    None => formats::for_path(&path)
+                        .ok_or_else(|| format!("could not tell the format of {path}"))?,
+                };
+
+                let solution = run.solution.as_deref().filter(|_| solution);
+                format.write(&run.maze, solution, &path)?;
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// Where a client's exports may be written
+#[derive(Clone)]
+enum Exports {
+    /// Anywhere, for the user on stdin
+    Anywhere,
+    /// Only by relative paths inside the directory, for TCP clients, or nowhere without one
+    Inside(Option<PathBuf>),
+}
+
+impl Exports {
+    fn resolve(&self, path: &str) -> Result<String, String> {
+        let dir = match self {
+            Exports::Anywhere => return Ok(path.to_string()),
+            Exports::Inside(Some(dir)) => dir,
+            Exports::Inside(None) => {
+                return Err(String::from("exporting over TCP needs --export-dir"))
+            }
+        };
+
+        let inside = Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !inside {
+            return Err(String::from(
+                "`path` must be relative and stay inside the export directory",
+            ));
+        }
+
+        Ok(dir.join(path).to_string_lossy().into_owned())
+    }
+}
+
+/// A request and where to send its reply
+type Message = (Request, Sender<Json>);
+
+/// Applies requests read from stdin or TCP clients to the run, one JSON object per line each
+/// way. Every request gets `{"ok": true}` or `{"ok": false, "error": "..."}` back.
+pub struct Server {
+    requests: Receiver<Message>,
+}
+
+impl Server {
+    /// Reads requests from stdin and replies on stdout
+    pub fn stdin() -> Self {
+        let (sender, requests) = channel();
+
+        thread::spawn(move || serve(io::stdin().lock(), io::stdout(), sender, Exports::Anywhere));
+
+        Self { requests }
+    }
+
+    /// Accepts any number of clients on `address`, which can only export into `export_dir`
+    pub fn listen(address: &str, export_dir: Option<&str>) -> Result<Self, String> {
+        let listener = TcpListener::bind(address).map_err(|err| format!("{address}: {err}"))?;
+        let (sender, requests) = channel();
+        let exports = Exports::Inside(export_dir.map(PathBuf::from));
+
+        eprintln!(
+            "Listening on {}",
+            listener
+                .local_addr()
+                .map_or(address.to_string(), |v| v.to_string())
+        );
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                let exports = exports.clone();
+
+                thread::spawn(move || {
+                    if let Ok(reader) = stream.try_clone() {
+                        serve(BufReader::new(reader), stream, sender, exports);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { requests })
+    }
+}
+
+/// Forwards each line to the window and waits for its reply, until the input or window closes
+fn serve(input: impl BufRead, mut output: impl Write, sender: Sender<Message>, exports: Exports) {
+    for line in input.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match Request::parse(&line).and_then(|request| request.confine(&exports)) {
+            Ok(request) => {
+                let (reply_sender, reply) = channel();
+
+                if sender.send((request, reply_sender)).is_err() {
+                    break;
+                }
+
+                match reply.recv() {
+                    Ok(v) => v,
+                    Err(_) => break,
+                }
+            }
+            Err(err) => error_reply(err),
+        };
+
+        if writeln!(output, "{reply}")
+            .and_then(|_| output.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+fn error_reply(err: String) -> Json {
+    Json::object([("ok", false.into()), ("error", err.into())])
+}
+
+impl Control for Server {
+    fn update(&mut self, run: &mut Run) -> bool {
+        let mut replaced = false;
+
+        while let Ok((request, reply)) = self.requests.try_recv() {
+            let result = match request.apply(run) {
+                Ok(v) => {
+                    replaced |= v;
+                    Json::object([("ok", true.into())])
+                }
+                Err(err) => error_reply(err),
+            };
+
+            let _ = reply.send(result);
+        }
+
+        replaced
+    }
+}
@@ -0,0 +1,256 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::config::{Config, ConfigValue};
+
+const SETTINGS_FILE: &str = "settings.toml";
+/// Recent maze files kept, one per number key
+const RECENT_LIMIT: usize = 9;
+
+/// Options last used in a window and the maze files opened or saved most recently, kept between
+/// runs in the platform's config directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    /// Solving algorithm, as given to `--alg`
+    pub alg: Option<String>,
+    pub size: Option<(u16, u16)>,
+    /// As given to `--theme`
+    pub theme: Option<String>,
+    /// Screen position of the last window closed
+    pub position: Option<(i32, i32)>,
+    /// Absolute paths, most recent first
+    pub recent: Vec<String>,
+}
+
+/// Settings in use, with whether they changed since loaded
+#[derive(Debug)]
+struct Loaded {
+    settings: Settings,
+    changed: bool,
+}
+
+/// `None` until loaded, and for good with `--no-settings`
+static SETTINGS: Mutex<Option<Loaded>> = Mutex::new(None);
+
+impl Settings {
+    fn from_config(config: &Config) -> Self {
+        let pair = |key| match config.get(key) {
+            Some(ConfigValue::Array(values)) => match values.as_slice() {
+                [ConfigValue::Number(x), ConfigValue::Number(y)] => {
+                    x.parse().ok().zip(y.parse().ok())
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let recent = match config.get("recent") {
+            Some(ConfigValue::Array(values)) => values
+                .iter()
+                .filter_map(|value| match value {
+                    ConfigValue::String(path) => Some(path.clone()),
+                    _ => None,
+                })
+                .take(RECENT_LIMIT)
+                .collect(),
+            _ => vec![],
+        };
+
+        Self {
+            alg: string(config, "alg"),
+            size: number(config, "width").zip(number(config, "height")),
+            theme: string(config, "theme"),
+            position: pair("position"),
+            recent,
+        }
+    }
+
+    fn to_toml(&self) -> String {
+        let mut lines = vec![];
+
+        if let Some(alg) = &self.alg {
+            lines.push(format!("alg = {}", quote(alg)));
+        }
+        if let Some((width, height)) = self.size {
+            lines.push(format!("width = {width}"));
+            lines.push(format!("height = {height}"));
+        }
+        if let Some(theme) = &self.theme {
+            lines.push(format!("theme = {}", quote(theme)));
+        }
+        if let Some((x, y)) = self.position {
+            lines.push(format!("position = [{x}, {y}]"));
+        }
+
+        let recent: Vec<_> = self.recent.iter().map(|path| quote(path)).collect();
+        lines.push(format!("recent = [{}]", recent.join(", ")));
+
+        lines.join("\n") + "\n"
+    }
+
+    /// As default values of command line flags, only those `accepts` returns `true` for like in
+    /// [`Config::to_defaults`]
+    pub fn to_defaults(&self, accepts: impl Fn(&str) -> bool) -> Vec<(String, Vec<String>)> {
+        let mut flags = vec![];
+
+        if let Some(alg) = &self.alg {
+            flags.push(("alg", alg.clone()));
+        }
+        if let Some((width, height)) = self.size {
+            flags.push(("width", width.to_string()));
+            flags.push(("height", height.to_string()));
+        }
+        if let Some(theme) = &self.theme {
+            flags.push(("theme", theme.clone()));
+        }
+        if let Some((x, y)) = self.position {
+            flags.push(("position", format!("{x},{y}")));
+        }
+
+        flags
+            .into_iter()
+            .filter(|(name, _)| accepts(name))
+            .map(|(name, value)| (name.to_string(), vec![value]))
+            .collect()
+    }
+
+    /// Moves `path` to the front of the recent files, dropping the oldest past the limit
+    pub fn add_recent(&mut self, path: &str) {
+        let path = fs::canonicalize(path)
+            .ok()
+            .and_then(|path| path.into_os_string().into_string().ok())
+            .unwrap_or_else(|| path.to_string());
+
+        self.recent.retain(|recent| *recent != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(RECENT_LIMIT);
+    }
+}
+
+/// `$XDG_CONFIG_HOME/maze` or `~/.config/maze` on Linux, `~/Library/Application Support/maze` on
+/// macOS and `%APPDATA%\maze` on Windows
+fn settings_dir() -> Option<PathBuf> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home().map(|home| home.join(".config")))
+    };
+
+    base.map(|base| base.join("maze"))
+}
+
+/// Reads the settings saved by earlier runs, after which [`update`] and [`save`] take effect. A
+/// missing file is no settings yet. An unreadable one is started over, after returning why.
+pub fn load() -> Result<(), String> {
+    let path = settings_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .filter(|path| path.exists());
+
+    let config = match &path {
+        Some(path) => Config::load(&path.to_string_lossy()),
+        None => Ok(Config::default()),
+    };
+
+    let (settings, result) = match config {
+        Ok(config) => (Settings::from_config(&config), Ok(())),
+        Err(err) => (Settings::default(), Err(err)),
+    };
+
+    *SETTINGS.lock().unwrap() = Some(Loaded {
+        settings,
+        changed: false,
+    });
+    result
+}
+
+/// A copy of the settings, `None` when they are not used
+pub fn get() -> Option<Settings> {
+    SETTINGS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|loaded| loaded.settings.clone())
+}
+
+/// Changes the settings, if they are used, to be saved by [`save`]
+pub fn update(change: impl FnOnce(&mut Settings)) {
+    if let Some(loaded) = SETTINGS.lock().unwrap().as_mut() {
+        let before = loaded.settings.clone();
+        change(&mut loaded.settings);
+        loaded.changed |= loaded.settings != before;
+    }
+}
+
+/// Writes the settings if [`update`] changed them, leaving the file alone otherwise
+pub fn save() -> Result<(), String> {
+    let settings = match SETTINGS.lock().unwrap().as_mut() {
+        Some(loaded) if loaded.changed => {
+            loaded.changed = false;
+            loaded.settings.clone()
+        }
+        _ => return Ok(()),
+    };
+    let dir = settings_dir().ok_or_else(|| String::from("no config directory to save to"))?;
+
+    fs::create_dir_all(&dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+
+    let path = dir.join(SETTINGS_FILE);
+    fs::write(&path, settings.to_toml()).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn string(config: &Config, key: &str) -> Option<String> {
+    match config.get(key) {
+        Some(ConfigValue::String(value)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn number<T: FromStr>(config: &Config, key: &str) -> Option<T> {
+    match config.get(key) {
+        Some(ConfigValue::Number(value)) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+/// A TOML string of `s`, escaped so [`Config`] reads it back the same
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let settings = Settings {
+            alg: Some(String::from("a-star")),
+            size: Some((40, 30)),
+            theme: Some(String::from("dark")),
+            position: Some((-10, 20)),
+            recent: ["a\"#b.dat", "a\",b.dat", "c:\\mazes\\d.dat", "e\tf.dat"]
+                .map(String::from)
+                .to_vec(),
+        };
+
+        let config = Config::parse(&settings.to_toml()).unwrap();
+
+        assert_eq!(Settings::from_config(&config), settings);
+    }
+}
@@ -0,0 +1,100 @@
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+use sfml::audio::{Sound, SoundBuffer, SoundSource};
+use sfml::cpp::FBox;
+
+use crate::run::StepEvent;
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Frequency of a click at pitch 1, in Hz
+const BASE_FREQUENCY: f32 = 440.;
+const CLICK_LENGTH: Duration = Duration::from_millis(40);
+/// Clicks that can sound at once, each cutting off the oldest
+const VOICES: usize = 8;
+/// Shortest time between clicks, however many events come in, so fast runs are not a buzz
+const CLICK_INTERVAL: Duration = Duration::from_millis(12);
+
+/// A sine tone fading out, played at every pitch
+pub fn click_buffer() -> Option<FBox<SoundBuffer>> {
+    let length = (CLICK_LENGTH.as_secs_f32() * SAMPLE_RATE as f32) as usize;
+
+    let samples: Vec<i16> = (0..length)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let fade = 1. - i as f32 / length as f32;
+
+            ((TAU * BASE_FREQUENCY * t).sin() * fade * i16::MAX as f32) as i16
+        })
+        .collect();
+
+    SoundBuffer::from_samples(&samples, 1, SAMPLE_RATE).ok()
+}
+
+/// Plays step events as clicks pitched by where they happened, like sorting algorithm
+/// visualizers do
+pub struct Sonifier<'a> {
+    voices: Vec<Sound<'a>>,
+    next_voice: usize,
+    last_click: Option<Instant>,
+}
+
+impl<'a> Sonifier<'a> {
+    /// `volume` goes from 0 to 100
+    pub fn new(buffer: &'a SoundBuffer, volume: f32) -> Self {
+        let voices = (0..VOICES)
+            .map(|_| {
+                let mut sound = Sound::with_buffer(buffer);
+                sound.set_volume(volume);
+                sound
+            })
+            .collect();
+
+        Self {
+            voices,
+            next_voice: 0,
+            last_click: None,
+        }
+    }
+
+    /// Plays as many of `events` as there has been time for since the last click, evenly picked
+    /// and ending with the latest
+    pub fn play(&mut self, events: &[StepEvent], bounds: (usize, usize)) {
+        let now = Instant::now();
+        let due = match self.last_click {
+            Some(last) => (now - last).as_millis() / CLICK_INTERVAL.as_millis(),
+            None => VOICES as u128,
+        };
+
+        let count = (due as usize).min(VOICES).min(events.len());
+        if count == 0 {
+            return;
+        }
+
+        for i in 1..=count {
+            let event = events[i * events.len() / count - 1];
+
+            let voice = &mut self.voices[self.next_voice];
+            voice.set_pitch(pitch(event, bounds));
+            voice.play();
+
+            self.next_voice = (self.next_voice + 1) % VOICES;
+        }
+
+        self.last_click = Some(now);
+    }
+}
+
+/// From half to twice the base frequency: rising through the maze row by row while carving, and
+/// as the solver gets closer to the end in the bottom right corner
+fn pitch(event: StepEvent, (width, height): (usize, usize)) -> f32 {
+    let fraction = match event {
+        StepEvent::Carve((x, y)) => (y * width + x) as f32 / (width * height - 1).max(1) as f32,
+        StepEvent::Visit((x, y)) => {
+            let distance = (width - 1 - x) + (height - 1 - y);
+            1. - distance as f32 / (width + height - 2).max(1) as f32
+        }
+    };
+
+    2f32.powf(fraction * 2. - 1.)
+}
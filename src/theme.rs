@@ -0,0 +1,72 @@
+use std::sync::RwLock;
+
+use crate::color::Color;
+use crate::consts::{CELL_COLOR, EMPTY_CELL_COLOR, WALL_COLOR};
+
+/// Colors of the maze and of what solvers draw over it. File exports keep their own fixed colors,
+/// so they read back the same whatever the theme.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub wall: Color,
+    pub cell: Color,
+    pub empty_cell: Color,
+    /// Cells a solver has explored
+    pub visited: Color,
+    /// Cells waiting to be explored
+    pub frontier: Color,
+    pub path: Color,
+    /// Cells a solver found it can not get to from the start
+    pub unreachable: Color,
+    /// Hatch visited cells and dot frontier cells instead of filling both, so they can be told
+    /// apart without relying on color
+    pub patterns: bool,
+}
+
+impl Theme {
+    pub const DEFAULT: Self = Self {
+        wall: WALL_COLOR,
+        cell: CELL_COLOR,
+        empty_cell: EMPTY_CELL_COLOR,
+        visited: Color::rgb(0, 255, 0),
+        frontier: Color::rgb(0, 160, 255),
+        path: Color::RED,
+        unreachable: Color::rgb(120, 40, 40),
+        patterns: false,
+    };
+
+    /// The Okabe-Ito palette, safe for the common kinds of color blindness
+    pub const OKABE_ITO: Self = Self {
+        visited: Color::rgb(86, 180, 233),
+        frontier: Color::rgb(230, 159, 0),
+        path: Color::rgb(0, 114, 178),
+        unreachable: Color::rgb(213, 94, 0),
+        ..Self::DEFAULT
+    };
+
+    /// Paul Tol's bright palette, also safe for color blindness
+    pub const TOL: Self = Self {
+        visited: Color::rgb(102, 204, 238),
+        frontier: Color::rgb(204, 187, 68),
+        path: Color::rgb(170, 51, 119),
+        unreachable: Color::rgb(187, 187, 187),
+        ..Self::DEFAULT
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static THEME: RwLock<Theme> = RwLock::new(Theme::DEFAULT);
+
+pub fn theme() -> Theme {
+    *THEME.read().unwrap()
+}
+
+/// Applies to everything drawn from now on. Maze renderers made before keep their colors until
+/// rebuilt.
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap() = theme;
+}
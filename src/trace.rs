@@ -0,0 +1,116 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use maze::json::Json;
+use maze::maze::solvers::MemoryStats;
+use maze::maze::{GenStep, SolveStep};
+
+/// Writes one JSON object per generator or solver step to a file, one per line, to be analyzed
+/// elsewhere, e.g. with `pandas.read_json(path, lines=True)`.
+///
+/// Every line has the `phase` (`generate` or `solve`), the `step` within it counting from 1, an
+/// `action` and the `cells` it touched. Generator steps `carve` when they write cells, `walk` when
+/// they do not and `finish` on the last step, and add the cells still `remaining` before the last.
+/// Solver steps `expand` a cell, or report the path as `found` with its `path_length`, or the end
+/// as `unsolvable`, and add the `open`, `closed` and `visited` sizes after the step.
+pub struct Trace {
+    path: String,
+    out: BufWriter<File>,
+    /// The first write that failed, after which nothing more is written
+    error: Option<String>,
+}
+
+impl Trace {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|err| format!("{path}: {err}"))?;
+
+        Ok(Self {
+            path: path.to_string(),
+            out: BufWriter::new(file),
+            error: None,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn generation_step(&mut self, step: usize, cells: &[(usize, usize)], result: GenStep) {
+        let action = match (result, cells.is_empty()) {
+            (GenStep::Done, _) => "finish",
+            (GenStep::InProgress { .. }, false) => "carve",
+            (GenStep::InProgress { .. }, true) => "walk",
+        };
+
+        let mut entries = vec![
+            ("phase", "generate".into()),
+            ("step", step.into()),
+            ("action", action.into()),
+            ("cells", points(cells)),
+        ];
+        if let GenStep::InProgress { remaining, .. } = result {
+            entries.push(("remaining", remaining.into()));
+        }
+
+        self.write(Json::object(entries));
+    }
+
+    pub fn solve_step(
+        &mut self,
+        step: usize,
+        cell: Option<(usize, usize)>,
+        memory: MemoryStats,
+        result: SolveStep,
+    ) {
+        let action = match result {
+            SolveStep::InProgress => "expand",
+            SolveStep::Found(_) => "found",
+            SolveStep::Unsolvable => "unsolvable",
+        };
+
+        let mut entries = vec![
+            ("phase", "solve".into()),
+            ("step", step.into()),
+            ("action", action.into()),
+            ("cells", points(cell.as_slice())),
+            ("open", memory.open.into()),
+            ("closed", memory.closed.into()),
+            ("visited", memory.visited.into()),
+        ];
+        if let SolveStep::Found(path) = result {
+            entries.push(("path_length", path.len().into()));
+        }
+
+        self.write(Json::object(entries));
+    }
+
+    fn write(&mut self, event: Json) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Err(err) = writeln!(self.out, "{event}") {
+            self.error = Some(format!("{}: {err}", self.path));
+        }
+    }
+
+    /// Flushes the file, returning the first error met while writing it
+    pub fn finish(mut self) -> Result<(), String> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+
+        self.out
+            .flush()
+            .map_err(|err| format!("{}: {err}", self.path))
+    }
+}
+
+fn points(cells: &[(usize, usize)]) -> Json {
+    Json::Array(
+        cells
+            .iter()
+            .map(|&(x, y)| Json::Array(vec![x.into(), y.into()]))
+            .collect(),
+    )
+}
@@ -0,0 +1,322 @@
+use std::ops::RangeInclusive;
+
+use sfml::graphics::{Color, FloatRect, PrimitiveType, RenderStates, RenderTarget, Vertex, View};
+use sfml::window::{mouse, Event};
+
+/// Window pixels per dot of the built-in font
+const DOT: f32 = 2.;
+const ROW_HEIGHT: f32 = 24.;
+const PADDING: f32 = 8.;
+const LABEL_WIDTH: f32 = 100.;
+const CONTROL_WIDTH: f32 = 160.;
+/// Room for the value shown after a slider
+const VALUE_WIDTH: f32 = 72.;
+/// Space between a control and the edges of its row
+const INSET: f32 = 3.;
+
+const BACKGROUND: Color = Color::rgba(20, 20, 20, 220);
+const TRACK: Color = Color::rgb(70, 70, 70);
+const FILL: Color = Color::rgb(90, 150, 230);
+const TEXT: Color = Color::rgb(235, 235, 235);
+
+/// Mouse state between frames of an immediate mode [`Ui`], kept by the window and fed its events
+#[derive(Debug, Default)]
+pub struct UiInput {
+    mouse: (f32, f32),
+    down: bool,
+    /// The left button went down this frame
+    pressed: bool,
+    /// Row of the slider being dragged
+    active: Option<usize>,
+    /// Where the last frame's panel was drawn, so clicks elsewhere are left to the window
+    area: Option<FloatRect>,
+}
+
+impl UiInput {
+    /// Returns `true` if the event was used by the ui, which is any click on its panel and any
+    /// mouse movement while dragging one of its sliders
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::MouseButtonPressed {
+                button: mouse::Button::Left,
+                x,
+                y,
+            } if self
+                .area
+                .is_some_and(|area| contains(area, (x as f32, y as f32))) =>
+            {
+                self.mouse = (x as f32, y as f32);
+                self.down = true;
+                self.pressed = true;
+            }
+            Event::MouseButtonReleased {
+                button: mouse::Button::Left,
+                ..
+            } if self.down => {
+                self.down = false;
+                self.active = None;
+            }
+            Event::MouseMoved { x, y } => {
+                self.mouse = (x as f32, y as f32);
+                return self.active.is_some();
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/// A panel of labeled controls in the top left corner of the window, laid out a row each in the
+/// order they are added, redrawn every frame. Controls return `true` when the user changed their
+/// value. Text is drawn with a small built-in pixel font of capitals, digits and a few symbols, so
+/// no font file is needed.
+pub struct Ui<'a> {
+    input: &'a mut UiInput,
+    rows: usize,
+    vertices: Vec<Vertex>,
+}
+
+impl<'a> Ui<'a> {
+    pub fn new(input: &'a mut UiInput) -> Self {
+        Self {
+            input,
+            rows: 0,
+            vertices: vec![],
+        }
+    }
+
+    /// A value picked along a track, shown as `text` gives it
+    pub fn slider(
+        &mut self,
+        label: &str,
+        value: &mut f32,
+        range: RangeInclusive<f32>,
+        text: impl Fn(f32) -> String,
+    ) -> bool {
+        let (row, track) = self.row(label);
+        let (min, max) = (*range.start(), *range.end());
+
+        if self.clicked(track) {
+            self.input.active = Some(row);
+        }
+
+        let mut changed = false;
+        if self.input.active == Some(row) {
+            let t = ((self.input.mouse.0 - track.left) / track.width).clamp(0., 1.);
+            let new = min + t * (max - min);
+
+            changed = new != *value;
+            *value = new;
+        }
+
+        let t = ((*value - min) / (max - min)).clamp(0., 1.);
+        let bar = track.height / 3.;
+        self.quad(track.left, track.top + bar, track.width, bar, TRACK);
+        self.quad(track.left, track.top + bar, track.width * t, bar, FILL);
+        self.text(
+            &text(*value),
+            track.left + track.width + PADDING,
+            track.top - INSET,
+        );
+
+        changed
+    }
+
+    /// An on and off switch
+    pub fn toggle(&mut self, label: &str, value: &mut bool) -> bool {
+        let (_, area) = self.row(label);
+        let clicked = self.clicked(area);
+
+        if clicked {
+            *value = !*value;
+        }
+
+        let side = area.height;
+        self.quad(area.left, area.top, side, side, TRACK);
+        if *value {
+            self.quad(
+                area.left + INSET,
+                area.top + INSET,
+                side - INSET * 2.,
+                side - INSET * 2.,
+                FILL,
+            );
+        }
+
+        clicked
+    }
+
+    /// One of `options`, moving on to the next on every click
+    pub fn choice(&mut self, label: &str, selected: &mut usize, options: &[&str]) -> bool {
+        let (_, area) = self.row(label);
+        let clicked = self.clicked(area) && !options.is_empty();
+
+        if clicked {
+            *selected = (*selected + 1) % options.len();
+        }
+
+        self.quad(area.left, area.top, area.width, area.height, TRACK);
+        if let Some(option) = options.get(*selected) {
+            self.text(option, area.left + INSET * 2., area.top - INSET);
+        }
+
+        clicked
+    }
+
+    /// Draws the panel, leaving the target's view on window pixels
+    pub fn draw(self, target: &mut dyn RenderTarget) {
+        let size = target.size();
+        let area = FloatRect::new(
+            0.,
+            0.,
+            PADDING * 3. + LABEL_WIDTH + CONTROL_WIDTH + VALUE_WIDTH,
+            PADDING * 2. + self.rows as f32 * ROW_HEIGHT,
+        );
+
+        let mut vertices = vec![];
+        push_quad(
+            &mut vertices,
+            (area.left, area.top, area.width, area.height),
+            BACKGROUND,
+        );
+        vertices.extend(self.vertices);
+
+        let view = View::from_rect(FloatRect::new(0., 0., size.x as f32, size.y as f32)).unwrap();
+        target.set_view(&view);
+        target.draw_primitives(&vertices, PrimitiveType::TRIANGLES, &RenderStates::DEFAULT);
+
+        self.input.area = Some(area);
+        self.input.pressed = false;
+    }
+
+    /// Lays out the next row with its label, returning its index and the area left for the
+    /// control
+    fn row(&mut self, label: &str) -> (usize, FloatRect) {
+        let row = self.rows;
+        self.rows += 1;
+
+        let top = PADDING + row as f32 * ROW_HEIGHT;
+        self.text(label, PADDING, top);
+
+        let control = FloatRect::new(
+            PADDING * 2. + LABEL_WIDTH,
+            top + INSET,
+            CONTROL_WIDTH,
+            ROW_HEIGHT - INSET * 2.,
+        );
+
+        (row, control)
+    }
+
+    fn clicked(&self, area: FloatRect) -> bool {
+        self.input.pressed && contains(area, self.input.mouse)
+    }
+
+    fn quad(&mut self, left: f32, top: f32, width: f32, height: f32, color: Color) {
+        push_quad(&mut self.vertices, (left, top, width, height), color);
+    }
+
+    /// Writes `text` in capitals, centered in the height of a row whose top is at `top`
+    fn text(&mut self, text: &str, left: f32, top: f32) {
+        let top = top + (ROW_HEIGHT - GLYPH_HEIGHT as f32 * DOT) / 2.;
+
+        for (i, c) in text.chars().enumerate() {
+            let x = left + i as f32 * (GLYPH_WIDTH + 1) as f32 * DOT;
+
+            for (row, bits) in glyph(c).into_iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits >> (GLYPH_WIDTH - 1 - column) & 1 == 1 {
+                        self.quad(
+                            x + column as f32 * DOT,
+                            top + row as f32 * DOT,
+                            DOT,
+                            DOT,
+                            TEXT,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn contains(area: FloatRect, (x, y): (f32, f32)) -> bool {
+    (area.left..area.left + area.width).contains(&x)
+        && (area.top..area.top + area.height).contains(&y)
+}
+
+fn push_quad(
+    vertices: &mut Vec<Vertex>,
+    (left, top, width, height): (f32, f32, f32, f32),
+    color: Color,
+) {
+    let corners = [
+        (left, top),
+        (left + width, top),
+        (left + width, top + height),
+        (left, top),
+        (left + width, top + height),
+        (left, top + height),
+    ];
+
+    vertices.extend(
+        corners
+            .into_iter()
+            .map(|corner| Vertex::with_pos_color(corner.into(), color)),
+    );
+}
+
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+
+/// Rows of a character of the built-in font, top first, with the leftmost dot in the highest of
+/// the low 3 bits. Lowercase letters are drawn as capitals and unknown characters as spaces.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '*' => [0b000, 0b101, 0b010, 0b101, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        _ => [0; GLYPH_HEIGHT],
+    }
+}
@@ -0,0 +1,937 @@
+use maze::analysis::{distance_colors, distance_map, RAINBOW_COLORS};
+use maze::camera::Camera;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use maze::consts::*;
+use maze::fog::Fog;
+use maze::formats;
+use maze::maze::infinite::InfiniteMaze;
+use maze::maze::render::{draw_marks, draw_path, Mark, MazeRenderer};
+use maze::maze::solvers;
+use maze::maze::tags::TagMarkers;
+use maze::maze::{GenerationProgress, Maze};
+use maze::minimap::Minimap;
+use maze::settings;
+use maze::theme::{set_theme, theme};
+use maze::Direction;
+use sfml::graphics::{
+    CircleShape, Color, RenderStates, RenderTarget, RenderWindow, Shape, Transformable, View,
+};
+use sfml::system::Vector2i;
+use sfml::window::{ContextSettings, Event, Key, Style, VideoMode};
+
+use crate::cli::WindowArgs;
+use crate::panel::{Panel, Tweaks};
+use crate::progress::time_left;
+use crate::run::Run;
+use crate::sound::{click_buffer, Sonifier};
+use crate::watch::FileWatcher;
+
+/// Changes the run from outside the window, once per frame
+pub trait Control {
+    /// Returns `true` if the maze was replaced, so everything drawn from it is rebuilt
+    fn update(&mut self, run: &mut Run) -> bool;
+
+    /// Replaces the run with one on the maze file at `path`, picked from the recent files.
+    /// Returns `true` if it did.
+    fn open(&mut self, _run: &mut Run, path: &str) -> bool {
+        eprintln!("Could not open {path}: this window only shows the maze it was given");
+        false
+    }
+}
+
+/// Replaces the run whenever the watched maze file changes on disk, or another file is opened
+pub struct Reload<'a> {
+    /// Only with `--watch`
+    pub watcher: Option<FileWatcher>,
+    /// Starts a fresh run on the reloaded maze
+    pub make_run: Box<dyn FnMut(Maze) -> Result<Run, String> + 'a>,
+}
+
+impl Reload<'_> {
+    fn load(&mut self, run: &mut Run, path: &str) -> Result<(), String> {
+        *run = formats::read(path).and_then(&mut self.make_run)?;
+        Ok(())
+    }
+}
+
+impl Control for Reload<'_> {
+    fn update(&mut self, run: &mut Run) -> bool {
+        let Some(watcher) = &mut self.watcher else {
+            return false;
+        };
+        if !watcher.changed() {
+            return false;
+        }
+
+        let path = watcher.path().to_string();
+
+        match self.load(run, &path) {
+            Ok(()) => {
+                eprintln!("Reloaded {path}");
+                true
+            }
+            Err(err) => {
+                eprintln!("Could not reload {path}: {err}");
+                false
+            }
+        }
+    }
+
+    fn open(&mut self, run: &mut Run, path: &str) -> bool {
+        match self.load(run, path) {
+            Ok(()) => {
+                eprintln!("Opened {path}");
+                settings::update(|settings| settings.add_recent(path));
+
+                // Watch the maze now shown instead
+                if let Some(watcher) = &mut self.watcher {
+                    *watcher = FileWatcher::new(path);
+                }
+                true
+            }
+            Err(err) => {
+                eprintln!("Could not open {path}: {err}");
+                false
+            }
+        }
+    }
+}
+
+/// The maze's name, when it has one, after the app's
+fn window_title(maze: &Maze) -> String {
+    match maze.metadata().get("name") {
+        Some(name) => format!("Maze - {name}"),
+        None => String::from("Maze"),
+    }
+}
+
+/// Size of the window showing a world of `world_size` pixels, scaled down to fit on the screen when
+/// even the smallest cells make the maze too large
+fn fit_to_screen(world_size: (f32, f32)) -> (f32, f32) {
+    let (width, height) = screen_size();
+    let scale = (width / world_size.0).min(height / world_size.1).min(1.);
+
+    (
+        (world_size.0 * scale).floor(),
+        (world_size.1 * scale).floor(),
+    )
+}
+
+fn maze_world_size(maze: &Maze) -> (f32, f32) {
+    let bounds = maze.get_bounds();
+
+    (
+        (bounds.0 * get_cell_size()) as f32,
+        (bounds.1 * get_cell_size()) as f32,
+    )
+}
+
+/// Steps owed at most, so a stalled frame, e.g. while the window is dragged, does not make the
+/// run jump ahead
+const MAX_BACKLOG: Duration = Duration::from_millis(250);
+
+/// Turns the time passed between frames into steps at a fixed rate
+struct StepClock {
+    step: Duration,
+    last: Instant,
+    /// Time passed that was not yet spent on a whole step
+    backlog: Duration,
+}
+
+impl StepClock {
+    fn new(steps_per_second: u32) -> Self {
+        Self {
+            step: Duration::from_secs(1) / steps_per_second,
+            last: Instant::now(),
+            backlog: Duration::ZERO,
+        }
+    }
+
+    /// Steps due since the last call
+    fn due(&mut self) -> u32 {
+        let now = Instant::now();
+        self.backlog = (self.backlog + (now - self.last)).min(MAX_BACKLOG.max(self.step));
+        self.last = now;
+
+        let steps = (self.backlog.as_nanos() / self.step.as_nanos().max(1)) as u32;
+        self.backlog -= self.step * steps;
+
+        steps
+    }
+}
+
+/// Time spent stepping between frames with `--fast`
+const FAST_FRAME: Duration = Duration::from_millis(100);
+/// Steps between looks at the clock with `--fast`, as steps take far less time than reading it
+const FAST_CHECK_EVERY: usize = 256;
+
+/// Steps the run as fast as it goes for a frame at a time. The window is only drawn every
+/// [`FAST_FRAME`], so the progress goes in its title.
+struct FastStepper {
+    /// When generating or solving started, to estimate the time left
+    phase_start: Instant,
+    generated: bool,
+    title: String,
+}
+
+impl FastStepper {
+    fn new(run: &Run) -> Self {
+        Self {
+            phase_start: Instant::now(),
+            generated: run.is_generated(),
+            title: window_title(&run.maze),
+        }
+    }
+
+    /// Returns the new window title, if it changed
+    fn step(&mut self, run: &mut Run) -> Option<String> {
+        let deadline = Instant::now() + FAST_FRAME;
+
+        while !run.is_done() && Instant::now() < deadline {
+            for _ in 0..FAST_CHECK_EVERY {
+                run.step();
+            }
+        }
+
+        if run.is_generated() != self.generated {
+            self.generated = run.is_generated();
+            self.phase_start = Instant::now();
+        }
+
+        let title = match self.progress(run) {
+            Some(progress) => format!("{} - {progress}", window_title(&run.maze)),
+            None => window_title(&run.maze),
+        };
+
+        (title != self.title).then(|| {
+            self.title = title.clone();
+            title
+        })
+    }
+
+    fn progress(&self, run: &Run) -> Option<String> {
+        if !run.is_generated() {
+            let generator = run.generator()?;
+            let (remaining, total) = (generator.remaining(), generator.total());
+            let done = total - remaining.min(total);
+
+            let mut progress = format!("generating {}%", done * 100 / total.max(1));
+            if let Some(left) = time_left(self.phase_start.elapsed(), done, remaining) {
+                progress.push_str(&format!(", about {left} left"));
+            }
+
+            Some(progress)
+        } else if run.is_done() {
+            None
+        } else if run.race().is_some_and(|race| !race.is_done()) {
+            Some(String::from("racing"))
+        } else {
+            let visited = run.solver()?.memory().visited;
+            Some(format!(
+                "solving, {visited} of {} cells visited",
+                run.maze.cell_count()
+            ))
+        }
+    }
+}
+
+/// Moves a new window to `--position`, which defaults to where the last one was closed
+fn place_window(window: &mut RenderWindow, args: &WindowArgs) {
+    if let Some((x, y)) = args.position {
+        window.set_position(Vector2i::new(x, y));
+    }
+}
+
+/// Remembers where the window was closed, for the next one to open there
+fn remember_position(window: &RenderWindow) {
+    let position = window.position();
+    settings::update(|settings| settings.position = Some((position.x, position.y)));
+}
+
+fn recent_files() -> Vec<String> {
+    settings::get()
+        .map(|settings| settings.recent)
+        .unwrap_or_default()
+}
+
+/// The recent files by name and the number key to open each with, for the window title
+fn recent_menu(recent: &[String]) -> String {
+    let items: Vec<_> = recent
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let name = Path::new(path).file_name().unwrap_or_default();
+            format!("{} {}", i + 1, name.to_string_lossy())
+        })
+        .collect();
+
+    format!("Open recent (other keys cancel): {}", items.join("  "))
+}
+
+/// 0 for the 1 key up to 8 for the 9 key, on the top row or the numpad
+fn number_key(code: Key) -> Option<usize> {
+    let top_row = [
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+    ];
+    let numpad = [
+        Key::Numpad1,
+        Key::Numpad2,
+        Key::Numpad3,
+        Key::Numpad4,
+        Key::Numpad5,
+        Key::Numpad6,
+        Key::Numpad7,
+        Key::Numpad8,
+        Key::Numpad9,
+    ];
+
+    top_row
+        .iter()
+        .position(|&key| key == code)
+        .or_else(|| numpad.iter().position(|&key| key == code))
+}
+
+/// What the maze cells are colored by, on top of the theme
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Tint {
+    distance: bool,
+    /// Cells the solver can not get to from the start, once it finds it can not reach the end
+    unreachable: bool,
+    /// With `--fog`, once the solver is under way
+    fog: bool,
+}
+
+impl Tint {
+    fn colors(self, maze: &Maze, fog: Option<&Fog>) -> Option<Vec<Option<Color>>> {
+        if self == Self::default() {
+            return None;
+        }
+
+        let mut colors = match self.distance {
+            true => distance_colors(maze, &RAINBOW_COLORS),
+            false => vec![None; maze.cell_count()],
+        };
+
+        if self.unreachable {
+            // Solvers always start from the top left corner
+            let distances = distance_map(maze, (0, 0));
+            for (color, distance) in colors.iter_mut().zip(distances) {
+                if distance.is_none() {
+                    *color = Some(theme().unreachable);
+                }
+            }
+        }
+
+        match fog.filter(|_| self.fog) {
+            Some(fog) => Some(fog.dim(Some(colors))),
+            None => Some(colors),
+        }
+    }
+}
+
+/// Opens a window showing the run, stepping it once per frame until the window is closed
+pub fn show(run: &mut Run, args: &WindowArgs, mut control: Option<&mut dyn Control>) {
+    set_theme(args.theme());
+    set_wall_width(args.wall_width());
+
+    let mut world_size = maze_world_size(&run.maze);
+    let mut window_size = fit_to_screen(world_size);
+
+    let mut window = RenderWindow::new(
+        VideoMode::new(window_size.0 as u32, window_size.1 as u32, 32),
+        window_title(&run.maze).as_str(),
+        Style::CLOSE,
+        &ContextSettings::default(),
+    )
+    .unwrap();
+    place_window(&mut window, args);
+
+    if args.vsync {
+        window.set_vertical_sync_enabled(true);
+    } else {
+        window.set_framerate_limit(FRAMERATE_LIMIT);
+    }
+
+    let mut clock = StepClock::new(args.speed);
+    let mut fast = args.fast.then(|| FastStepper::new(run));
+
+    let click = args.sound.then(click_buffer).flatten();
+    if args.sound && click.is_none() {
+        eprintln!("Could not make the sound of a click, staying silent");
+    }
+    let mut sonifier = click
+        .as_ref()
+        .map(|buffer| Sonifier::new(buffer, args.volume as f32));
+
+    let mut renderer = MazeRenderer::new(&mut run.maze);
+
+    let mut camera = Camera::new(world_size, window_size);
+    let mut minimap = Minimap::new(world_size, window_size);
+    let mut show_minimap = true;
+
+    // Tinting waits for the maze to be generated, and is redone whenever it changes
+    let mut color_by_distance = false;
+    let mut tinted = Tint::default();
+
+    // Follows the solver's head, starting over with every maze
+    let mut fog: Option<Fog> = None;
+
+    let mut tweaks = Tweaks {
+        speed: args.speed,
+        steps_per_frame: 0,
+        theme: args.theme,
+        accessible: args.accessible,
+        heuristic_weight: run.heuristic_weight(),
+    };
+    let mut panel = Panel::default();
+
+    // While the recent files are listed in the title, the next key picks one
+    let mut picking = false;
+    let mut open = None;
+
+    'mainloop: loop {
+        while let Some(ev) = window.poll_event() {
+            match ev {
+                Event::Closed => break 'mainloop,
+                Event::KeyPressed { code, .. } if picking => {
+                    picking = false;
+                    open = number_key(code).and_then(|i| recent_files().into_iter().nth(i));
+                    window.set_title(window_title(&run.maze).as_str());
+                }
+                Event::KeyPressed { code, ctrl, .. }
+                    if code == Key::Q || (code == Key::C && ctrl) =>
+                {
+                    break 'mainloop;
+                }
+                Event::KeyPressed {
+                    code: Key::O,
+                    ctrl: false,
+                    ..
+                } if control.is_some() => {
+                    let recent = recent_files();
+
+                    if recent.is_empty() {
+                        eprintln!("No recent maze files to open");
+                    } else {
+                        for (i, path) in recent.iter().enumerate() {
+                            eprintln!("{}: {path}", i + 1);
+                        }
+                        window.set_title(&recent_menu(&recent));
+                        picking = true;
+                    }
+                }
+                Event::KeyPressed { code: Key::Tab, .. } => panel.shown = !panel.shown,
+                Event::KeyPressed { code: Key::M, .. } => show_minimap = !show_minimap,
+                Event::KeyPressed { code: Key::D, .. } => color_by_distance = !color_by_distance,
+                Event::KeyPressed {
+                    code: Key::R | Key::G,
+                    ctrl: false,
+                    ..
+                } => {
+                    if run.regenerate() {
+                        let generator = run.generator().unwrap();
+                        eprintln!(
+                            "Generating with {} from seed {}",
+                            generator.name(),
+                            generator.seed()
+                        );
+                    }
+                }
+                Event::KeyPressed {
+                    code: Key::A,
+                    ctrl: false,
+                    ..
+                } if run.race().is_none() => {
+                    eprintln!("Solving with {}", run.next_algorithm());
+                }
+                Event::KeyPressed {
+                    code: Key::Z,
+                    ctrl: true,
+                    shift,
+                    ..
+                } => {
+                    if shift {
+                        run.redo();
+                    } else {
+                        run.undo();
+                    }
+                }
+                Event::KeyPressed {
+                    code: Key::Y,
+                    ctrl: true,
+                    ..
+                } => {
+                    run.redo();
+                }
+                _ => {
+                    if !panel.handle_event(&ev) {
+                        camera.handle_event(&ev);
+                    }
+                }
+            }
+        }
+
+        let opened = match (open.take(), control.as_mut()) {
+            (Some(path), Some(control)) => control.open(run, &path),
+            _ => false,
+        };
+
+        if opened || control.as_mut().is_some_and(|control| control.update(run)) {
+            update_cell_size(&run.maze.get_bounds(), args.cell_size());
+            world_size = maze_world_size(&run.maze);
+            window_size = fit_to_screen(world_size);
+            window.set_size((window_size.0 as u32, window_size.1 as u32));
+            window.set_title(window_title(&run.maze).as_str());
+
+            renderer = MazeRenderer::new(&mut run.maze);
+            camera = Camera::new(world_size, window_size);
+            minimap = Minimap::new(world_size, window_size);
+            tinted = Tint::default();
+            fog = None;
+        }
+
+        // A new run from the control does not keep events yet
+        if sonifier.is_some() {
+            run.record_events();
+        }
+
+        if let Some(fast) = &mut fast {
+            if let Some(title) = fast.step(run).filter(|_| !picking) {
+                window.set_title(&title);
+            }
+        } else {
+            let due = clock.due();
+            let steps = match tweaks.steps_per_frame {
+                0 => due,
+                steps => steps,
+            };
+
+            for _ in 0..steps {
+                run.step();
+            }
+        }
+
+        if let Some(sonifier) = &mut sonifier {
+            sonifier.play(&run.take_events(), run.maze.get_bounds());
+        }
+
+        let changed = renderer.update(&mut run.maze);
+
+        let head = run
+            .solver()
+            .and_then(|solver| solver.current())
+            .filter(|_| run.is_generated());
+
+        if !run.is_generated() {
+            fog = None;
+        }
+
+        let fog_moved = match (args.fog, head) {
+            (Some(steps), Some(head)) => fog
+                .get_or_insert_with(|| Fog::new(run.maze.get_bounds(), steps as usize))
+                .look(&run.maze, head),
+            _ => false,
+        };
+
+        let tint = Tint {
+            distance: color_by_distance && run.is_generated(),
+            unreachable: run.is_unsolvable(),
+            fog: fog.is_some(),
+        };
+
+        let retint = tint != tinted || (tint != Tint::default() && changed) || fog_moved;
+
+        if retint {
+            renderer.set_colors(&run.maze, tint.colors(&run.maze, fog.as_ref()));
+            tinted = tint;
+        }
+
+        if changed || retint {
+            minimap.invalidate();
+        }
+
+        window.clear(theme().wall);
+        window.set_view(camera.view());
+
+        renderer.set_smooth(camera.units_per_pixel() > 1.);
+        window.draw(&renderer);
+        window.draw(&TagMarkers(&run.maze));
+
+        let overlay = run.overlay();
+
+        if let Some(overlay) = overlay {
+            window.draw(overlay);
+        }
+
+        // The solver draws the path it found, so a straightened one goes over it in another color
+        if let Some(path) = run.smoothed_solution() {
+            draw_path(
+                &mut *window,
+                &RenderStates::DEFAULT,
+                path,
+                Some(theme().frontier),
+            );
+        }
+
+        if let Some(shifter) = run.shifter() {
+            window.draw(shifter);
+        }
+
+        if show_minimap && camera.is_zoomed() {
+            minimap.refresh(&renderer);
+            minimap.draw(&mut *window, overlay, &camera);
+        }
+
+        let before = tweaks;
+        panel.show(&mut *window, &mut tweaks);
+
+        window.display();
+
+        if tweaks.speed != before.speed {
+            clock = StepClock::new(tweaks.speed);
+        }
+        if (tweaks.theme, tweaks.accessible) != (before.theme, before.accessible) {
+            set_theme(tweaks.theme.theme(tweaks.accessible));
+
+            // The renderer keeps the theme it was made with
+            renderer = MazeRenderer::new(&mut run.maze);
+            tinted = Tint::default();
+            minimap.invalidate();
+        }
+        if tweaks.heuristic_weight != before.heuristic_weight {
+            run.set_heuristic_weight(tweaks.heuristic_weight);
+        }
+    }
+
+    remember_position(&window);
+}
+
+/// Direction of the first movement key held down, arrow keys or WASD
+fn held_direction() -> Option<Direction> {
+    [
+        (Key::Up, Key::W, Direction::UP),
+        (Key::Right, Key::D, Direction::RIGHT),
+        (Key::Down, Key::S, Direction::DOWN),
+        (Key::Left, Key::A, Direction::LEFT),
+    ]
+    .into_iter()
+    .find(|(arrow, letter, _)| arrow.is_pressed() || letter.is_pressed())
+    .map(|(_, _, direction)| direction)
+}
+
+/// Opens a window walking an endless maze from its origin, one cell per step while a movement key
+/// is held. The chunks up to `radius` chunks around the player are copied into one maze for the
+/// renderer, which is made again whenever the player enters another chunk.
+pub fn explore(maze: &mut InfiniteMaze, radius: usize, args: &WindowArgs) {
+    set_theme(args.theme());
+    set_wall_width(args.wall_width());
+
+    if let Some(size) = args.cell_size() {
+        set_cell_size(size);
+    }
+
+    let cell_size = get_cell_size() as f32;
+    let (width, height) = screen_size();
+    let screen_size = width.min(height).floor();
+
+    let mut window = RenderWindow::new(
+        VideoMode::new(screen_size as u32, screen_size as u32, 32),
+        "Maze",
+        Style::CLOSE,
+        &ContextSettings::default(),
+    )
+    .unwrap();
+    place_window(&mut window, args);
+
+    if args.vsync {
+        window.set_vertical_sync_enabled(true);
+    } else {
+        window.set_framerate_limit(FRAMERATE_LIMIT);
+    }
+
+    let mut clock = StepClock::new(args.speed);
+
+    let mut player = (0, 0);
+    let mut chunk = InfiniteMaze::chunk_of(player);
+    let (mut loaded, mut origin) = maze.window(chunk, radius);
+    let mut renderer = MazeRenderer::new(&mut loaded);
+    let mut fog = args
+        .fog
+        .map(|steps| Fog::new(loaded.get_bounds(), steps as usize));
+
+    let mut view = View::new((0., 0.).into(), (screen_size, screen_size).into()).unwrap();
+
+    let mut marker = CircleShape::new(cell_size / 3., 16);
+    marker.set_origin((cell_size / 3., cell_size / 3.));
+    marker.set_fill_color(Color::RED);
+
+    'mainloop: loop {
+        while let Some(ev) = window.poll_event() {
+            match ev {
+                Event::Closed => break 'mainloop,
+                Event::KeyPressed { code, ctrl, .. }
+                    if code == Key::Q || (code == Key::C && ctrl) =>
+                {
+                    break 'mainloop;
+                }
+                _ => {}
+            }
+        }
+
+        let steps = clock.due();
+        if let Some(direction) = held_direction() {
+            for _ in 0..steps {
+                match maze.travel(player, direction) {
+                    Some(next) => player = next,
+                    None => break,
+                }
+            }
+        }
+
+        if InfiniteMaze::chunk_of(player) != chunk {
+            let old_origin = origin;
+
+            chunk = InfiniteMaze::chunk_of(player);
+            (loaded, origin) = maze.window(chunk, radius);
+            renderer = MazeRenderer::new(&mut loaded);
+
+            if let Some(fog) = &mut fog {
+                let offset = (old_origin.0 - origin.0, old_origin.1 - origin.1);
+                *fog = fog.shifted(loaded.get_bounds(), offset);
+            }
+        }
+
+        renderer.update(&mut loaded);
+
+        if let Some(fog) = &mut fog {
+            let at = (
+                (player.0 - origin.0) as usize,
+                (player.1 - origin.1) as usize,
+            );
+
+            if fog.look(&loaded, at) {
+                renderer.set_colors(&loaded, Some(fog.dim(None)));
+            }
+        }
+
+        let position = (
+            ((player.0 - origin.0) as f32 + 0.5) * cell_size,
+            ((player.1 - origin.1) as f32 + 0.5) * cell_size,
+        );
+        view.set_center(position);
+        marker.set_position(position);
+
+        window.clear(theme().wall);
+        window.set_view(&view);
+
+        window.draw(&renderer);
+        window.draw(&marker);
+
+        window.display();
+    }
+
+    remember_position(&window);
+}
+
+/// Cells of the shortest route a hint shows, past the player's own
+const HINT_CELLS: usize = 5;
+/// How long a hint stays on screen
+const HINT_TIME: Duration = Duration::from_millis(1500);
+/// Points a hint takes off the score, out of the 1000 for walking the shortest route
+const HINT_COST: u32 = 100;
+
+/// Out of 1000, for walking a route of `moves` when the shortest is `shortest` moves long, less
+/// [`HINT_COST`] per hint
+fn score(shortest: usize, moves: usize, hints: u32) -> u32 {
+    let walked = (1000 * shortest).checked_div(moves).unwrap_or(1000) as u32;
+
+    walked.saturating_sub(hints * HINT_COST)
+}
+
+/// Opens a window for walking the maze from the top left to the bottom right corner, one cell per
+/// step while a movement key is held. H briefly shows the next cells of the shortest route from
+/// the player once a worker thread has found it, and the score printed at the end counts the hints
+/// used against the moves made.
+pub fn play(maze: &Maze, args: &WindowArgs) -> Result<(), String> {
+    let bounds = maze.get_bounds();
+    let end = (bounds.0 - 1, bounds.1 - 1);
+    let shortest = solvers::a_star(maze, (0, 0), end)
+        .ok_or_else(|| String::from("The end can not be reached from the start"))?
+        .len()
+        - 1;
+
+    set_theme(args.theme());
+    set_wall_width(args.wall_width());
+
+    let world_size = maze_world_size(maze);
+    let window_size = fit_to_screen(world_size);
+    let cell_size = get_cell_size() as f32;
+
+    let mut window = RenderWindow::new(
+        VideoMode::new(window_size.0 as u32, window_size.1 as u32, 32),
+        window_title(maze).as_str(),
+        Style::CLOSE,
+        &ContextSettings::default(),
+    )
+    .unwrap();
+    place_window(&mut window, args);
+
+    if args.vsync {
+        window.set_vertical_sync_enabled(true);
+    } else {
+        window.set_framerate_limit(FRAMERATE_LIMIT);
+    }
+
+    let mut clock = StepClock::new(args.speed);
+    // The renderer takes the cells it has drawn off a maze of its own
+    let mut drawn = maze.clone();
+    let mut renderer = MazeRenderer::new(&mut drawn);
+    let camera = Camera::new(world_size, window_size);
+
+    let mut marker = CircleShape::new(cell_size / 3., 16);
+    marker.set_origin((cell_size / 3., cell_size / 3.));
+
+    // Hints are searched for on a worker thread, so a huge maze does not stall the window
+    let hint_maze = Arc::new(maze.clone());
+    let search = |from: (usize, usize)| {
+        let (sender, receiver) = channel();
+        let maze = Arc::clone(&hint_maze);
+
+        thread::spawn(move || {
+            let _ = sender.send(solvers::a_star(&maze, from, end).unwrap_or_default());
+        });
+
+        receiver
+    };
+
+    let start = Instant::now();
+    let mut player = (0, 0);
+    let mut moves = 0;
+    let mut hints = 0;
+    // The route shown by the last hint and when it was found
+    let mut hint: Option<(Vec<(usize, usize)>, Instant)> = None;
+    let mut searching: Option<Receiver<Vec<(usize, usize)>>> = None;
+    let mut finished = player == end;
+    let mut title = String::new();
+
+    'mainloop: loop {
+        while let Some(ev) = window.poll_event() {
+            match ev {
+                Event::Closed => break 'mainloop,
+                Event::KeyPressed { code, ctrl, .. }
+                    if code == Key::Q || (code == Key::C && ctrl) =>
+                {
+                    break 'mainloop;
+                }
+                Event::KeyPressed { code: Key::H, .. } if !finished && searching.is_none() => {
+                    searching = Some(search(player));
+                    hints += 1;
+                }
+                _ => {}
+            }
+        }
+
+        let found = searching.as_ref().map(Receiver::try_recv);
+        match found {
+            Some(Ok(route)) => match route.iter().position(|&cell| cell == player) {
+                // The player may have walked on along the route while it was searched for
+                Some(past) => {
+                    hint = Some((
+                        route.into_iter().skip(past + 1).take(HINT_CELLS).collect(),
+                        Instant::now(),
+                    ));
+                    searching = None;
+                }
+                // Or off it, so it is searched for again from where they are now
+                None if !route.is_empty() => searching = Some(search(player)),
+                None => searching = None,
+            },
+            Some(Err(TryRecvError::Disconnected)) => searching = None,
+            Some(Err(TryRecvError::Empty)) | None => {}
+        }
+
+        let steps = clock.due();
+        if let Some(direction) = held_direction().filter(|_| !finished) {
+            for _ in 0..steps {
+                let next = direction
+                    .travel(player.0, player.1)
+                    .filter(|&(x, y)| x < bounds.0 && y < bounds.1)
+                    .filter(|_| maze.get(player.0, player.1).contains(direction));
+
+                let Some(next) = next else {
+                    break;
+                };
+                player = next;
+                moves += 1;
+
+                if player == end {
+                    finished = true;
+                    println!(
+                        "Reached the end in {moves} moves and {:.1?} with {hints} hints, scoring {}",
+                        start.elapsed(),
+                        score(shortest, moves, hints)
+                    );
+                    break;
+                }
+            }
+        }
+
+        let status = match finished {
+            true => format!("score {}", score(shortest, moves, hints)),
+            false => format!("{moves} moves, {hints} hints, H for a hint"),
+        };
+        let status = format!("{} - {status}", window_title(maze));
+        if status != title {
+            window.set_title(&status);
+            title = status;
+        }
+
+        renderer.update(&mut drawn);
+
+        window.clear(theme().wall);
+        window.set_view(camera.view());
+        window.draw(&renderer);
+        window.draw(&TagMarkers(maze));
+
+        if let Some((route, _)) = hint
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < HINT_TIME)
+        {
+            draw_marks(
+                &mut *window,
+                &RenderStates::DEFAULT,
+                route.iter().copied(),
+                Mark::Frontier,
+                false,
+            );
+        }
+
+        for (cell, color) in [(end, Color::GREEN), (player, Color::RED)] {
+            marker.set_fill_color(color);
+            marker.set_position((
+                (cell.0 as f32 + 0.5) * cell_size,
+                (cell.1 as f32 + 0.5) * cell_size,
+            ));
+            window.draw(&marker);
+        }
+
+        window.display();
+    }
+
+    remember_position(&window);
+
+    Ok(())
+}
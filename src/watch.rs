@@ -0,0 +1,47 @@
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the file is checked, so polling stays cheap at high frame rates
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls a file's modification time
+pub struct FileWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl FileWatcher {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            modified: modified(path),
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns `true` once per change. A file that is missing, e.g. while another program
+    /// replaces it, is not a change.
+    pub fn changed(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        match modified(&self.path) {
+            Some(time) if Some(time) != self.modified => {
+                self.modified = Some(time);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|v| v.modified()).ok()
+}